@@ -0,0 +1,279 @@
+//! Arithmetic with rounding after every operation, in `f32`, `f64` or a
+//! [`SoftFloatSpec`].
+//!
+//! Soft float results are correctly rounded under the rounding of
+//! [`SoftFloatSpec::quantize`] (ties away from zero, no subnormals): each
+//! operation is computed as an `f64` plus its exact error (see
+//! [`crate::double_double`]), and the error only matters for breaking ties
+//! that the `f64` result lands on. [`crate::verify`] checks this exhaustively.
+//!
+//! The `*_flags` variants also report the IEEE 754 exception flags of each
+//! operation, for every format. For `f64` the error term is itself rounded
+//! below the smallest normal, so flags of tiny `f64` results are approximate.
+
+use crate::double_double::{two_prod, two_sum, DoubleDouble};
+use crate::soft_float::{Flags, SoftFloatSpec, Tininess};
+
+/// A format whose operations round: the hardware types or a soft float spec.
+#[derive(Clone, Debug)]
+pub enum Format {
+    F32,
+    F64,
+    Soft(SoftFloatSpec),
+}
+
+impl Format {
+    pub fn name(&self) -> &str {
+        match self {
+            Format::F32 => "f32",
+            Format::F64 => "f64",
+            Format::Soft(spec) => &spec.name,
+        }
+    }
+
+    /// Significand precision including the hidden bit.
+    pub fn precision(&self) -> u32 {
+        match self {
+            Format::F32 => f32::MANTISSA_DIGITS,
+            Format::F64 => f64::MANTISSA_DIGITS,
+            Format::Soft(spec) => spec.mantissa_bits + 1,
+        }
+    }
+
+    /// Smallest positive normal value.
+    pub fn min_normal(&self) -> f64 {
+        match self {
+            Format::F32 => f32::MIN_POSITIVE as f64,
+            Format::F64 => f64::MIN_POSITIVE,
+            Format::Soft(spec) => spec.min_normal(),
+        }
+    }
+
+    /// Spacing of the format at `|x|`; `None` for zero, non-finite or out-of-range values.
+    pub fn ulp(&self, x: f64) -> Option<f64> {
+        let ax = x.abs();
+        if !(ax.is_finite() && ax > 0.0) {
+            return None;
+        }
+        match self {
+            Format::F32 => {
+                let v = ax as f32;
+                Some((v.next_up() - v) as f64)
+            }
+            Format::F64 => Some(ax.next_up() - ax),
+            Format::Soft(spec) => spec.ulp_near(ax),
+        }
+    }
+
+    /// Rounds an `f64` into the format.
+    pub fn round(&self, x: f64) -> f64 {
+        match self {
+            Format::F32 => x as f32 as f64,
+            Format::F64 => x,
+            Format::Soft(spec) => spec.quantize(x),
+        }
+    }
+
+    /// Rounds the exact value `hi + lo` (with `|lo|` below half an ULP of `hi`)
+    /// into the format, once.
+    pub fn round_exact(&self, hi: f64, lo: f64) -> f64 {
+        let q = self.round(hi);
+        if lo == 0.0 || !q.is_finite() || q == hi || matches!(self, Format::F64) {
+            return q;
+        }
+        if q == 0.0 && matches!(self, Format::Soft(_)) {
+            // Flushed: everything below the smallest normal goes to zero.
+            return q;
+        }
+        // Rounding `hi` instead of `hi + lo` only goes wrong when `hi` is an
+        // exact tie, i.e. the mirror image of `q` is representable too; the
+        // sign of `lo` then says which side the exact value is on.
+        let other = 2.0 * hi - q;
+        let toward_q = (lo > 0.0) == (q > hi);
+        if !toward_q && other.is_finite() && self.round(other) == other {
+            other
+        } else {
+            q
+        }
+    }
+
+    /// [`Self::round_exact`] with the flags it raises: inexact, underflow (a
+    /// tiny result that is also inexact) and overflow. `tininess` only
+    /// decides whether underflow is raised; the value is that of `round_exact`.
+    pub fn round_exact_flags(&self, hi: f64, lo: f64, tininess: Tininess) -> (f64, Flags) {
+        let q = self.round_exact(hi, lo);
+        if !hi.is_finite() || (q == hi && lo == 0.0) {
+            return (q, Flags::NONE);
+        }
+        if q.is_infinite() {
+            return (q, Flags::OVERFLOW | Flags::INEXACT);
+        }
+        let min_normal = self.min_normal();
+        if below(hi, lo, min_normal) {
+            let halfway = min_normal - min_normal * 2f64.powi(-(self.precision() as i32) - 1);
+            if tininess == Tininess::AfterRounding && !below(hi, lo, halfway) {
+                return (q, Flags::INEXACT);
+            }
+            return (q, Flags::UNDERFLOW | Flags::INEXACT);
+        }
+        (q, Flags::INEXACT)
+    }
+
+    /// Flags of an operation on `inputs` whose exact result is `hi + lo`.
+    /// Operations on infinities are exact; NaN inputs are quiet and raise nothing.
+    fn finish(&self, inputs: &[f64], hi: f64, lo: f64, tininess: Tininess) -> (f64, Flags) {
+        if inputs.iter().any(|x| x.is_nan()) {
+            return (f64::NAN, Flags::NONE);
+        }
+        if hi.is_nan() {
+            return (f64::NAN, Flags::INVALID);
+        }
+        if inputs.iter().any(|x| x.is_infinite()) {
+            return (self.round(hi), Flags::NONE);
+        }
+        if hi.is_infinite() {
+            // Beyond the f64 range, so beyond every format's.
+            return (hi, Flags::OVERFLOW | Flags::INEXACT);
+        }
+        self.round_exact_flags(hi, lo, tininess)
+    }
+
+    /// Rounds a reference value into the format.
+    pub fn round_reference(&self, x: DoubleDouble) -> f64 {
+        self.round_exact(x.hi, x.lo)
+    }
+
+    pub fn add(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Format::F32 => (a as f32 + b as f32) as f64,
+            Format::F64 => a + b,
+            Format::Soft(_) => {
+                let (s, e) = two_sum(a, b);
+                self.round_exact(s, e)
+            }
+        }
+    }
+
+    pub fn sub(&self, a: f64, b: f64) -> f64 {
+        self.add(a, -b)
+    }
+
+    pub fn mul(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Format::F32 => (a as f32 * b as f32) as f64,
+            Format::F64 => a * b,
+            Format::Soft(_) => {
+                let (p, e) = two_prod(a, b);
+                self.round_exact(p, e)
+            }
+        }
+    }
+
+    pub fn div(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Format::F32 => (a as f32 / b as f32) as f64,
+            Format::F64 => a / b,
+            Format::Soft(_) => {
+                let (q, r) = div_exact(a, b);
+                self.round_exact(q, r)
+            }
+        }
+    }
+
+    pub fn sqrt(&self, a: f64) -> f64 {
+        match self {
+            Format::F32 => (a as f32).sqrt() as f64,
+            Format::F64 => a.sqrt(),
+            Format::Soft(_) => {
+                let (s, r) = sqrt_exact(a);
+                self.round_exact(s, r)
+            }
+        }
+    }
+
+    /// `a * b + c` with a single rounding. For soft formats the result is
+    /// exact whenever `a * b` fits in an `f64`, as it does up to 26 bits of precision.
+    pub fn fma(&self, a: f64, b: f64, c: f64) -> f64 {
+        match self {
+            Format::F32 => (a as f32).mul_add(b as f32, c as f32) as f64,
+            Format::F64 => a.mul_add(b, c),
+            Format::Soft(_) => {
+                let (hi, lo) = fma_exact(a, b, c);
+                self.round_exact(hi, lo)
+            }
+        }
+    }
+
+    pub fn add_flags(&self, a: f64, b: f64, tininess: Tininess) -> (f64, Flags) {
+        let (s, e) = two_sum(a, b);
+        self.finish(&[a, b], s, e, tininess)
+    }
+
+    pub fn sub_flags(&self, a: f64, b: f64, tininess: Tininess) -> (f64, Flags) {
+        self.add_flags(a, -b, tininess)
+    }
+
+    pub fn mul_flags(&self, a: f64, b: f64, tininess: Tininess) -> (f64, Flags) {
+        let (p, e) = two_prod(a, b);
+        self.finish(&[a, b], p, e, tininess)
+    }
+
+    pub fn div_flags(&self, a: f64, b: f64, tininess: Tininess) -> (f64, Flags) {
+        if b == 0.0 && a.is_finite() && a != 0.0 {
+            return (a / b, Flags::DIV_BY_ZERO);
+        }
+        let (q, r) = div_exact(a, b);
+        self.finish(&[a, b], q, r, tininess)
+    }
+
+    pub fn sqrt_flags(&self, a: f64, tininess: Tininess) -> (f64, Flags) {
+        let (s, r) = sqrt_exact(a);
+        self.finish(&[a], s, r, tininess)
+    }
+
+    pub fn fma_flags(&self, a: f64, b: f64, c: f64, tininess: Tininess) -> (f64, Flags) {
+        let (hi, lo) = fma_exact(a, b, c);
+        self.finish(&[a, b, c], hi, lo, tininess)
+    }
+}
+
+/// `|hi + lo| < t`, for `|lo|` below half an ULP of `hi`.
+fn below(hi: f64, lo: f64, t: f64) -> bool {
+    hi.abs() < t || (hi.abs() == t && lo != 0.0 && (lo < 0.0) != (hi < 0.0))
+}
+
+/// `a / b` and the remainder; only the sign of the remainder is needed to break ties.
+fn div_exact(a: f64, b: f64) -> (f64, f64) {
+    let q = a / b;
+    let r = if q.is_finite() {
+        (-q).mul_add(b, a)
+    } else {
+        0.0
+    };
+    (q, r * b.signum())
+}
+
+fn sqrt_exact(a: f64) -> (f64, f64) {
+    let s = a.sqrt();
+    let r = if s.is_finite() {
+        (-s).mul_add(s, a)
+    } else {
+        0.0
+    };
+    (s, r)
+}
+
+/// `a * b + c` as `hi + lo`, exact whenever `a * b` fits in an `f64`.
+fn fma_exact(a: f64, b: f64, c: f64) -> (f64, f64) {
+    if !(a.is_finite() && b.is_finite() && c.is_finite()) {
+        return (a.mul_add(b, c), 0.0);
+    }
+    let (p, e) = two_prod(a, b);
+    let (s, t) = two_sum(p, c);
+    let (hi, lo) = two_sum(s, t + e);
+    if hi == 0.0 && lo == 0.0 {
+        // Exact zero: `p + c` has the IEEE sign (-0 only for -0 + -0).
+        return (p + c, 0.0);
+    }
+    (hi, lo)
+}
@@ -0,0 +1,388 @@
+use prectest::plot::{
+    palette_color, Axis, Band, Figure, Legend, LegendLayout, Panel, PointLabel, Rect, Series,
+    Style, Ticks,
+};
+use prectest::cli::{parse_format_arg, Global};
+use prectest::soft_float::{preset, preset_names, SoftFloatSpec};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+const INPUT_COLOR: &str = "#ef6c00";
+const CLIP_BAND: &str = "#fdecea";
+const BINADE_BAND: &str = "#f3f3f3";
+
+#[derive(Debug)]
+struct Config {
+    formats: Vec<SoftFloatSpec>,
+    inputs: Vec<f64>,
+    linear_max: Option<f64>,
+    linear: bool,
+    log: bool,
+    max_values: u64,
+    out: String,
+    png: bool,
+    dpi: f64,
+}
+
+fn parse_args(args: Vec<String>, global: &Global) -> Result<Config, String> {
+    let mut config = Config {
+        formats: Vec::new(),
+        inputs: Vec::new(),
+        linear_max: None,
+        linear: true,
+        log: true,
+        max_values: 20_000,
+        out: global.output("docs/number_line.svg"),
+        png: false,
+        dpi: 96.0,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Err(help_text());
+        }
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{arg} requires a value"))
+        };
+        match arg.as_str() {
+            "--preset" => {
+                let name = value()?;
+                let spec = preset(&name).ok_or_else(|| {
+                    format!("unknown preset '{name}' (known: {})", preset_names().join(", "))
+                })?;
+                config.formats.push(spec);
+            }
+            "--format" => config.formats.push(parse_format_arg(&value()?)?),
+            "--inputs" => {
+                let v = value()?;
+                for part in v.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                    let x = part
+                        .parse::<f64>()
+                        .map_err(|e| format!("invalid --inputs value '{part}': {e}"))?;
+                    if !(x.is_finite() && x > 0.0) {
+                        return Err(format!(
+                            "--inputs values must be positive and finite, got '{part}'"
+                        ));
+                    }
+                    config.inputs.push(x);
+                }
+            }
+            "--linear-max" => {
+                let v = value()?;
+                let x = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --linear-max '{v}': {e}"))?;
+                if !(x.is_finite() && x > 0.0) {
+                    return Err("--linear-max must be positive".to_string());
+                }
+                config.linear_max = Some(x);
+            }
+            "--views" => {
+                let v = value()?;
+                config.linear = false;
+                config.log = false;
+                for view in v.split(',').map(str::trim) {
+                    match view {
+                        "linear" => config.linear = true,
+                        "log" => config.log = true,
+                        _ => {
+                            return Err(format!(
+                                "invalid --views entry '{view}', expected linear and/or log"
+                            ))
+                        }
+                    }
+                }
+            }
+            "--max-values" => {
+                let v = value()?;
+                config.max_values = v
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid --max-values '{v}': {e}"))?;
+            }
+            "--out" => config.out = value()?,
+            "--png" => config.png = true,
+            "--dpi" => {
+                let v = value()?;
+                config.dpi = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --dpi '{v}': {e}"))?;
+                config.png = true;
+            }
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+
+    if config.formats.is_empty() {
+        config.formats = ["tiny8", "fp8_e4m3"]
+            .iter()
+            .filter_map(|name| preset(name))
+            .collect();
+    }
+    if !(config.dpi.is_finite() && (24.0..=1200.0).contains(&config.dpi)) {
+        return Err("--dpi must be in [24, 1200]".to_string());
+    }
+    if !(config.linear || config.log) {
+        return Err("--views must name at least one view".to_string());
+    }
+    for spec in &config.formats {
+        if spec.positive_count() > config.max_values {
+            return Err(format!(
+                "{} has {} positive values, more than --max-values {}; pick a smaller format",
+                spec.name,
+                spec.positive_count(),
+                config.max_values
+            ));
+        }
+    }
+    Ok(config)
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  cargo run --bin number_line -- [options]",
+        "",
+        "Options:",
+        "  --preset <name>                Add a preset format (repeatable), e.g. tiny8, fp8_e4m3, fp8_e5m2",
+        "  --format <preset|name,m,min_e,max_e>  Add a format (repeatable)",
+        "  --inputs x1,x2,...             Mark positive inputs and where they round to",
+        "  --linear-max <f64>             Right end of the linear view, default: each format's max finite",
+        "  --views linear,log             Views per format, default: both",
+        "  --max-values <n>               Refuse formats with more positive values, default: 20000",
+        "  --out <path.svg>               Default: docs/number_line.svg (or in --out-dir)",
+        "  --png                          Also write the same path with .png (built-in rasterizer)",
+        "  --dpi <f64>                    PNG resolution, 96 = one pixel per SVG unit; default: 96 (implies --png)",
+        "",
+        "Without --preset or --format the figure shows tiny8 and fp8_e4m3.",
+        "Shared options: --out-dir <dir>, --quiet/-q (see `prectest help`).",
+        "",
+        "Examples:",
+        "  cargo run --bin number_line",
+        "  cargo run --bin number_line -- --preset tiny8 --inputs 0.01,0.3,3.3,17,250 --linear-max 20",
+        "  cargo run --bin number_line -- --format toy,2,-2,3 --views linear",
+    ]
+    .join("\n")
+}
+
+fn short(v: f64) -> String {
+    if v != 0.0 && v.is_finite() && !(1e-4..1e5).contains(&v.abs()) {
+        format!("{v:e}")
+    } else {
+        format!("{v}")
+    }
+}
+
+/// Alternating shading per binade `[2^e, 2^(e+1))`; skipped when there are too many to see.
+fn binade_bands(spec: &SoftFloatSpec) -> Vec<Band> {
+    if spec.max_exp2 - spec.min_exp2 > 64 {
+        return Vec::new();
+    }
+    (spec.min_exp2..=spec.max_exp2)
+        .filter(|e| (e - spec.min_exp2) % 2 == 1)
+        .map(|e| Band {
+            min: 2f64.powi(e),
+            max: 2f64.powi(e + 1),
+            color: BINADE_BAND.to_string(),
+            label: None,
+        })
+        .collect()
+}
+
+fn number_line_panel(
+    config: &Config,
+    spec: &SoftFloatSpec,
+    color: &str,
+    log: bool,
+    rect: Rect,
+) -> Panel {
+    let values = spec.positive_values(config.max_values).unwrap_or_default();
+    let min_normal = spec.min_normal();
+    let max_finite = spec.max_finite();
+
+    let (x_axis, lo, hi) = if log {
+        let (lo, hi) = (min_normal / 4.0, max_finite * 4.0);
+        let binades = (spec.max_exp2 - spec.min_exp2 + 1).max(1);
+        let stride = (binades as usize).div_ceil(16).max(1) as i32;
+        let labels = (spec.min_exp2..=spec.max_exp2 + 1)
+            .filter(|e| (e - spec.min_exp2) % stride == 0)
+            .map(|e| (2f64.powi(e), format!("2^{e}")))
+            .collect();
+        (
+            Axis::log10("x (log scale, one shaded strip per binade)", lo, hi)
+                .ticks(Ticks::Labeled(labels)),
+            lo,
+            hi,
+        )
+    } else {
+        let hi = config.linear_max.unwrap_or(max_finite);
+        (Axis::linear("x (linear scale)", 0.0, hi).ticks(Ticks::Auto(10)), 0.0, hi)
+    };
+
+    let view = if log { "log scale" } else { "linear scale" };
+    let mut panel = Panel::new(rect, x_axis, Axis::linear("", 0.0, 1.0).hidden()).caption(
+        format!(
+            "{}: {} positive values, m={}, e=[{},{}] ({view})",
+            spec.name,
+            values.len(),
+            spec.mantissa_bits,
+            spec.min_exp2,
+            spec.max_exp2
+        ),
+    );
+
+    // Gap below the smallest normal: this model has no subnormals, so it flushes to zero.
+    let flush_label = (log || min_normal / hi > 0.15).then(|| "flushes to 0".to_string());
+    panel.bands.push(Band {
+        min: lo,
+        max: min_normal,
+        color: CLIP_BAND.to_string(),
+        label: flush_label,
+    });
+    panel.bands.extend(binade_bands(spec));
+    if max_finite < hi {
+        panel.bands.push(Band {
+            min: max_finite,
+            max: hi,
+            color: CLIP_BAND.to_string(),
+            label: Some("overflows to inf".to_string()),
+        });
+    }
+
+    let visible: Vec<(f64, f64)> = values
+        .iter()
+        .filter(|&&v| v <= hi)
+        .map(|&v| (v, 0.35))
+        .collect();
+    panel.series.push(Series {
+        style: Style::Ticks { half_height: 9.0 },
+        ..Series::line(&spec.name, color, visible).width(1.2)
+    });
+    let binade_starts: Vec<(f64, f64)> = (spec.min_exp2..=spec.max_exp2)
+        .map(|e| (2f64.powi(e), 0.35))
+        .filter(|&(v, _)| v <= hi)
+        .collect();
+    panel.series.push(Series {
+        style: Style::Ticks { half_height: 16.0 },
+        ..Series::line("binade start", "#222", binade_starts).width(1.5)
+    });
+
+    // Alternate label heights so neighbouring inputs do not overprint each other.
+    let mut inputs: Vec<f64> = config.inputs.iter().copied().filter(|&x| x <= hi).collect();
+    inputs.sort_by(f64::total_cmp);
+    for (i, &x) in inputs.iter().enumerate() {
+        let y = if i % 2 == 0 { 0.85 } else { 0.62 };
+        let q = spec.quantize(x);
+        // Zero and inf have no place on the axis; point at the matching edge instead.
+        let target = if q == 0.0 && log {
+            lo
+        } else if q.is_infinite() {
+            hi
+        } else {
+            q
+        };
+        panel.series.push(
+            Series::line("input", INPUT_COLOR, vec![(x, y), (target, 0.45)]).width(1.5),
+        );
+        panel.series.push(Series::scatter(
+            "input",
+            INPUT_COLOR,
+            vec![(x, y)],
+            3.5,
+            true,
+        ));
+        panel.labels.push(PointLabel {
+            x,
+            y,
+            dx: 5.0,
+            dy: -4.0,
+            text: format!("{} → {}", short(x), short(q)),
+            size: 11.0,
+            bold: false,
+            halo: false,
+        });
+    }
+    panel
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let parsed = Global::extract(env::args().skip(1).collect())
+        .and_then(|(global, args)| Ok((parse_args(args, &global)?, global)));
+    let (config, global) = match parsed {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    let output = Path::new(&config.out);
+    if let Some(parent) = output.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    let width = 1400.0;
+    let left = 40.0;
+    let right = 40.0;
+    let top = 110.0;
+    let panel_h = 70.0;
+    let gap = 100.0;
+
+    let mut views = Vec::new();
+    for (idx, spec) in config.formats.iter().enumerate() {
+        if config.linear {
+            views.push((idx, spec, false));
+        }
+        if config.log {
+            views.push((idx, spec, true));
+        }
+    }
+    let n = views.len() as f64;
+    let height = top + n * panel_h + (n - 1.0) * gap + 70.0;
+    let rects = Rect::new(left, top, width - right, height - 70.0).grid(views.len(), 1, 0.0, gap);
+
+    let panels = views
+        .into_iter()
+        .zip(rects)
+        .map(|((idx, spec, log), rect)| {
+            number_line_panel(&config, spec, palette_color(idx), log, rect)
+        })
+        .collect();
+
+    let mut entries = vec![("binade start 2^e".to_string(), "#222".to_string())];
+    if !config.inputs.is_empty() {
+        entries.push(("input → rounded".to_string(), INPUT_COLOR.to_string()));
+    }
+    let figure = Figure {
+        title_size: 28.0,
+        subtitle: Some(
+            "Every positive finite value as a tick; negatives mirror them. Red strips: below the smallest normal (flush to zero) and past the largest finite value."
+                .to_string(),
+        ),
+        panels,
+        legend: Some(Legend {
+            layout: LegendLayout::Row {
+                x: width - right - 420.0,
+                y: 94.0,
+                spacing: 210.0,
+            },
+            font_size: 14.0,
+            entries,
+        }),
+        ..Figure::new(width, height).title("Representable values of small float formats")
+    };
+
+    figure.write_svg(output)?;
+    global.wrote(output.display());
+    if config.png {
+        let png_path = output.with_extension("png");
+        figure.write_png(&png_path, config.dpi)?;
+        global.wrote(png_path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,424 @@
+//! Catastrophic cancellation: how many leading bits an addition or
+//! subtraction wipes out, and a wrapper type that reports it as it happens.
+//!
+//! When two nearby values are subtracted, the leading bits they share cancel
+//! and the result keeps only what was in their trailing bits, rounding errors
+//! included. The loss is measured as the larger operand's exponent minus the
+//! result's. It says nothing about whether the operands were exact (then the
+//! subtraction is harmless), only how much of any earlier error is magnified.
+//!
+//! [`Watched`] wraps `f32` or `f64` and checks every `+` and `-` against the
+//! thread's [`Policy`], so a test suite can run with the checks on:
+//!
+//! ```text
+//! PRECTEST_CANCELLATION=panic:20 cargo test
+//! ```
+
+use crate::arith::Format;
+use crate::soft_float::exponent_of;
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::panic::Location;
+
+/// The outcome of one addition in some format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cancellation {
+    pub result: f64,
+    /// Exponent of the larger-magnitude operand.
+    pub operand_exponent: i32,
+    /// Exponent of the result; `None` when it is zero.
+    pub result_exponent: Option<i32>,
+    /// Leading bits that cancelled; the whole precision when a nonzero sum
+    /// rounds to zero, none when the operands cancel exactly (`x - x`).
+    pub bits_lost: u32,
+    /// Significand bits of the result still backed by the operands.
+    pub bits_left: u32,
+}
+
+/// Analyzes `a + b` rounded in `format`. Use `-b` for a subtraction.
+///
+/// Zero or non-finite operands never count as cancellation, and neither does
+/// an exact zero such as `x - x`: nothing was left to lose.
+pub fn analyze_sum(format: &Format, a: f64, b: f64) -> Cancellation {
+    let result = format.add(a, b);
+    let precision = format.precision();
+    if a == 0.0 || b == 0.0 || !a.is_finite() || !b.is_finite() || !result.is_finite() {
+        let e = [a, b, result]
+            .into_iter()
+            .filter(|x| x.is_finite() && *x != 0.0)
+            .map(exponent_of)
+            .max()
+            .unwrap_or(0);
+        return Cancellation {
+            result,
+            operand_exponent: e,
+            result_exponent: (result != 0.0 && result.is_finite()).then(|| exponent_of(result)),
+            bits_lost: 0,
+            bits_left: precision,
+        };
+    }
+    let operand_exponent = exponent_of(a).max(exponent_of(b));
+    let (result_exponent, bits_lost) = if result == 0.0 {
+        (None, if a == -b { 0 } else { precision })
+    } else {
+        let e = exponent_of(result);
+        (Some(e), (operand_exponent - e).max(0) as u32)
+    };
+    Cancellation {
+        result,
+        operand_exponent,
+        result_exponent,
+        bits_lost,
+        bits_left: precision.saturating_sub(bits_lost),
+    }
+}
+
+/// Analyzes `a - b` rounded in `format`.
+pub fn analyze_difference(format: &Format, a: f64, b: f64) -> Cancellation {
+    analyze_sum(format, a, -b)
+}
+
+/// What [`Watched`] does when an operation cancels at least `threshold_bits`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Only count it (see [`stats`]).
+    Count,
+    /// Count it and print a line to stderr.
+    Log,
+    /// Panic at the operation's call site.
+    Panic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Policy {
+    pub action: Action,
+    /// `None` means half the type's precision (12 bits for `f32`, 26 for `f64`).
+    pub threshold_bits: Option<u32>,
+}
+
+impl Policy {
+    /// Parses `count`, `log` or `panic`, optionally followed by `:<bits>`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (action, bits) = match spec.trim().split_once(':') {
+            Some((a, b)) => (a, Some(b)),
+            None => (spec.trim(), None),
+        };
+        let action = match action {
+            "count" => Action::Count,
+            "log" => Action::Log,
+            "panic" => Action::Panic,
+            other => {
+                return Err(format!(
+                    "invalid cancellation action '{other}', expected count, log or panic"
+                ))
+            }
+        };
+        let threshold_bits = bits
+            .map(|b| {
+                b.trim()
+                    .parse::<u32>()
+                    .map_err(|e| format!("invalid cancellation threshold '{b}': {e}"))
+            })
+            .transpose()?;
+        Ok(Self {
+            action,
+            threshold_bits,
+        })
+    }
+
+    /// From `$PRECTEST_CANCELLATION`, counting silently when it is unset.
+    ///
+    /// Panics on an invalid value, so that a misspelled setting cannot turn
+    /// every check off without notice.
+    pub fn from_env() -> Self {
+        Policy::from_value(env::var("PRECTEST_CANCELLATION"))
+            .unwrap_or_else(|e| panic!("PRECTEST_CANCELLATION: {e}"))
+    }
+
+    /// The policy for a value read as by [`env::var`]; unset means count.
+    pub fn from_value(value: Result<String, env::VarError>) -> Result<Self, String> {
+        match value {
+            Ok(v) => Policy::parse(&v),
+            Err(env::VarError::NotPresent) => Ok(Policy {
+                action: Action::Count,
+                threshold_bits: None,
+            }),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Cancellations counted on this thread since the last [`reset_stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub checked: u64,
+    pub flagged: u64,
+    pub worst_bits: u32,
+    /// `file:line:column` of the operation that lost `worst_bits`.
+    pub worst_location: Option<String>,
+}
+
+thread_local! {
+    static POLICY: Cell<Policy> = Cell::new(Policy::from_env());
+    static STATS: RefCell<Stats> = RefCell::new(Stats::default());
+}
+
+/// Sets the policy for [`Watched`] operations on the current thread.
+pub fn set_policy(policy: Policy) {
+    POLICY.with(|p| p.set(policy));
+}
+
+pub fn policy() -> Policy {
+    POLICY.with(|p| p.get())
+}
+
+pub fn stats() -> Stats {
+    STATS.with(|s| s.borrow().clone())
+}
+
+pub fn reset_stats() {
+    STATS.with(|s| *s.borrow_mut() = Stats::default());
+}
+
+/// The primitive types [`Watched`] can wrap.
+pub trait Native:
+    Copy
+    + PartialOrd
+    + fmt::Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn format() -> Format;
+    fn to_f64(self) -> f64;
+}
+
+impl Native for f32 {
+    fn format() -> Format {
+        Format::F32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Native for f64 {
+    fn format() -> Format {
+        Format::F64
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+/// An `f32` or `f64` whose additions and subtractions are checked for
+/// cancellation under the thread's [`Policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Watched<T>(pub T);
+
+impl<T: Native> Watched<T> {
+    pub fn value(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Native> From<T> for Watched<T> {
+    fn from(x: T) -> Self {
+        Watched(x)
+    }
+}
+
+impl<T: Native> fmt::Display for Watched<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[track_caller]
+fn check<T: Native>(a: T, b: T, op: char) {
+    let format = T::format();
+    let (x, y) = (a.to_f64(), b.to_f64());
+    let c = if op == '-' {
+        analyze_difference(&format, x, y)
+    } else {
+        analyze_sum(&format, x, y)
+    };
+    let policy = policy();
+    let threshold = policy
+        .threshold_bits
+        .unwrap_or(format.precision() / 2)
+        .max(1);
+    let flagged = c.bits_lost >= threshold;
+    let location = Location::caller();
+    STATS.with(|s| {
+        let mut s = s.borrow_mut();
+        s.checked += 1;
+        if flagged {
+            s.flagged += 1;
+            if c.bits_lost > s.worst_bits {
+                s.worst_bits = c.bits_lost;
+                s.worst_location = Some(location.to_string());
+            }
+        }
+    });
+    if !flagged {
+        return;
+    }
+    let message = format!(
+        "cancellation: {a} {op} {b} = {} lost {} of {} bits ({})",
+        c.result,
+        c.bits_lost,
+        format.precision(),
+        location
+    );
+    match policy.action {
+        Action::Count => {}
+        Action::Log => eprintln!("{message}"),
+        Action::Panic => panic!("{message}"),
+    }
+}
+
+impl<T: Native> Add for Watched<T> {
+    type Output = Self;
+
+    #[track_caller]
+    fn add(self, rhs: Self) -> Self {
+        check(self.0, rhs.0, '+');
+        Watched(self.0 + rhs.0)
+    }
+}
+
+impl<T: Native> Sub for Watched<T> {
+    type Output = Self;
+
+    #[track_caller]
+    fn sub(self, rhs: Self) -> Self {
+        check(self.0, rhs.0, '-');
+        Watched(self.0 - rhs.0)
+    }
+}
+
+impl<T: Native> Add<T> for Watched<T> {
+    type Output = Self;
+
+    #[track_caller]
+    fn add(self, rhs: T) -> Self {
+        self + Watched(rhs)
+    }
+}
+
+impl<T: Native> Sub<T> for Watched<T> {
+    type Output = Self;
+
+    #[track_caller]
+    fn sub(self, rhs: T) -> Self {
+        self - Watched(rhs)
+    }
+}
+
+impl<T: Native> Mul for Watched<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Watched(self.0 * rhs.0)
+    }
+}
+
+impl<T: Native> Div for Watched<T> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Watched(self.0 / rhs.0)
+    }
+}
+
+impl<T: Native> Mul<T> for Watched<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        Watched(self.0 * rhs)
+    }
+}
+
+impl<T: Native> Div<T> for Watched<T> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self {
+        Watched(self.0 / rhs)
+    }
+}
+
+impl<T: Native> Neg for Watched<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Watched(-self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reset_stats, set_policy, stats, Action, Policy, Watched};
+    use std::env::VarError;
+    use std::ffi::OsString;
+
+    #[test]
+    fn parse_rejects_bad_settings() {
+        assert!(Policy::parse("panik:20").is_err());
+        assert!(Policy::parse("panic:x").is_err());
+        let policy = Policy::parse("panic:20").unwrap();
+        assert_eq!(policy.action, Action::Panic);
+        assert_eq!(policy.threshold_bits, Some(20));
+    }
+
+    #[test]
+    fn from_value_rejects_bad_settings() {
+        assert!(Policy::from_value(Ok("panik:20".to_string())).is_err());
+        assert!(Policy::from_value(Err(VarError::NotUnicode(OsString::from("x")))).is_err());
+        let policy = Policy::from_value(Err(VarError::NotPresent)).unwrap();
+        assert_eq!(policy.action, Action::Count);
+        assert_eq!(policy.threshold_bits, None);
+        let policy = Policy::from_value(Ok("log".to_string())).unwrap();
+        assert_eq!(policy.action, Action::Log);
+    }
+
+    fn watch(action: Action) {
+        set_policy(Policy {
+            action,
+            threshold_bits: Some(20),
+        });
+        reset_stats();
+    }
+
+    #[test]
+    fn watched_flags_only_real_cancellation() {
+        watch(Action::Count);
+        let x = Watched(1.0f64);
+        assert_eq!((x - Watched(0.5)).value(), 0.5);
+        assert_eq!((x - x).value(), 0.0);
+        assert_eq!((x + -1.0).value(), 0.0);
+        assert_eq!(stats().checked, 3);
+        assert_eq!(stats().flagged, 0);
+
+        let y = Watched(1.0 - 2f64.powi(-30));
+        assert_eq!((x - y).value(), 2f64.powi(-30));
+        let stats = stats();
+        assert_eq!((stats.checked, stats.flagged, stats.worst_bits), (4, 1, 30));
+        assert!(stats.worst_location.unwrap().contains("cancellation.rs"));
+    }
+
+    #[test]
+    #[should_panic(expected = "lost 30 of 53 bits")]
+    fn watched_panics_past_the_threshold() {
+        watch(Action::Panic);
+        let x = Watched(1.0f64);
+        let _ = x - x;
+        let _ = x - Watched(1.0 - 2f64.powi(-30));
+    }
+}
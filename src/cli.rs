@@ -0,0 +1,94 @@
+//! The `prectest` command line: subcommands and the options they share.
+//!
+//! Each subcommand module exposes `main(args, global)`, taking the arguments
+//! after the subcommand name. The standalone binaries (`precision_graph`,
+//! `soft_float_explorer`, `profile_float_demo`) call the same functions with
+//! default [`Global`] options.
+
+pub mod cycles;
+pub mod demo;
+pub mod eval;
+pub mod graph;
+pub mod inspect;
+pub mod iterate;
+pub mod libm;
+pub mod poly;
+pub mod profile;
+pub mod sweep;
+pub mod vectors;
+pub mod verify;
+
+use crate::arith::Format;
+use crate::soft_float::{parse_format, preset, preset_names, SoftFloatSpec};
+use std::fmt::Display;
+use std::path::Path;
+
+/// Options accepted by every subcommand, before or after its name.
+#[derive(Clone, Debug, Default)]
+pub struct Global {
+    /// Directory for default output files instead of `docs/`.
+    pub out_dir: Option<String>,
+    /// Suppress progress lines such as "Wrote <path>".
+    pub quiet: bool,
+}
+
+impl Global {
+    /// Pulls `--out-dir <dir>` and `--quiet`/`-q` out of `args`, leaving the rest in order.
+    pub fn extract(args: Vec<String>) -> Result<(Self, Vec<String>), String> {
+        let mut global = Global::default();
+        let mut rest = Vec::with_capacity(args.len());
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--out-dir" => {
+                    let dir = args
+                        .next()
+                        .ok_or_else(|| "--out-dir requires a value".to_string())?;
+                    global.out_dir = Some(dir);
+                }
+                "--quiet" | "-q" => global.quiet = true,
+                _ => rest.push(arg),
+            }
+        }
+        Ok((global, rest))
+    }
+
+    /// A default output path such as `docs/x.svg`, moved into `--out-dir` when given.
+    pub fn output(&self, default: &str) -> String {
+        match &self.out_dir {
+            Some(dir) => {
+                let name = Path::new(default).file_name().unwrap_or_default();
+                Path::new(dir).join(name).display().to_string()
+            }
+            None => default.to_string(),
+        }
+    }
+
+    pub fn wrote(&self, path: impl Display) {
+        if !self.quiet {
+            println!("Wrote {path}");
+        }
+    }
+}
+
+/// `--format` value shared by all subcommands: a preset name or `name,m,min_e,max_e`.
+pub fn parse_format_arg(spec: &str) -> Result<SoftFloatSpec, String> {
+    if spec.contains(',') {
+        return parse_format(spec);
+    }
+    preset(spec.trim()).ok_or_else(|| {
+        format!(
+            "unknown format '{spec}': expected a preset ({}) or name,m,min_e,max_e",
+            preset_names().join(", ")
+        )
+    })
+}
+
+/// Like [`parse_format_arg`], also accepting the hardware types `f32` and `f64`.
+pub fn parse_arith_format(spec: &str) -> Result<Format, String> {
+    match spec.trim() {
+        "f32" => Ok(Format::F32),
+        "f64" => Ok(Format::F64),
+        _ => parse_format_arg(spec).map(Format::Soft),
+    }
+}
@@ -0,0 +1,206 @@
+use crate::arith::Format;
+use crate::double_double::DoubleDouble;
+use crate::eval::{evaluate, parse_expr, Expr};
+use crate::fixed_point::{all_values, analyze, sample_values, Analysis};
+use std::error::Error;
+use super::{parse_arith_format, Global};
+
+/// Formats with at most this many values are enumerated instead of sampled.
+const EXHAUSTIVE_LIMIT: u64 = 1 << 21;
+
+struct Config {
+    text: String,
+    map: Expr,
+    formats: Vec<Format>,
+    samples: usize,
+    max_steps: u64,
+    top: usize,
+}
+
+fn parse_args(args: Vec<String>) -> Result<Config, String> {
+    let mut map: Option<(String, Expr)> = None;
+    let mut formats = Vec::new();
+    let mut samples = 100_000;
+    let mut max_steps = 10_000;
+    let mut top = 5;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.as_str() {
+            "--format" => formats.push(parse_arith_format(&value("--format")?)?),
+            "--samples" => {
+                let v = value("--samples")?;
+                samples = v
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid --samples '{v}'"))?;
+            }
+            "--max-steps" => {
+                let v = value("--max-steps")?;
+                max_steps = v
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid --max-steps '{v}'"))?;
+            }
+            "--top" => {
+                let v = value("--top")?;
+                top = v
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid --top '{v}'"))?;
+            }
+            "--help" | "-h" => return Err(help_text()),
+            _ if map.is_none() && !arg.starts_with("--") => {
+                let expr = parse_expr(&arg)?;
+                if let Some(other) = expr.variables().into_iter().find(|v| v != "x") {
+                    return Err(format!(
+                        "the map may only use the variable x, found '{other}'"
+                    ));
+                }
+                map = Some((arg, expr));
+            }
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    let (text, map) = map.ok_or_else(|| format!("missing map\n\n{}", help_text()))?;
+    if formats.is_empty() {
+        formats = vec![
+            parse_arith_format("tiny8")?,
+            parse_arith_format("fp16_like")?,
+        ];
+    }
+    Ok(Config {
+        text,
+        map,
+        formats,
+        samples,
+        max_steps,
+        top,
+    })
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest cycles <map in x> [options]",
+        "",
+        "Applies x -> map(x) repeatedly, every operation rounded into the format,",
+        "from every value of the format (or a sample for large formats), and reports",
+        "how many starts reach a fixed point, how many steps that takes, and any",
+        "longer cycles. Same expression syntax as `prectest eval`.",
+        "",
+        "Options:",
+        "  --format <f32|f64|preset|name,m,min_e,max_e>  Repeatable, default: tiny8 and fp16_like",
+        "  --samples <n>      Starts for formats with more than 2^21 values (default: 100000)",
+        "  --max-steps <n>    Give up on an orbit after this many steps (default: 10000)",
+        "  --top <n>          Cycles to list per format (default: 5)",
+        "",
+        "Examples:",
+        "  cargo run -- cycles \"(x / 3) * 3\"",
+        "  cargo run -- cycles \"sqrt(x * x)\" --format bf16_like",
+        "  cargo run -- cycles \"3.9 * x * (1 - x)\" --format tiny8 --format f32",
+    ]
+    .join("\n")
+}
+
+fn run(config: &Config, format: &Format) -> Analysis {
+    let (starts, exhaustive) = match format {
+        Format::Soft(spec) => match all_values(spec, EXHAUSTIVE_LIMIT) {
+            Some(values) => (values, true),
+            None => (sample_values(format, config.samples), false),
+        },
+        _ => (sample_values(format, config.samples), false),
+    };
+    let map = |x: f64| {
+        let vars = [("x".to_string(), DoubleDouble::from(x))];
+        evaluate(&config.map, &vars, format).map_or(f64::NAN, |e| e.value)
+    };
+    analyze(format, map, &starts, exhaustive, config.max_steps)
+}
+
+fn print_analysis(a: &Analysis, top: usize, max_steps: u64) {
+    let scope = if a.exhaustive { "all" } else { "sampled" };
+    println!("\n{} ({scope} {} values):", a.format.name(), a.inputs);
+    let share = |n: u64| 100.0 * n as f64 / a.inputs.max(1) as f64;
+    let rows = [
+        ("already fixed", a.already_fixed),
+        ("reach a fixed point", a.converged),
+        ("end in a longer cycle", a.cycling),
+        ("overflow to inf/NaN", a.escaped),
+    ];
+    for (label, n) in rows {
+        println!("  {label:<24}  {n:>9}  {:>6.2}%", share(n));
+    }
+    if a.unresolved > 0 {
+        println!(
+            "  {:<24}  {:>9}  {:>6.2}%",
+            format!("moving after {max_steps} steps"),
+            a.unresolved,
+            share(a.unresolved)
+        );
+    }
+    let steps = |n: u64| {
+        if n == 1 {
+            "1 step".to_string()
+        } else {
+            format!("{n} steps")
+        }
+    };
+    match a.longest_start {
+        Some(x) if a.max_transient > 0 => println!(
+            "  longest transient         {}, from x = {x:e}",
+            steps(a.max_transient)
+        ),
+        _ => println!("  longest transient         0 steps"),
+    }
+    if a.transients.len() <= 8 {
+        let lengths: Vec<String> = a
+            .transients
+            .iter()
+            .map(|(len, n)| format!("{len}: {n}"))
+            .collect();
+        if !lengths.is_empty() {
+            println!("  starts by transient       {}", lengths.join(", "));
+        }
+    } else {
+        println!(
+            "  transient percentiles     50%: {}, 90%: {}, 99%: {}",
+            a.transient_quantile(0.5),
+            a.transient_quantile(0.9),
+            a.transient_quantile(0.99)
+        );
+    }
+    println!("  distinct fixed points     {}", a.fixed_points().count());
+    let longer: Vec<_> = a.cycles.iter().filter(|c| c.len() > 1).collect();
+    println!("  cycles of length 2+       {}", longer.len());
+    for c in longer.iter().take(top) {
+        let shown: Vec<String> = c.members.iter().take(4).map(|m| format!("{m:e}")).collect();
+        let back = if c.len() > 4 { "..." } else { &shown[0] };
+        println!(
+            "    length {}, {} starts: {} -> {back}",
+            c.len(),
+            c.basin,
+            shown.join(" -> ")
+        );
+    }
+}
+
+pub fn main(args: Vec<String>, _global: &Global) -> Result<(), Box<dyn Error>> {
+    let config = match parse_args(args) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+    println!("map x -> {}", config.text);
+    for format in &config.formats {
+        let analysis = run(&config, format);
+        print_analysis(&analysis, config.top, config.max_steps);
+    }
+    Ok(())
+}
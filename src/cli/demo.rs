@@ -0,0 +1,209 @@
+use crate::arith::Format;
+use crate::conditioning::analyze;
+use crate::double_double::DoubleDouble;
+use crate::fixed_point;
+use crate::iteration::study;
+use crate::shadow::Tracer;
+use crate::soft_float::preset;
+use std::error::Error;
+use super::Global;
+
+fn decimal_representation_demo() {
+    let a32: f32 = 0.1;
+    let b32: f32 = 0.2;
+    let a64: f64 = 0.1;
+    let b64: f64 = 0.2;
+
+    println!("== Decimal representation ==");
+    println!("f32: 0.1 + 0.2 = {:.10}", a32 + b32);
+    println!("f64: 0.1 + 0.2 = {:.17}", a64 + b64);
+    println!("Expected mathematically: 0.3");
+    println!();
+}
+
+fn accumulation_demo() {
+    let sum32: f32 = (0..10).map(|_| 0.1f32).sum();
+    let sum64: f64 = (0..10).map(|_| 0.1f64).sum();
+
+    println!("== Accumulation error ==");
+    println!("f32: sum(0.1 repeated 10x) = {:.10}", sum32);
+    println!("f64: sum(0.1 repeated 10x) = {:.17}", sum64);
+    println!("Target: 1.0");
+
+    // The same loop a million times: every add rounds, and 0.1 itself is off.
+    let tenth = DoubleDouble::parse("0.1").unwrap();
+    for format in [Format::F32, Format::F64] {
+        let c = format.round_reference(tenth);
+        let s = study(
+            &format,
+            &[DoubleDouble::ZERO],
+            1_000_000,
+            |x, _| x[0] = x[0] + tenth,
+            |f, x, _| x[0] = f.add(x[0], c),
+        );
+        let error = s.final_sample().map_or(0.0, |l| l.rel_error);
+        println!(
+            "{}: sum(0.1 repeated 10^6x) is off by {:.3e}, error growth {}",
+            format.name(),
+            error,
+            s.classification.describe()
+        );
+    }
+    println!();
+}
+
+fn cancellation_demo() {
+    let big32: f32 = 100_000_000.0;
+    let big64: f64 = 100_000_000.0;
+
+    let out32 = (big32 + 1.0) - big32;
+    let out64 = (big64 + 1.0) - big64;
+
+    println!("== Catastrophic cancellation ==");
+    println!("f32: (1e8 + 1) - 1e8 = {}", out32);
+    println!("f64: (1e8 + 1) - 1e8 = {}", out64);
+    println!();
+
+    // Replay the f32 version with shadow values to see which step lost the 1.
+    let tracer = Tracer::new(Format::F32);
+    let big = tracer.input("big", 1e8);
+    let sum = (big + 1.0).named("sum");
+    (sum - big).named("out");
+    println!("f32 trace (each value shadowed in double-double):");
+    print!("{}", tracer.table());
+    println!();
+
+    // The problem itself is harmless: (x + 1) - x is 1 for every x.
+    let single = Format::F32;
+    let unstable = analyze(
+        &single,
+        |x| (x + DoubleDouble::ONE) - x,
+        |x| single.sub(single.add(x, 1.0), x),
+        DoubleDouble::from(1e8),
+    );
+    // x - 1 near 1 is ill-conditioned whatever the algorithm.
+    let ill = analyze(
+        &single,
+        |x| x - DoubleDouble::ONE,
+        |x| single.sub(x, 1.0),
+        DoubleDouble::parse("1.0000001").unwrap(),
+    );
+    println!("f32 (x + 1) - x at x = 1e8:    {}", unstable.summary());
+    println!("f32 x - 1 at x = 1.0000001: {}", ill.summary());
+    println!();
+}
+
+fn scaling_demo() {
+    let small32: f32 = 0.0002298;
+    let small64: f64 = 0.0002298;
+
+    println!("== Scaling a small decimal by powers of ten ==");
+    println!("Starting value (f32): {}", small32);
+    println!("Starting value (f64): {}", small64);
+    println!();
+
+    for i in 0..10 {
+        let multiplier = 10u64.pow(i) as f64;
+        let result32 = small32 * multiplier as f32;
+        let result64 = small64 * multiplier;
+
+        println!("Multiplier: 10^{}", i);
+        println!("f32 result: {}", result32);
+        println!("f64 result: {}", result64);
+        println!("Absolute difference: {}", (result64 - result32 as f64).abs());
+        println!();
+    }
+}
+
+fn non_accumulating_demo() {
+    println!("== Counterexamples: repeated ops without growing error ==");
+
+    // Exact in binary: dividing/multiplying by powers of two just shifts exponent bits.
+    let start_exact = 0.3f64;
+    let mut exact = start_exact;
+    for _ in 0..100 {
+        exact = (exact / 2.0) * 2.0;
+    }
+    println!("Exact roundtrip (/2 then *2, 100x)");
+    println!("start:  {:.17}", start_exact);
+    println!("end:    {:.17}", exact);
+    println!("drift:  {:.17}", (exact - start_exact).abs());
+    println!();
+
+    // Inexact operation pair: first round may change value, then it can settle to a fixed point.
+    let start_stable = 0.9f64;
+    let mut stable = start_stable;
+    let mut after_one = start_stable;
+    for i in 0..100 {
+        stable = (stable / 3.0) * 3.0;
+        if i == 0 {
+            after_one = stable;
+        }
+    }
+    println!("Inexact but non-growing (/3 then *3, f64)");
+    println!("start:      {:.20}", start_stable);
+    println!("after 1x:   {:.20}", after_one);
+    println!("after 100x: {:.20}", stable);
+    println!("1-step drift:   {:.20}", (after_one - start_stable).abs());
+    println!("100-step drift: {:.20}", (stable - start_stable).abs());
+    println!();
+
+    // The same map from every start of a small format, not just 0.9.
+    for name in ["tiny8", "fp16_like"] {
+        let Some(spec) = preset(name) else { continue };
+        let Some(starts) = fixed_point::all_values(&spec, 1 << 21) else { continue };
+        let format = Format::Soft(spec);
+        let cycles = fixed_point::analyze(
+            &format,
+            |x| format.mul(format.div(x, 3.0), 3.0),
+            &starts,
+            true,
+            1000,
+        );
+        println!(
+            "All {} {} values under /3 then *3: {} already fixed, {} fixed after at most {} step(s), {} in longer cycles, {} overflow",
+            cycles.inputs,
+            name,
+            cycles.already_fixed,
+            cycles.converged,
+            cycles.max_transient,
+            cycles.cycling,
+            cycles.escaped
+        );
+    }
+    println!();
+}
+
+fn parse_args(args: &[String]) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        None => Ok(()),
+        Some("--help" | "-h") => Err(help_text()),
+        Some(arg) => Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+    }
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest demo",
+        "",
+        "Prints the tutorial demos: decimal representation, accumulation error,",
+        "catastrophic cancellation, scaling by powers of ten and repeated operations",
+        "without growing error. A bare `cargo run` shows the same.",
+    ]
+    .join("\n")
+}
+
+/// The tutorial demos printed by a bare `cargo run`.
+pub fn main(args: Vec<String>, _global: &Global) -> Result<(), Box<dyn Error>> {
+    if let Err(msg) = parse_args(&args) {
+        eprintln!("{msg}");
+        std::process::exit(2);
+    }
+    decimal_representation_demo();
+    accumulation_demo();
+    cancellation_demo();
+    scaling_demo();
+    non_accumulating_demo();
+    Ok(())
+}
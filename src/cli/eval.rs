@@ -0,0 +1,220 @@
+use crate::arith::Format;
+use crate::conditioning::{condition_number_multi, ErrorAnalysis};
+use crate::double_double::DoubleDouble;
+use crate::eval::{evaluate, parse_expr, reference, Evaluation, Expr};
+use std::error::Error;
+use super::{parse_arith_format, Global};
+
+struct Config {
+    expr: Expr,
+    vars: Vec<(String, String)>,
+    formats: Vec<Format>,
+    steps: bool,
+}
+
+fn parse_var(v: &str) -> Result<(String, String), String> {
+    let (name, value) = v
+        .split_once('=')
+        .ok_or_else(|| format!("invalid variable '{v}', expected name=value"))?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("invalid variable name in '{v}'"));
+    }
+    DoubleDouble::parse(value)?;
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+fn parse_args(args: Vec<String>) -> Result<Config, String> {
+    let mut expr: Option<Expr> = None;
+    let mut vars: Vec<(String, String)> = Vec::new();
+    let mut formats = Vec::new();
+    let mut steps = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--var" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--var requires a value".to_string())?;
+                vars.push(parse_var(&v)?);
+            }
+            "--format" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--format requires a value".to_string())?;
+                formats.push(parse_arith_format(&v)?);
+            }
+            "--steps" => steps = true,
+            "--help" | "-h" => return Err(help_text()),
+            _ if arg.contains('=') && !arg.starts_with("--") => vars.push(parse_var(&arg)?),
+            _ if expr.is_none() && !arg.starts_with("--") => expr = Some(parse_expr(&arg)?),
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    let expr = expr.ok_or_else(|| format!("missing expression\n\n{}", help_text()))?;
+    if formats.is_empty() {
+        formats = vec![Format::F32, Format::F64];
+    }
+    Ok(Config {
+        expr,
+        vars,
+        formats,
+        steps,
+    })
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest eval <expression> [name=value ...] [options]",
+        "",
+        "Evaluates the expression in each format, rounding the inputs and every",
+        "operation, and compares with a double-double (~106 bit) reference.",
+        "Supports + - * /, unary -, x^n (integer n, repeated multiplication),",
+        "parentheses, sqrt(..) and abs(..).",
+        "",
+        "Options:",
+        "  --var name=value               Bind a variable (same as a bare name=value)",
+        "  --format <f32|f64|preset|name,m,min_e,max_e>  Repeatable, default: f32 and f64",
+        "  --steps                        Also print every operation with its error",
+        "",
+        "The condition number is taken with respect to the variables; literals",
+        "count as exact constants, so bind a value to a name to treat it as data.",
+        "",
+        "Examples:",
+        "  cargo run -- eval \"(a + 1) - a\" a=1e8",
+        "  cargo run -- eval \"0.1 + 0.2\" --format f64 --format bf16_like --steps",
+        "  cargo run -- eval \"(x^2 - 1) / (x - 1)\" x=1.0001 --format fp16_like",
+    ]
+    .join("\n")
+}
+
+fn steps_table(eval: &Evaluation) -> String {
+    let text_w = eval
+        .steps
+        .iter()
+        .map(|s| s.text.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("operation".len());
+    let mut out = format!(
+        "  {:>3}  {:<text_w$}  {:>24}  {:>26}  {:>10}  {:>6}  {:>6}\n",
+        "#", "operation", "value", "reference", "rel error", "bits", "lost"
+    );
+    for (i, s) in eval.steps.iter().enumerate() {
+        out.push_str(&format!(
+            "  {:>3}  {:<text_w$}  {:>24e}  {:>26}  {:>10.3e}  {:>6.1}  {:>6.1}\n",
+            i + 1,
+            s.text,
+            s.value,
+            s.reference.to_sci_string(20),
+            s.rel_error,
+            s.correct_bits,
+            s.bits_lost
+        ));
+    }
+    out
+}
+
+pub fn main(args: Vec<String>, _global: &Global) -> Result<(), Box<dyn Error>> {
+    let config = match parse_args(args) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut vars = Vec::new();
+    for (name, value) in &config.vars {
+        vars.push((name.clone(), DoubleDouble::parse(value)?));
+    }
+    for name in config.expr.variables() {
+        if !vars.iter().any(|(n, _)| *n == name) {
+            return Err(format!("no value for variable '{name}'; pass {name}=<value>").into());
+        }
+    }
+    let exact = reference(&config.expr, &vars)?;
+    let evals = config
+        .formats
+        .iter()
+        .map(|f| evaluate(&config.expr, &vars, f))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    println!("expression  {}", config.expr.to_text());
+    for (name, value) in &config.vars {
+        println!("  {name} = {value}");
+    }
+    let reduced = std::iter::once(exact)
+        .chain(vars.iter().map(|(_, v)| *v))
+        .any(|v| !v.is_full_precision());
+    let note = if reduced {
+        "double-double; fewer than 106 bits below 1e-292"
+    } else {
+        "double-double"
+    };
+    println!("reference   {} ({note})", exact.to_sci_string(30));
+    println!();
+
+    let name_w = evals
+        .iter()
+        .map(|e| e.format.name().chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("format".len());
+    println!(
+        "{:<name_w$}  {:>24}  {:>10}  {:>12}  most bits lost at",
+        "format", "result", "rel error", "correct bits"
+    );
+    for e in &evals {
+        let worst = match e.worst_step() {
+            Some(s) => format!("{} ({:.1} bits)", s.text, s.bits_lost),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<name_w$}  {:>24e}  {:>10.3e}  {:>12}  {}",
+            e.format.name(),
+            e.value,
+            e.rel_error,
+            format!("{:.1} / {}", e.correct_bits, e.format.precision()),
+            worst
+        );
+    }
+
+    let names = config.expr.variables();
+    let inputs: Vec<DoubleDouble> = names
+        .iter()
+        .map(|n| vars.iter().find(|(v, _)| v == n).map(|(_, x)| *x))
+        .collect::<Option<_>>()
+        .unwrap_or_default();
+    let condition = condition_number_multi(
+        |xs| {
+            let bound: Vec<(String, DoubleDouble)> =
+                names.iter().cloned().zip(xs.iter().copied()).collect();
+            reference(&config.expr, &bound).unwrap_or(DoubleDouble::from(f64::NAN))
+        },
+        &inputs,
+    );
+    println!();
+    if names.is_empty() {
+        println!("conditioning  no variables, every literal counts as an exact constant");
+    } else {
+        println!(
+            "conditioning  κ = {:.3e} with respect to {}",
+            condition,
+            names.join(", ")
+        );
+    }
+    for e in &evals {
+        let analysis = ErrorAnalysis::new(&e.format, e.rel_error, condition);
+        println!("{:<name_w$}  {}", e.format.name(), analysis.summary());
+    }
+
+    if config.steps {
+        for e in &evals {
+            println!("\n{}:", e.format.name());
+            print!("{}", steps_table(e));
+        }
+    }
+    Ok(())
+}
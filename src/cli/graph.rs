@@ -0,0 +1,485 @@
+use crate::html::{render_page, Chart, Tooltip};
+use crate::plot::{palette_color, Axis, Figure, Legend, LegendLayout, Panel, Rect, Series, Ticks};
+use crate::soft_float::{parse_format, preset, preset_names, SoftFloatSpec};
+use crate::tty::{render_panel, TtyOptions};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use super::Global;
+
+fn ulp32(x: f32) -> f32 {
+    x.next_up() - x
+}
+
+fn ulp64(x: f64) -> f64 {
+    x.next_up() - x
+}
+
+/// A plotted format: the hardware types (with subnormals) or a soft float spec.
+#[derive(Debug)]
+enum Format {
+    NativeF32,
+    NativeF64,
+    Soft(SoftFloatSpec),
+}
+
+impl Format {
+    fn name(&self) -> &str {
+        match self {
+            Format::NativeF32 => "f32",
+            Format::NativeF64 => "f64",
+            Format::Soft(spec) => &spec.name,
+        }
+    }
+
+    fn mantissa_bits(&self) -> u32 {
+        match self {
+            Format::NativeF32 => f32::MANTISSA_DIGITS - 1,
+            Format::NativeF64 => f64::MANTISSA_DIGITS - 1,
+            Format::Soft(spec) => spec.mantissa_bits,
+        }
+    }
+
+    /// log10 of the smallest positive and largest finite values.
+    fn k_range(&self) -> (f64, f64) {
+        match self {
+            Format::NativeF32 => ((f32::from_bits(1) as f64).log10(), (f32::MAX as f64).log10()),
+            Format::NativeF64 => (f64::from_bits(1).log10(), f64::MAX.log10()),
+            Format::Soft(spec) => (spec.min_normal().log10(), spec.max_finite().log10()),
+        }
+    }
+
+    /// ULP at `x = 10^k`, or `None` where `10^k` is zero or out of range.
+    fn ulp_at(&self, k: f64) -> Option<f64> {
+        let u = match self {
+            Format::NativeF32 => {
+                let x = 10f32.powf(k as f32);
+                if !x.is_finite() || x <= 0.0 {
+                    return None;
+                }
+                ulp32(x) as f64
+            }
+            Format::NativeF64 => {
+                let x = 10f64.powf(k);
+                if !x.is_finite() || x <= 0.0 {
+                    return None;
+                }
+                ulp64(x)
+            }
+            Format::Soft(spec) => spec.ulp_near(10f64.powf(k))?,
+        };
+        (u > 0.0 && u.is_finite()).then_some(u)
+    }
+}
+
+fn parse_preset(name: &str) -> Result<Format, String> {
+    match name {
+        "f32" => Ok(Format::NativeF32),
+        "f64" => Ok(Format::NativeF64),
+        _ => preset(name).map(Format::Soft).ok_or_else(|| {
+            format!(
+                "unknown preset '{name}' (known: f32, f64, {})",
+                preset_names().join(", ")
+            )
+        }),
+    }
+}
+
+#[derive(Debug)]
+struct Config {
+    formats: Vec<Format>,
+    k_min: Option<f64>,
+    k_max: Option<f64>,
+    density: f64,
+    residual_k_min: f64,
+    residual_k_max: f64,
+    residual_density: f64,
+    trend: bool,
+    residual: bool,
+    out: String,
+    html: bool,
+    png: bool,
+    dpi: f64,
+    tty_plot: bool,
+    tty_ascii: bool,
+}
+
+fn parse_args(args: Vec<String>, global: &Global) -> Result<Config, String> {
+    let mut config = Config {
+        formats: Vec::new(),
+        k_min: None,
+        k_max: None,
+        density: 8.0,
+        residual_k_min: -20.0,
+        residual_k_max: 20.0,
+        residual_density: 8.0,
+        trend: true,
+        residual: true,
+        out: global.output("docs/precision_over_range.svg"),
+        html: false,
+        png: false,
+        dpi: 96.0,
+        tty_plot: false,
+        tty_ascii: false,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Err(help_text());
+        }
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{arg} requires a value"))
+        };
+        let number = |v: String| {
+            v.parse::<f64>()
+                .map_err(|e| format!("invalid {arg} '{v}': {e}"))
+        };
+        match arg.as_str() {
+            "--preset" | "--format" => {
+                let v = value()?;
+                config.formats.push(if v.contains(',') {
+                    Format::Soft(parse_format(&v)?)
+                } else {
+                    parse_preset(&v)?
+                });
+            }
+            "--k-min" => config.k_min = Some(number(value()?)?),
+            "--k-max" => config.k_max = Some(number(value()?)?),
+            "--density" => config.density = number(value()?)?,
+            "--residual-k-min" => config.residual_k_min = number(value()?)?,
+            "--residual-k-max" => config.residual_k_max = number(value()?)?,
+            "--residual-density" => config.residual_density = number(value()?)?,
+            "--panels" => {
+                let v = value()?;
+                config.trend = false;
+                config.residual = false;
+                for panel in v.split(',').map(str::trim) {
+                    match panel {
+                        "trend" => config.trend = true,
+                        "residual" => config.residual = true,
+                        _ => {
+                            return Err(format!(
+                                "invalid --panels entry '{panel}', expected trend and/or residual"
+                            ))
+                        }
+                    }
+                }
+            }
+            "--out" => config.out = value()?,
+            "--html" => config.html = true,
+            "--png" => config.png = true,
+            "--tty-plot" => config.tty_plot = true,
+            "--tty-ascii" => {
+                config.tty_plot = true;
+                config.tty_ascii = true;
+            }
+            "--dpi" => {
+                config.dpi = number(value()?)?;
+                config.png = true;
+            }
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+
+    if config.formats.is_empty() {
+        config.formats = vec![Format::NativeF64, Format::NativeF32];
+    }
+    validate(&config)?;
+    Ok(config)
+}
+
+fn validate(config: &Config) -> Result<(), String> {
+    for (name, v) in [
+        ("--density", config.density),
+        ("--residual-density", config.residual_density),
+    ] {
+        if !(v.is_finite() && v > 0.0 && v <= 1000.0) {
+            return Err(format!("{name} must be in (0, 1000]"));
+        }
+    }
+    if let (Some(lo), Some(hi)) = (config.k_min, config.k_max) {
+        if lo.is_nan() || hi.is_nan() || hi <= lo {
+            return Err("--k-max must be greater than --k-min".to_string());
+        }
+    }
+    if config.residual_k_min.is_nan()
+        || config.residual_k_max.is_nan()
+        || config.residual_k_max <= config.residual_k_min
+    {
+        return Err("--residual-k-max must be greater than --residual-k-min".to_string());
+    }
+    if !(config.dpi.is_finite() && (24.0..=1200.0).contains(&config.dpi)) {
+        return Err("--dpi must be in [24, 1200]".to_string());
+    }
+    if !(config.trend || config.residual) {
+        return Err("--panels must name at least one panel".to_string());
+    }
+    Ok(())
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest graph [options]",
+        "  cargo run --bin precision_graph -- [options]",
+        "",
+        "Options:",
+        "  --format <name|spec>           Plot f32, f64 (hardware), a soft float preset or name,m,min_e,max_e (repeatable)",
+        "  --preset <name>                Same as --format",
+        "  --k-min <f64>                  Trend panel lower k; default: smallest value of any format",
+        "  --k-max <f64>                  Trend panel upper k; default: largest value of any format",
+        "  --density <f64>                Trend samples per decade, default: 8",
+        "  --residual-k-min <f64>         Residual panel lower k, default: -20",
+        "  --residual-k-max <f64>         Residual panel upper k, default: 20",
+        "  --residual-density <f64>       Residual samples per decade, default: 8",
+        "  --panels trend,residual        Panels to draw, default: both",
+        "  --out <path.svg>               Default: docs/precision_over_range.svg",
+        "  --html                         Also write the same path with .html (interactive, self-contained)",
+        "  --png                          Also write the same path with .png (built-in rasterizer)",
+        "  --dpi <f64>                    PNG resolution, 96 = one pixel per SVG unit; default: 96 (implies --png)",
+        "  --tty-plot                     Also print the panels as terminal charts (braille, colored on a TTY)",
+        "  --tty-ascii                    Like --tty-plot, with ASCII instead of braille characters",
+        "",
+        "Without --preset or --format the graph shows f64 and f32.",
+        "",
+        "Examples:",
+        "  cargo run -- graph",
+        "  cargo run -- graph --format fp16_like --format bf16_like --format f32",
+        "  cargo run -- graph --format custom,11,-40,40 --panels residual --out custom.svg",
+    ]
+    .join("\n")
+}
+
+/// `k_min..=k_max` in steps of `1 / density`.
+fn k_samples(k_min: f64, k_max: f64, density: f64) -> Vec<f64> {
+    let n = ((k_max - k_min) * density + 1e-9).floor() as usize;
+    (0..=n).map(|i| k_min + i as f64 / density).collect()
+}
+
+fn trend_panel(config: &Config, rect: Rect) -> Panel {
+    let (auto_min, auto_max) = config
+        .formats
+        .iter()
+        .map(Format::k_range)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (a, b)| {
+            (lo.min(a), hi.max(b))
+        });
+    let k_min = config.k_min.unwrap_or(auto_min.floor());
+    let k_max = config.k_max.unwrap_or(auto_max.ceil());
+    let ks = k_samples(k_min, k_max, config.density);
+
+    let series: Vec<Series> = config
+        .formats
+        .iter()
+        .enumerate()
+        .map(|(idx, fmt)| {
+            let points = ks
+                .iter()
+                .filter_map(|&k| fmt.ulp_at(k).map(|u| (k, u.log10())))
+                .collect();
+            Series::line(fmt.name(), palette_color(idx), points)
+        })
+        .collect();
+
+    let (y_min, y_max) = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|p| p.1))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    let (y_min, y_max) = if y_min.is_finite() {
+        let pad = ((y_max - y_min) * 0.05).max(1.0);
+        (y_min - pad, y_max + pad)
+    } else {
+        (-1.0, 1.0)
+    };
+
+    Panel {
+        series,
+        ..Panel::new(
+            rect,
+            Axis::linear("k where x = 10^k", k_min, k_max),
+            Axis::linear("log10(ULP(x))", y_min, y_max).ticks(Ticks::Auto(7)),
+        )
+        .caption("Global trend (dense sampling)")
+    }
+}
+
+fn residual_panel(config: &Config, rect: Rect) -> Panel {
+    let log10_2 = 2f64.log10();
+    let ks = k_samples(config.residual_k_min, config.residual_k_max, config.residual_density);
+
+    // Residual against the ideal normal-range ULP 10^k * 2^-m: a sawtooth in [-log10(2), 0].
+    let series: Vec<Series> = config
+        .formats
+        .iter()
+        .enumerate()
+        .map(|(idx, fmt)| {
+            let m = fmt.mantissa_bits() as f64;
+            let points = ks
+                .iter()
+                .filter_map(|&k| {
+                    let u = fmt.ulp_at(k)?;
+                    Some((k, u.log10() - (k - m * log10_2)))
+                })
+                .collect();
+            Series::step(fmt.name(), palette_color(idx), points).width(1.8)
+        })
+        .collect();
+
+    let (y_min, y_max) = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|p| p.1))
+        .fold((-log10_2, 0.0f64), |(lo, hi), v| (lo.min(v), hi.max(v)));
+
+    Panel {
+        series,
+        ..Panel::new(
+            rect,
+            Axis::linear("k where x = 10^k", config.residual_k_min, config.residual_k_max)
+                .ticks(Ticks::Auto(6)),
+            Axis::linear("Residual vs baseline", y_min - 0.02, y_max + 0.02).ticks(Ticks::Auto(6)),
+        )
+        .caption("Jagged view (step plot of sawtooth residual)")
+    }
+}
+
+/// Hover values for each series: `x = 10^k`, its ULP and, for the residual panel, the residual.
+fn tooltips(config: &Config, panel: &Panel, residual: bool) -> Vec<Tooltip> {
+    let mut columns = vec!["x".to_string(), "ULP(x)".to_string()];
+    if residual {
+        columns.push("residual".to_string());
+    }
+    panel
+        .series
+        .iter()
+        .zip(&config.formats)
+        .map(|(series, fmt)| Tooltip {
+            columns: columns.clone(),
+            rows: series
+                .points
+                .iter()
+                .map(|&(k, y)| {
+                    let mut row = vec![10f64.powf(k), fmt.ulp_at(k).unwrap_or(f64::NAN)];
+                    if residual {
+                        row.push(y);
+                    }
+                    row
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn write_html(config: &Config, figure: &Figure, path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut charts = Vec::new();
+    let mut panels = figure.panels.iter();
+    for (enabled, residual) in [(config.trend, false), (config.residual, true)] {
+        if !enabled {
+            continue;
+        }
+        let panel = panels.next().ok_or("missing panel")?;
+        charts.push(Chart {
+            title: panel.caption.clone().unwrap_or_default(),
+            panel: panel.clone(),
+            tooltips: tooltips(config, panel, residual),
+        });
+    }
+    let names: Vec<&str> = config.formats.iter().map(Format::name).collect();
+    let title = figure.title.clone().unwrap_or_default();
+    let subtitle = format!("Formats: {}", names.join(", "));
+    fs::write(path, render_page(&title, &subtitle, &charts))?;
+    Ok(())
+}
+
+pub fn main(args: Vec<String>, global: &Global) -> Result<(), Box<dyn Error>> {
+    let config = match parse_args(args, global) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    let output = Path::new(&config.out);
+    if let Some(parent) = output.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    let width = 1400.0;
+    let left = 90.0;
+    let right = 40.0;
+    let top = 80.0;
+    let panel_h = 360.0;
+    let gap = 110.0;
+
+    let n_panels = config.trend as usize + config.residual as usize;
+    let height = top + n_panels as f64 * panel_h + (n_panels as f64 - 1.0) * gap + 70.0;
+    let x1 = width - right;
+    let mut rects = Rect::new(left, top, x1, height - 70.0)
+        .grid(n_panels, 1, 0.0, gap)
+        .into_iter();
+
+    let mut panels = Vec::new();
+    if config.trend {
+        panels.push(trend_panel(&config, rects.next().unwrap()));
+    }
+    if config.residual {
+        panels.push(residual_panel(&config, rects.next().unwrap()));
+    }
+
+    let longest = config
+        .formats
+        .iter()
+        .map(|f| f.name().chars().count())
+        .max()
+        .unwrap_or(0);
+    let spacing = (60.0 + 9.0 * longest as f64).max(90.0);
+    let figure = Figure {
+        panels,
+        legend: Some(Legend {
+            layout: LegendLayout::Row {
+                x: x1 - spacing * config.formats.len() as f64,
+                y: 64.0,
+                spacing,
+            },
+            font_size: 17.0,
+            entries: config
+                .formats
+                .iter()
+                .enumerate()
+                .map(|(idx, f)| (f.name().to_string(), palette_color(idx).to_string()))
+                .collect(),
+        }),
+        ..Figure::new(width, height)
+            .title("Floating-point precision over range (ULP at x = 10^k)")
+    };
+
+    figure.write_svg(output)?;
+    global.wrote(output.display());
+
+    if config.png {
+        let png_path = output.with_extension("png");
+        figure.write_png(&png_path, config.dpi)?;
+        global.wrote(png_path.display());
+    }
+
+    if config.html {
+        let html_path = output.with_extension("html");
+        write_html(&config, &figure, &html_path)?;
+        global.wrote(html_path.display());
+    }
+
+    if config.tty_plot {
+        let opts = TtyOptions::detect(config.tty_ascii);
+        for panel in &figure.panels {
+            println!("\n{}", render_panel(panel, &opts));
+        }
+    }
+
+    Ok(())
+}
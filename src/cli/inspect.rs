@@ -0,0 +1,71 @@
+use crate::inspect::inspect;
+use crate::soft_float::{default_presets, SoftFloatSpec};
+use std::error::Error;
+use super::{parse_format_arg, Global};
+
+struct Config {
+    value: f64,
+    formats: Vec<SoftFloatSpec>,
+}
+
+fn parse_args(args: Vec<String>) -> Result<Config, String> {
+    let mut value: Option<f64> = None;
+    let mut formats = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--format requires a value".to_string())?;
+                formats.push(parse_format_arg(&v)?);
+            }
+            "--help" | "-h" => return Err(help_text()),
+            _ if value.is_none() => {
+                value = Some(
+                    arg.parse::<f64>()
+                        .map_err(|e| format!("invalid value '{arg}': {e}"))?,
+                );
+            }
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    let value = value.ok_or_else(|| format!("missing value\n\n{}", help_text()))?;
+    if formats.is_empty() {
+        formats = default_presets();
+    }
+    Ok(Config { value, formats })
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest inspect <value> [--format <preset|name,m,min_e,max_e>]...",
+        "",
+        "Explains how each format stores <value>: sign, exponent and mantissa fields, the",
+        "neighboring values, ULP, the rounding decision and the error. Default: every",
+        "built-in preset.",
+        "",
+        "Example:",
+        "  cargo run -- inspect 0.0002298 --format bf16_like",
+    ]
+    .join("\n")
+}
+
+pub fn main(args: Vec<String>, _global: &Global) -> Result<(), Box<dyn Error>> {
+    let config = match parse_args(args) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    let reports: Vec<String> = config
+        .formats
+        .iter()
+        .map(|spec| inspect(spec, config.value).report())
+        .collect();
+    println!("{}", reports.join("\n\n"));
+    Ok(())
+}
@@ -0,0 +1,353 @@
+use crate::arith::Format;
+use crate::double_double::DoubleDouble;
+use crate::iteration::{csv, study, Study};
+use crate::plot::{palette_color, Axis, Figure, Legend, LegendLayout, Panel, Rect, Series};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use super::{parse_arith_format, Global};
+
+/// Built-in step functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Scenario {
+    /// `x += c`, the accumulation demo at scale.
+    Sum,
+    /// `x = (x / c) * c`: rounds every step but settles.
+    Roundtrip,
+    /// Harmonic oscillator, symplectic Euler with step `h`: state `(x, v)`.
+    Oscillator,
+    /// Logistic map `x = r x (1 - x)`, chaotic for `r = 3.9`.
+    Logistic,
+}
+
+impl Scenario {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "sum" => Ok(Scenario::Sum),
+            "roundtrip" => Ok(Scenario::Roundtrip),
+            "oscillator" => Ok(Scenario::Oscillator),
+            "logistic" => Ok(Scenario::Logistic),
+            other => Err(format!(
+                "unknown scenario '{other}', expected sum, roundtrip, oscillator or logistic"
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Scenario::Sum => "sum",
+            Scenario::Roundtrip => "roundtrip",
+            Scenario::Oscillator => "oscillator",
+            Scenario::Logistic => "logistic",
+        }
+    }
+
+    fn default_steps(self) -> u64 {
+        match self {
+            Scenario::Logistic => 200,
+            Scenario::Roundtrip => 100_000,
+            _ => 1_000_000,
+        }
+    }
+
+    /// Start value and the scenario's constant (`c`, `h` or `r`).
+    fn defaults(self) -> (&'static str, &'static str) {
+        match self {
+            Scenario::Sum => ("0", "0.1"),
+            Scenario::Roundtrip => ("0.9", "3"),
+            Scenario::Oscillator => ("1", "0.01"),
+            Scenario::Logistic => ("0.3", "3.9"),
+        }
+    }
+
+    fn formula(self) -> &'static str {
+        match self {
+            Scenario::Sum => "x = x + c",
+            Scenario::Roundtrip => "x = (x / c) * c",
+            Scenario::Oscillator => "v = v - h x; x = x + h v",
+            Scenario::Logistic => "x = r x (1 - x)",
+        }
+    }
+
+    fn run(
+        self,
+        format: &Format,
+        start: DoubleDouble,
+        constant: DoubleDouble,
+        steps: u64,
+    ) -> Study {
+        let c = format.round_reference(constant);
+        match self {
+            Scenario::Sum => study(
+                format,
+                &[start],
+                steps,
+                |s, _| s[0] = s[0] + constant,
+                |f, s, _| s[0] = f.add(s[0], c),
+            ),
+            Scenario::Roundtrip => study(
+                format,
+                &[start],
+                steps,
+                |_, _| {},
+                |f, s, _| s[0] = f.mul(f.div(s[0], c), c),
+            ),
+            Scenario::Oscillator => study(
+                format,
+                &[start, DoubleDouble::ZERO],
+                steps,
+                |s, _| {
+                    s[1] = s[1] - constant * s[0];
+                    s[0] = s[0] + constant * s[1];
+                },
+                |f, s, _| {
+                    s[1] = f.sub(s[1], f.mul(c, s[0]));
+                    s[0] = f.add(s[0], f.mul(c, s[1]));
+                },
+            ),
+            Scenario::Logistic => study(
+                format,
+                &[start],
+                steps,
+                |s, _| s[0] = constant * s[0] * (DoubleDouble::ONE - s[0]),
+                |f, s, _| s[0] = f.mul(f.mul(c, s[0]), f.sub(1.0, s[0])),
+            ),
+        }
+    }
+}
+
+struct Config {
+    scenario: Scenario,
+    steps: u64,
+    start: String,
+    constant: String,
+    formats: Vec<Format>,
+    csv: String,
+    out: String,
+}
+
+fn parse_args(args: Vec<String>, global: &Global) -> Result<Config, String> {
+    let mut scenario = None;
+    let mut steps = None;
+    let mut start = None;
+    let mut constant = None;
+    let mut formats = Vec::new();
+    let mut csv = None;
+    let mut out = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.as_str() {
+            "--steps" => {
+                let v = value("--steps")?;
+                steps = Some(
+                    v.parse::<f64>()
+                        .ok()
+                        .filter(|n| *n >= 1.0 && n.fract() == 0.0)
+                        .ok_or_else(|| format!("invalid --steps '{v}'"))?
+                        as u64,
+                );
+            }
+            "--start" => {
+                let v = value("--start")?;
+                DoubleDouble::parse(&v)?;
+                start = Some(v);
+            }
+            "--constant" => {
+                let v = value("--constant")?;
+                DoubleDouble::parse(&v)?;
+                constant = Some(v);
+            }
+            "--format" => formats.push(parse_arith_format(&value("--format")?)?),
+            "--csv" => csv = Some(value("--csv")?),
+            "--out" => out = Some(value("--out")?),
+            "--help" | "-h" => return Err(help_text()),
+            _ if scenario.is_none() && !arg.starts_with("--") => {
+                scenario = Some(Scenario::parse(&arg)?)
+            }
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    let scenario = scenario.ok_or_else(|| format!("missing scenario\n\n{}", help_text()))?;
+    let (default_start, default_constant) = scenario.defaults();
+    if formats.is_empty() {
+        formats = vec![Format::F32, Format::F64];
+    }
+    let stem = format!("docs/iteration_{}", scenario.name());
+    Ok(Config {
+        scenario,
+        steps: steps.unwrap_or(scenario.default_steps()),
+        start: start.unwrap_or_else(|| default_start.to_string()),
+        constant: constant.unwrap_or_else(|| default_constant.to_string()),
+        formats,
+        csv: csv.unwrap_or_else(|| global.output(&format!("{stem}.csv"))),
+        out: out.unwrap_or_else(|| global.output(&format!("{stem}.svg"))),
+    })
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest iterate <scenario> [options]",
+        "",
+        "Runs a step function many times in each format beside a double-double",
+        "reference, records the error after every step and classifies its growth.",
+        "",
+        "Scenarios:",
+        "  sum         x = x + c from 0, c = 0.1 (10^6 steps)",
+        "  roundtrip   x = (x / c) * c from 0.9, c = 3 (10^5 steps)",
+        "  oscillator  v = v - h x; x = x + h v from (1, 0), h = 0.01 (10^6 steps)",
+        "  logistic    x = r x (1 - x) from 0.3, r = 3.9 (200 steps)",
+        "",
+        "Options:",
+        "  --steps <n>        Number of iterations (e.g. 1e7)",
+        "  --start <x>        Start value",
+        "  --constant <c>     The scenario's constant (c, h or r)",
+        "  --format <f32|f64|preset|name,m,min_e,max_e>  Repeatable, default: f32 and f64",
+        "  --csv <path>       Default: docs/iteration_<scenario>.csv",
+        "  --out <path>       Log-log SVG of error vs iteration (default: docs/iteration_<scenario>.svg)",
+        "",
+        "Examples:",
+        "  cargo run -- iterate sum --steps 1e7",
+        "  cargo run -- iterate oscillator --format f32 --format bf16_like",
+        "  cargo run -- iterate logistic --constant 3.7 --steps 500",
+    ]
+    .join("\n")
+}
+
+fn figure(config: &Config, studies: &[Study]) -> Figure {
+    let (width, height) = (1100.0, 700.0);
+    let rect = Rect::new(100.0, 100.0, width - 40.0, height - 80.0);
+    let mut series: Vec<Series> = studies
+        .iter()
+        .enumerate()
+        .map(|(idx, s)| {
+            let points = s
+                .samples
+                .iter()
+                .filter(|p| p.rel_error > 0.0 && p.rel_error.is_finite())
+                .map(|p| (p.step as f64, p.rel_error))
+                .collect();
+            Series::line(s.format.name(), palette_color(idx), points).width(1.5)
+        })
+        .collect();
+    // Guides from the first format's unit roundoff.
+    let steps = config.steps as f64;
+    let mut guides = Vec::new();
+    if let Some(first) = studies.first() {
+        let u = 2f64.powi(-(first.format.precision() as i32));
+        for (name, power) in [("u·sqrt(n)", 0.5), ("u·n", 1.0)] {
+            let points = vec![(1.0, u), (steps, u * steps.powf(power))];
+            series.push(Series::line(name, "#999", points).width(1.0).dashed());
+            guides.push(name);
+        }
+    }
+    let (y_min, y_max) = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|p| p.1))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    let (y_min, y_max) = if y_min.is_finite() {
+        (y_min / 2.0, y_max * 2.0)
+    } else {
+        (1e-20, 1.0)
+    };
+    let panel = Panel {
+        series,
+        ..Panel::new(
+            rect,
+            Axis::log10("iteration", 1.0, steps.max(10.0)),
+            Axis::log10("relative error", y_min, y_max),
+        )
+        .caption(format!(
+            "{}: {}",
+            config.scenario.name(),
+            config.scenario.formula()
+        ))
+    };
+    let mut entries: Vec<(String, String)> = studies
+        .iter()
+        .enumerate()
+        .map(|(idx, s)| (s.format.name().to_string(), palette_color(idx).to_string()))
+        .collect();
+    entries.extend(guides.iter().map(|g| (g.to_string(), "#999".to_string())));
+    let spacing = 130.0;
+    Figure {
+        panels: vec![panel],
+        legend: Some(Legend {
+            layout: LegendLayout::Row {
+                x: width - 40.0 - spacing * entries.len() as f64,
+                y: 64.0,
+                spacing,
+            },
+            font_size: 17.0,
+            entries,
+        }),
+        ..Figure::new(width, height).title("Error growth over iterations")
+    }
+}
+
+pub fn main(args: Vec<String>, global: &Global) -> Result<(), Box<dyn Error>> {
+    let config = match parse_args(args, global) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+    let start = DoubleDouble::parse(&config.start)?;
+    let constant = DoubleDouble::parse(&config.constant)?;
+
+    let studies: Vec<Study> = config
+        .formats
+        .iter()
+        .map(|f| config.scenario.run(f, start, constant, config.steps))
+        .collect();
+
+    println!(
+        "{}: {} from {}, constant {}, {} steps",
+        config.scenario.name(),
+        config.scenario.formula(),
+        config.start,
+        config.constant,
+        config.steps
+    );
+    let name_w = studies
+        .iter()
+        .map(|s| s.format.name().chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("format".len());
+    println!(
+        "{:<name_w$}  {:>24}  {:>12}  {:>12}  growth",
+        "format", "final value", "final error", "max error"
+    );
+    for s in &studies {
+        let last = s.final_sample();
+        println!(
+            "{:<name_w$}  {:>24e}  {:>12.3e}  {:>12.3e}  {}",
+            s.format.name(),
+            last.map_or(f64::NAN, |l| l.value),
+            last.map_or(0.0, |l| l.rel_error),
+            last.map_or(0.0, |l| l.max_rel_error),
+            s.classification.describe()
+        );
+    }
+
+    for path in [&config.csv, &config.out] {
+        if let Some(parent) = Path::new(path).parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+    }
+    fs::write(&config.csv, csv(&studies))?;
+    global.wrote(&config.csv);
+    figure(&config, &studies).write_svg(Path::new(&config.out))?;
+    global.wrote(&config.out);
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use crate::arith::Format;
+use crate::elementary::{measure, Accuracy, Function, Range};
+use crate::plot::{palette_color, Axis, Figure, Legend, LegendLayout, Panel, Rect, Series};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use super::{parse_arith_format, Global};
+
+struct Config {
+    functions: Vec<Function>,
+    formats: Vec<Format>,
+    range: Option<String>,
+    y_range: Option<String>,
+    log: bool,
+    points: usize,
+    top: usize,
+    out: String,
+}
+
+fn parse_args(args: Vec<String>, global: &Global) -> Result<Config, String> {
+    let mut functions = Vec::new();
+    let mut formats = Vec::new();
+    let mut range = None;
+    let mut y_range = None;
+    let mut log = false;
+    let mut points = 2000;
+    let mut top = 3;
+    let mut out = global.output("docs/libm_ulps.svg");
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.as_str() {
+            "--function" => {
+                for name in value("--function")?.split(',') {
+                    functions.push(Function::parse(name)?);
+                }
+            }
+            "--format" => formats.push(parse_arith_format(&value("--format")?)?),
+            "--range" => range = Some(value("--range")?),
+            "--y-range" => y_range = Some(value("--y-range")?),
+            "--log" => log = true,
+            "--points" => {
+                let v = value("--points")?;
+                points = v
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid --points '{v}'"))?;
+            }
+            "--top" => {
+                let v = value("--top")?;
+                top = v.parse().map_err(|e| format!("invalid --top '{v}': {e}"))?;
+            }
+            "--out" => out = value("--out")?,
+            "--help" | "-h" => return Err(help_text()),
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    if functions.is_empty() {
+        functions = Function::ALL.to_vec();
+    }
+    if formats.is_empty() {
+        formats = vec![Format::F32, Format::F64];
+    }
+    // Check the ranges up front rather than once per function.
+    if let Some(r) = &range {
+        Range::parse(r, log)?;
+    } else if log {
+        return Err("--log applies to --range; pass a range as well".to_string());
+    }
+    if let Some(r) = &y_range {
+        Range::parse(r, false)?;
+    }
+    Ok(Config {
+        functions,
+        formats,
+        range,
+        y_range,
+        log,
+        points,
+        top,
+        out,
+    })
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest libm [options]",
+        "",
+        "Measures elementary functions in ULPs against a double-double reference.",
+        "f32 and f64 call the platform libm; soft formats round the f64 result.",
+        "",
+        "Options:",
+        "  --function <sin|cos|exp|ln|pow|sqrt|atan2>  Repeatable or comma-separated, default: all",
+        "  --format <f32|f64|preset|name,m,min_e,max_e>  Repeatable, default: f32 and f64",
+        "  --range lo:hi      First argument range (default depends on the function)",
+        "  --y-range lo:hi    Second argument of pow and atan2, sampled pseudo-randomly",
+        "  --log              Space --range geometrically (lo > 0)",
+        "  --points <n>       Samples per function and format (default: 2000)",
+        "  --top <n>          Worst inputs to list per function and format (default: 3)",
+        "  --out <path>       SVG of ULP error over the range (default: docs/libm_ulps.svg)",
+        "",
+        "Examples:",
+        "  cargo run -- libm --function sin,cos --range -1e4:1e4",
+        "  cargo run -- libm --function ln --range 1e-30:1e30 --log --format f32",
+        "  cargo run -- libm --function pow --y-range 0.5:3 --format bf16_like",
+    ]
+    .join("\n")
+}
+
+fn ranges(config: &Config, function: Function) -> (Range, Range) {
+    let (default_x, default_y) = function.default_ranges();
+    // Both were validated in parse_args.
+    let x = config
+        .range
+        .as_ref()
+        .and_then(|r| Range::parse(r, config.log).ok())
+        .unwrap_or(default_x);
+    let y = config
+        .y_range
+        .as_ref()
+        .and_then(|r| Range::parse(r, false).ok())
+        .unwrap_or(default_y);
+    (x, y)
+}
+
+fn describe_input(a: &Accuracy, x: f64, y: f64) -> String {
+    match a.function {
+        Function::Pow => format!("pow({x:e}, {y:e})"),
+        Function::Atan2 => format!("atan2({x:e}, {y:e})"),
+        f => format!("{}({x:e})", f.name()),
+    }
+}
+
+fn panel(function: Function, results: &[&Accuracy], x: Range, rect: Rect) -> Panel {
+    let series: Vec<Series> = results
+        .iter()
+        .enumerate()
+        .map(|(idx, a)| {
+            let points = a
+                .samples
+                .iter()
+                .filter(|s| s.ulps.is_finite())
+                .map(|s| (s.x, s.ulps))
+                .collect();
+            Series::scatter(a.format.name(), palette_color(idx), points, 1.6, true)
+        })
+        .collect();
+    let y_max = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|p| p.1))
+        .fold(0.0, f64::max);
+    let y_max = (y_max * 1.1).max(1.0);
+    let x_axis = if x.log {
+        Axis::log10("x", x.lo, x.hi)
+    } else {
+        Axis::linear("x", x.lo, x.hi.max(x.lo + f64::EPSILON))
+    };
+    let caption = match function.arity() {
+        2 => format!("{} (x is the first argument)", function.name()),
+        _ => function.name().to_string(),
+    };
+    Panel {
+        series,
+        ..Panel::new(rect, x_axis, Axis::linear("error (ULPs)", 0.0, y_max)).caption(caption)
+    }
+}
+
+pub fn main(args: Vec<String>, global: &Global) -> Result<(), Box<dyn Error>> {
+    let config = match parse_args(args, global) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut results = Vec::new();
+    for &function in &config.functions {
+        let (x, y) = ranges(&config, function);
+        for format in &config.formats {
+            results.push(measure(function, format, x, y, config.points));
+        }
+    }
+
+    let name_w = config
+        .formats
+        .iter()
+        .map(|f| f.name().chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("format".len());
+    println!(
+        "{:<8}  {:<name_w$}  {:>7}  {:>7}  {:>10}  {:>10}  worst input",
+        "function", "format", "samples", "skipped", "max ulp", "mean ulp"
+    );
+    for a in &results {
+        let worst = match a.worst(1).first() {
+            Some(s) => describe_input(a, s.x, s.y),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<8}  {:<name_w$}  {:>7}  {:>7}  {:>10.3}  {:>10.4}  {}",
+            a.function.name(),
+            a.format.name(),
+            a.samples.len(),
+            a.skipped,
+            a.max_ulps(),
+            a.mean_ulps(),
+            worst
+        );
+    }
+
+    if config.top > 0 {
+        for a in &results {
+            let worst = a.worst(config.top);
+            if worst.first().is_none_or(|s| s.ulps == 0.0) {
+                continue;
+            }
+            let inputs: Vec<String> = worst.iter().map(|s| describe_input(a, s.x, s.y)).collect();
+            let input_w = inputs.iter().map(|i| i.len()).max().unwrap_or(0);
+            println!(
+                "\n{} in {}, worst inputs:",
+                a.function.name(),
+                a.format.name()
+            );
+            for (s, input) in worst.iter().zip(inputs) {
+                println!(
+                    "  {:<input_w$}  {:>9.3} ulp  got {:e}, exact {}",
+                    input,
+                    s.ulps,
+                    s.value,
+                    s.reference.to_sci_string(20)
+                );
+            }
+        }
+    }
+
+    let output = Path::new(&config.out);
+    if let Some(parent) = output.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let width = 1200.0;
+    let (left, right, top, panel_h, gap) = (90.0, 40.0, 100.0, 300.0, 100.0);
+    let n = config.functions.len();
+    let height = top + n as f64 * panel_h + (n as f64 - 1.0) * gap + 70.0;
+    let x1 = width - right;
+    let rects = Rect::new(left, top, x1, height - 70.0).grid(n, 1, 0.0, gap);
+    let panels = config
+        .functions
+        .iter()
+        .zip(rects)
+        .map(|(&function, rect)| {
+            let of_function: Vec<&Accuracy> =
+                results.iter().filter(|a| a.function == function).collect();
+            panel(function, &of_function, ranges(&config, function).0, rect)
+        })
+        .collect();
+    let longest = config
+        .formats
+        .iter()
+        .map(|f| f.name().chars().count())
+        .max()
+        .unwrap_or(0);
+    let spacing = (60.0 + 9.0 * longest as f64).max(90.0);
+    let figure = Figure {
+        panels,
+        legend: Some(Legend {
+            layout: LegendLayout::Row {
+                x: x1 - spacing * config.formats.len() as f64,
+                y: 64.0,
+                spacing,
+            },
+            font_size: 17.0,
+            entries: config
+                .formats
+                .iter()
+                .enumerate()
+                .map(|(idx, f)| (f.name().to_string(), palette_color(idx).to_string()))
+                .collect(),
+        }),
+        ..Figure::new(width, height).title("Elementary function error in ULPs")
+    };
+    figure.write_svg(output)?;
+    global.wrote(output.display());
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use crate::arith::Format;
+use crate::double_double::DoubleDouble;
+use crate::elementary::{Function, Range};
+use crate::poly::{analyze, coefficient_errors, fit_chebyshev, fit_minimax, Polynomial, Scheme};
+use std::error::Error;
+use std::f64::consts::{FRAC_PI_4, LN_2};
+use super::{parse_arith_format, Global};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Fit {
+    Minimax,
+    Chebyshev,
+}
+
+struct Config {
+    function: Option<Function>,
+    interval: Option<Range>,
+    degree: usize,
+    fit: Fit,
+    coeffs: Option<Polynomial>,
+    formats: Vec<Format>,
+    coeff_format: Option<Format>,
+    schemes: Vec<Scheme>,
+    points: usize,
+}
+
+fn parse_args(args: Vec<String>) -> Result<Config, String> {
+    let mut config = Config {
+        function: None,
+        interval: None,
+        degree: 4,
+        fit: Fit::Minimax,
+        coeffs: None,
+        formats: Vec::new(),
+        coeff_format: None,
+        schemes: Vec::new(),
+        points: 2001,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.as_str() {
+            "--function" => {
+                let f = Function::parse(&value("--function")?)?;
+                if f.arity() != 1 {
+                    return Err(format!(
+                        "--function {} takes two arguments; pick a one-argument function",
+                        f.name()
+                    ));
+                }
+                config.function = Some(f);
+            }
+            "--interval" => config.interval = Some(Range::parse(&value("--interval")?, false)?),
+            "--degree" => {
+                let v = value("--degree")?;
+                config.degree = v
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&d| d <= 20)
+                    .ok_or_else(|| format!("invalid --degree '{v}', expected 0 to 20"))?;
+            }
+            "--fit" => {
+                config.fit = match value("--fit")?.as_str() {
+                    "minimax" | "remez" => Fit::Minimax,
+                    "chebyshev" => Fit::Chebyshev,
+                    other => {
+                        return Err(format!(
+                            "invalid --fit '{other}', expected minimax or chebyshev"
+                        ))
+                    }
+                }
+            }
+            "--coeffs" => config.coeffs = Some(Polynomial::parse(&value("--coeffs")?)?),
+            "--format" => config
+                .formats
+                .push(parse_arith_format(&value("--format")?)?),
+            "--coeff-format" => {
+                config.coeff_format = Some(parse_arith_format(&value("--coeff-format")?)?)
+            }
+            "--scheme" => {
+                for name in value("--scheme")?.split(',') {
+                    config.schemes.push(match name.trim() {
+                        "horner" => Scheme::Horner,
+                        "estrin" => Scheme::Estrin,
+                        other => {
+                            return Err(format!(
+                                "invalid --scheme '{other}', expected horner or estrin"
+                            ))
+                        }
+                    });
+                }
+            }
+            "--points" => {
+                let v = value("--points")?;
+                config.points = v
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid --points '{v}'"))?;
+            }
+            "--help" | "-h" => return Err(help_text()),
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    if config.function.is_none() && config.coeffs.is_none() {
+        return Err(format!(
+            "pass --function to fit, --coeffs to evaluate, or both\n\n{}",
+            help_text()
+        ));
+    }
+    if config.formats.is_empty() {
+        config.formats = vec![parse_arith_format("fp16_like")?, Format::F32];
+    }
+    if config.schemes.is_empty() {
+        config.schemes = vec![Scheme::Horner, Scheme::Estrin];
+    }
+    Ok(config)
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest poly --function <f> [options]",
+        "  prectest poly --coeffs c0,c1,... [--function <f>] [options]",
+        "",
+        "Fits or takes a polynomial, evaluates it with every operation rounded into each",
+        "format, and splits the error into approximation, coefficient quantization and",
+        "evaluation rounding, in relative terms and in ULPs of the format.",
+        "",
+        "Options:",
+        "  --function <sin|cos|exp|ln|sqrt>  Target function (reference in double-double)",
+        "  --interval lo:hi        Default: [-pi/4, pi/4] for sin/cos, [-ln2/2, ln2/2] for exp,",
+        "                          [0.75, 1.5] for ln, [0.5, 2] for sqrt, [-1, 1] without a target",
+        "  --degree <n>            Degree of the fitted polynomial (default: 4)",
+        "  --fit <minimax|chebyshev>  Remez minimax (default) or Chebyshev interpolation",
+        "  --coeffs c0,c1,...      Use these coefficients, constant term first, instead of fitting",
+        "  --format <f32|f64|preset|name,m,min_e,max_e>  Repeatable, default: fp16_like and f32",
+        "  --coeff-format <f>      Store coefficients in this format (default: the evaluation format)",
+        "  --scheme <horner|estrin>  Repeatable or comma-separated, default: both",
+        "  --points <n>            Evenly spaced test inputs (default: 2001)",
+        "",
+        "Examples:",
+        "  cargo run -- poly --function exp --degree 3 --format fp16_like",
+        "  cargo run -- poly --function sin --degree 5 --format bf16_like --coeff-format f32",
+        "  cargo run -- poly --coeffs 1,1,0.5,0.16666667 --function exp --interval -0.35:0.35",
+    ]
+    .join("\n")
+}
+
+fn default_interval(function: Option<Function>) -> Range {
+    match function {
+        Some(Function::Sin | Function::Cos) => Range::linear(-FRAC_PI_4, FRAC_PI_4),
+        Some(Function::Exp) => Range::linear(-LN_2 / 2.0, LN_2 / 2.0),
+        Some(Function::Ln) => Range::linear(0.75, 1.5),
+        Some(Function::Sqrt) => Range::linear(0.5, 2.0),
+        _ => Range::linear(-1.0, 1.0),
+    }
+}
+
+pub fn main(args: Vec<String>, _global: &Global) -> Result<(), Box<dyn Error>> {
+    let config = match parse_args(args) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    let interval = config
+        .interval
+        .unwrap_or_else(|| default_interval(config.function));
+    let (lo, hi) = (interval.lo, interval.hi);
+    let target = config.function.map(|f| move |x: f64| f.reference(x, 0.0));
+    let target: Option<&dyn Fn(f64) -> DoubleDouble> = target.as_ref().map(|f| f as _);
+
+    let (poly, origin) = match (&config.coeffs, target) {
+        (Some(p), _) => (p.clone(), "given".to_string()),
+        (None, Some(f)) => match config.fit {
+            Fit::Minimax => {
+                let (p, err) = fit_minimax(f, lo, hi, config.degree)?;
+                (p, format!("minimax fit, max error {err:.3e}"))
+            }
+            Fit::Chebyshev => (
+                fit_chebyshev(f, lo, hi, config.degree)?,
+                "Chebyshev interpolation".to_string(),
+            ),
+        },
+        (None, None) => unreachable!("checked in parse_args"),
+    };
+
+    match config.function {
+        Some(f) => println!("target        {} on [{lo:e}, {hi:e}]", f.name()),
+        None => {
+            println!("target        none (errors against the exact polynomial) on [{lo:e}, {hi:e}]")
+        }
+    }
+    println!("polynomial    degree {} ({origin})", poly.degree());
+
+    let breakdowns: Vec<_> = config
+        .formats
+        .iter()
+        .map(|format| {
+            let coeff_format = config.coeff_format.as_ref().unwrap_or(format);
+            analyze(
+                &poly,
+                target,
+                (lo, hi),
+                format,
+                coeff_format,
+                &config.schemes,
+                config.points,
+            )
+        })
+        .collect();
+
+    println!("\ncoefficients (constant term first), quantized with relative change:");
+    let columns: Vec<Vec<String>> = breakdowns
+        .iter()
+        .map(|b| {
+            let changes = coefficient_errors(&poly, &b.quantized);
+            b.quantized
+                .iter()
+                .zip(changes)
+                .map(|(q, change)| format!("{q:e} ({change:.1e})"))
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = breakdowns
+        .iter()
+        .zip(&columns)
+        .map(|(b, cells)| {
+            cells
+                .iter()
+                .map(|c| c.len())
+                .chain([b.coeff_format.name().len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut header = format!("  {:<4}  {:>26}", "", "exact");
+    for (b, w) in breakdowns.iter().zip(&widths) {
+        header.push_str(&format!("  {:>w$}", b.coeff_format.name()));
+    }
+    println!("{header}");
+    for (i, c) in poly.coeffs.iter().enumerate() {
+        let mut row = format!("  c{i:<3}  {:>26}", c.to_sci_string(20));
+        for (cells, w) in columns.iter().zip(&widths) {
+            row.push_str(&format!("  {:>w$}", cells[i]));
+        }
+        println!("{row}");
+    }
+
+    for b in &breakdowns {
+        println!(
+            "\n{} (coefficients in {}), {} points:",
+            b.format.name(),
+            b.coeff_format.name(),
+            b.points
+        );
+        println!(
+            "  {:<26}  {:>13}  {:>10}  {:>14}",
+            "source", "max rel error", "max ulp", "worst x"
+        );
+        let mut rows = Vec::new();
+        if let Some(a) = b.approximation {
+            rows.push(("approximation".to_string(), a));
+        }
+        rows.push(("coefficient quantization".to_string(), b.quantization));
+        for s in &b.schemes {
+            rows.push((format!("rounding, {}", s.scheme.name()), s.rounding));
+        }
+        for s in &b.schemes {
+            rows.push((format!("total, {}", s.scheme.name()), s.total));
+        }
+        for (name, e) in rows {
+            println!(
+                "  {:<26}  {:>13.3e}  {:>10.3}  {:>14e}",
+                name, e.max_rel_error, e.max_ulps, e.worst_x
+            );
+        }
+    }
+    Ok(())
+}
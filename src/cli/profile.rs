@@ -0,0 +1,271 @@
+use crate::profile::{PiecewiseQuantizer, Quantizer, Region};
+use crate::report::{quantizer_json, render, report_header, MachineFormat};
+use crate::soft_float::SoftFloatSpec;
+use crate::value::Value;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use super::Global;
+
+#[derive(Clone, Copy)]
+struct Zone {
+    name: &'static str,
+    min: f64,
+    max: f64,
+}
+
+fn sample_range(min: f64, max: f64, step: f64) -> Vec<f64> {
+    let mut xs = Vec::new();
+    let mut x = min;
+    while x <= max + step * 0.5 {
+        xs.push(x);
+        x += step;
+    }
+    xs
+}
+
+fn mean_abs_rel_error(q: &Quantizer, zone: Zone, xs: &[f64]) -> (f64, f64) {
+    let mut abs_sum = 0.0;
+    let mut rel_sum = 0.0;
+    let mut n = 0usize;
+
+    for &x in xs {
+        if !(x >= zone.min && x < zone.max) {
+            continue;
+        }
+        let y = q.quantize(x);
+        let abs_err = (y - x).abs();
+        let rel_err = if x == 0.0 { 0.0 } else { abs_err / x.abs() };
+
+        abs_sum += abs_err;
+        rel_sum += rel_err;
+        n += 1;
+    }
+
+    if n == 0 {
+        (0.0, 0.0)
+    } else {
+        (abs_sum / n as f64, rel_sum / n as f64)
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Option<MachineFormat>, String> {
+    let mut machine = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => machine = Some(MachineFormat::Json),
+            "--format-out" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--format-out requires a value".to_string())?;
+                machine = Some(MachineFormat::parse(&v)?);
+            }
+            "--help" | "-h" => return Err(help_text()),
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    Ok(machine)
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest profile [options]",
+        "  cargo run --bin profile_float_demo -- [options]",
+        "",
+        "Writes docs/profile_quantizer_examples.md and .csv (see --out-dir).",
+        "",
+        "Options:",
+        "  --json                  Also write docs/profile_quantizer_examples.json",
+        "  --format-out json|jsonl Machine-readable layout; jsonl writes a .jsonl file",
+    ]
+    .join("\n")
+}
+
+/// Schema `prectest.profile`: `quantizers`, per-zone `zones` metrics and per-input `samples`.
+fn write_machine(
+    quantizers: &[Quantizer],
+    zones: &[Zone],
+    xs: &[f64],
+    format: MachineFormat,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut header = report_header("prectest.profile", "profile_float_demo");
+    let mut sweep = Value::table();
+    sweep.set("x_min", Value::Float(xs.first().copied().unwrap_or(0.0)));
+    sweep.set("x_max", Value::Float(xs.last().copied().unwrap_or(0.0)));
+    sweep.set("count", Value::Int(xs.len() as i64));
+    header.set("sweep", sweep);
+
+    let specs = quantizers.iter().map(quantizer_json).collect();
+
+    let mut zone_rows = Vec::new();
+    for q in quantizers {
+        for &zone in zones {
+            let (mae, mre) = mean_abs_rel_error(q, zone, xs);
+            let mut t = Value::table();
+            t.set("quantizer", Value::Str(q.name().to_string()));
+            t.set("zone", Value::Str(zone.name.to_string()));
+            t.set("zone_min", Value::Float(zone.min));
+            t.set("zone_max", Value::Float(zone.max));
+            t.set("mean_abs_err", Value::Float(mae));
+            t.set("mean_rel_err", Value::Float(mre));
+            zone_rows.push(t);
+        }
+    }
+
+    let mut sample_rows = Vec::new();
+    for &x in xs {
+        for q in quantizers {
+            let y = q.quantize(x);
+            let abs = (y - x).abs();
+            let rel = if x == 0.0 { 0.0 } else { abs / x.abs() };
+            let mut t = Value::table();
+            t.set("quantizer", Value::Str(q.name().to_string()));
+            t.set("x", Value::Float(x));
+            t.set("quantized", Value::Float(y));
+            t.set("abs_error", Value::Float(abs));
+            t.set("rel_error", Value::Float(rel));
+            sample_rows.push(t);
+        }
+    }
+
+    let out = render(
+        format,
+        header,
+        vec![
+            ("quantizers", "quantizer", specs),
+            ("zones", "zone", zone_rows),
+            ("samples", "sample", sample_rows),
+        ],
+    );
+    fs::write(path, out)?;
+    Ok(())
+}
+
+pub fn main(args: Vec<String>, global: &Global) -> Result<(), Box<dyn Error>> {
+    let machine = match parse_args(args) {
+        Ok(m) => m,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    let prefix = global.output("docs/profile_quantizer_examples");
+    if let Some(parent) = Path::new(&prefix).parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let bf16_like = Quantizer::Uniform(SoftFloatSpec::new("bf16_like", 7, -126, 127));
+    let f32_like = Quantizer::Uniform(SoftFloatSpec::new("f32_like", 23, -126, 127));
+
+    let profile = Quantizer::Piecewise(PiecewiseQuantizer {
+        name: "profile_pos_fine_neg_coarse".to_string(),
+        regions: vec![
+            Region {
+                min: -1.0,
+                max: 0.0,
+                // Intentionally coarse in [-1, 0)
+                spec: SoftFloatSpec::new("neg_coarse", 4, -20, 20),
+            },
+            Region {
+                min: 0.0,
+                max: 2.0,
+                // Intentionally fine in [0, 2)
+                spec: SoftFloatSpec::new("pos_fine", 12, -20, 20),
+            },
+        ],
+        // Medium precision elsewhere
+        fallback: SoftFloatSpec::new("fallback", 7, -20, 20),
+    });
+
+    let quantizers = vec![bf16_like, f32_like, profile];
+
+    let zones = [
+        Zone {
+            name: "[-1, 0)",
+            min: -1.0,
+            max: 0.0,
+        },
+        Zone {
+            name: "[0, 2)",
+            min: 0.0,
+            max: 2.0,
+        },
+    ];
+
+    let xs = sample_range(-1.0, 2.0, 0.01);
+
+    let mut md = String::new();
+    md.push_str("---\n");
+    md.push_str("title: Profile Quantizer Concrete Example\n");
+    md.push_str("---\n\n");
+    md.push_str("# Profile Quantizer Concrete Example\n\n");
+    md.push_str("Goal example: **high precision in [0, 2)** and **low precision in [-1, 0)**.\n\n");
+    md.push_str("Important note: true IEEE-like floats (including bfloat16) are sign-symmetric.\n");
+    md.push_str("At the same magnitude, `+x` and `-x` have the same spacing/precision.\n");
+    md.push_str("So this asymmetric behavior needs a profile/piecewise quantizer, not a single standard float format.\n\n");
+
+    md.push_str("## Zone metrics\n\n");
+    md.push_str("| quantizer | zone | mean abs err | mean rel err |\n");
+    md.push_str("| --- | --- | ---: | ---: |\n");
+
+    for q in &quantizers {
+        for &zone in &zones {
+            let (mae, mre) = mean_abs_rel_error(q, zone, &xs);
+            md.push_str(&format!(
+                "| {} | {} | {:.3e} | {:.3e} |\n",
+                q.name(),
+                zone.name,
+                mae,
+                mre
+            ));
+        }
+    }
+
+    let sample_points = [-0.9, -0.5, -0.1, 0.1, 0.5, 1.0, 1.5];
+    md.push_str("\n## Sample points\n\n");
+    md.push_str("| x | bf16_like | f32_like | profile_pos_fine_neg_coarse |\n");
+    md.push_str("| ---: | ---: | ---: | ---: |\n");
+    for &x in &sample_points {
+        let b = quantizers[0].quantize(x);
+        let f = quantizers[1].quantize(x);
+        let p = quantizers[2].quantize(x);
+        md.push_str(&format!("| {:.3} | {:.8} | {:.8} | {:.8} |\n", x, b, f, p));
+    }
+
+    let md_path = format!("{prefix}.md");
+    fs::write(&md_path, md)?;
+
+    let mut csv = String::from("x,quantizer,quantized,abs_error,rel_error\n");
+    for &x in &xs {
+        for q in &quantizers {
+            let y = q.quantize(x);
+            let abs = (y - x).abs();
+            let rel = if x == 0.0 { 0.0 } else { abs / x.abs() };
+            csv.push_str(&format!(
+                "{:.6},{},{:.12},{:.12e},{:.12e}\n",
+                x,
+                q.name(),
+                y,
+                abs,
+                rel
+            ));
+        }
+    }
+    let csv_path = format!("{prefix}.csv");
+    fs::write(&csv_path, csv)?;
+
+    global.wrote(&md_path);
+    global.wrote(&csv_path);
+
+    if let Some(format) = machine {
+        let path = format!("{prefix}.{}", format.extension());
+        write_machine(&quantizers, &zones, &xs, format, &path)?;
+        global.wrote(&path);
+    }
+
+    Ok(())
+}
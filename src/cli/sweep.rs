@@ -0,0 +1,2481 @@
+use crate::html::{render_page, Chart, Tooltip};
+use crate::plot::{
+    colormap, palette_color, Axis, Cell, ColorBar, Figure, Legend, LegendLayout, Panel,
+    PointLabel, Rect, Series, Ticks,
+};
+use crate::profile::{PiecewiseQuantizer, Quantizer, Region};
+use crate::soft_float::{default_presets, preset, Flags, SoftFloatSpec, Tininess};
+use crate::report::{quantizer_json, render, report_header, MachineFormat};
+use crate::tty::{render_bars, render_panel, Bar, TtyOptions};
+use crate::value::{parse_config_file, to_toml, Value};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use super::{parse_format_arg, Global};
+
+#[derive(Debug)]
+struct Config {
+    k_min: f64,
+    k_max: f64,
+    k_step: f64,
+    sweep: SweepMode,
+    x_min: f64,
+    x_max: f64,
+    x_step: f64,
+    out_prefix: String,
+    outputs: OutputPaths,
+    machine: Option<MachineFormat>,
+    html: bool,
+    /// Also rasterize every SVG chart to a PNG beside it.
+    png: bool,
+    dpi: f64,
+    /// Print the error curves and ranking as terminal charts.
+    tty_plot: bool,
+    tty_ascii: bool,
+    heatmap: Option<Heatmap>,
+    formats: Vec<Quantizer>,
+    focus: Vec<WeightTerm>,
+    max_err_weight: f64,
+    underflow_penalty: f64,
+    overflow_penalty: f64,
+}
+
+/// How sample values are laid out over the number line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SweepMode {
+    /// `x = 10^k` for `k` in `[k_min, k_max]` (positive values only).
+    Log,
+    /// `x = -10^k`, `0` and `+10^k` for `k` in `[k_min, k_max]`.
+    Signed,
+    /// Evenly spaced `x` in `[x_min, x_max]`, which may cross zero.
+    Linear,
+}
+
+impl SweepMode {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "log" => Ok(SweepMode::Log),
+            "signed" => Ok(SweepMode::Signed),
+            "linear" => Ok(SweepMode::Linear),
+            other => Err(format!(
+                "invalid sweep '{other}', expected log, signed or linear"
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SweepMode::Log => "log",
+            SweepMode::Signed => "signed",
+            SweepMode::Linear => "linear",
+        }
+    }
+}
+
+/// Metric colored in the design-space heatmap; lower is better for all of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HeatMetric {
+    Score,
+    MeanErr,
+    MaxErr,
+    /// Fraction of samples that underflow or overflow.
+    Clip,
+}
+
+impl HeatMetric {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "score" => Ok(HeatMetric::Score),
+            "mean" => Ok(HeatMetric::MeanErr),
+            "max" => Ok(HeatMetric::MaxErr),
+            "clip" => Ok(HeatMetric::Clip),
+            other => Err(format!(
+                "invalid heatmap metric '{other}', expected score, mean, max or clip"
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            HeatMetric::Score => "score",
+            HeatMetric::MeanErr => "mean",
+            HeatMetric::MaxErr => "max",
+            HeatMetric::Clip => "clip",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            HeatMetric::Score => "score",
+            HeatMetric::MeanErr => "log10(mean relative error)",
+            HeatMetric::MaxErr => "log10(max relative error)",
+            HeatMetric::Clip => "underflow + overflow fraction",
+        }
+    }
+
+    fn value(self, m: &FormatMetrics) -> f64 {
+        match self {
+            HeatMetric::Score => m.score,
+            HeatMetric::MeanErr => m.mean_rel_err.max(1e-18).log10(),
+            HeatMetric::MaxErr => m.max_rel_err.max(1e-18).log10(),
+            HeatMetric::Clip => m.underflow_frac + m.overflow_frac,
+        }
+    }
+}
+
+/// Grid of IEEE-style formats: every mantissa width against every exponent field width.
+#[derive(Debug, Clone)]
+struct Heatmap {
+    mantissa_bits: (u32, u32),
+    exponent_bits: (u32, u32),
+    metric: HeatMetric,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            mantissa_bits: (1, 16),
+            exponent_bits: (2, 8),
+            metric: HeatMetric::Score,
+        }
+    }
+}
+
+/// Parses `lo:hi` (inclusive) bit widths.
+fn parse_bit_range(v: &str, ctx: &str) -> Result<(u32, u32), String> {
+    let (lo, hi) = v
+        .split_once(':')
+        .ok_or_else(|| format!("invalid {ctx} '{v}', expected lo:hi"))?;
+    let parse = |s: &str| {
+        s.trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid {ctx} '{v}': {e}"))
+    };
+    Ok((parse(lo)?, parse(hi)?))
+}
+
+/// One sweep input; `k = log10(|x|)` drives focus weights in every mode.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    k: f64,
+    x: f64,
+}
+
+/// Which sample signs a weight term applies to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SignFilter {
+    Any,
+    Positive,
+    Negative,
+}
+
+impl SignFilter {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "any" => Ok(SignFilter::Any),
+            "pos" | "positive" => Ok(SignFilter::Positive),
+            "neg" | "negative" => Ok(SignFilter::Negative),
+            other => Err(format!(
+                "invalid sign '{other}', expected any, positive or negative"
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SignFilter::Any => "any",
+            SignFilter::Positive => "positive",
+            SignFilter::Negative => "negative",
+        }
+    }
+
+    fn matches(self, x: f64) -> bool {
+        match self {
+            SignFilter::Any => true,
+            SignFilter::Positive => x.is_sign_positive(),
+            SignFilter::Negative => x.is_sign_negative(),
+        }
+    }
+}
+
+/// One term of the ranking weight, as a function of `k` (and the sign of `x`).
+#[derive(Debug, Clone)]
+enum WeightTerm {
+    /// Constant `weight` for `k` in `[min, max]`.
+    Window {
+        min: f64,
+        max: f64,
+        weight: f64,
+        sign: SignFilter,
+    },
+    /// `1 + (peak - 1) * exp(-(k - center)^2 / (2 sigma^2))`, defined for every `k`.
+    Gaussian {
+        center: f64,
+        sigma: f64,
+        peak: f64,
+        sign: SignFilter,
+    },
+    /// Linear interpolation between `(k, weight)` points, defined on their span.
+    Table {
+        points: Vec<(f64, f64)>,
+        sign: SignFilter,
+    },
+}
+
+impl WeightTerm {
+    /// Weight at `k`, or `None` when the term does not cover this sample.
+    fn weight_at(&self, k: f64, x: f64) -> Option<f64> {
+        match self {
+            WeightTerm::Window {
+                min,
+                max,
+                weight,
+                sign,
+            } => (sign.matches(x) && k >= *min && k <= *max).then_some(*weight),
+            WeightTerm::Gaussian {
+                center,
+                sigma,
+                peak,
+                sign,
+            } => sign.matches(x).then(|| {
+                let z = (k - center) / sigma;
+                1.0 + (peak - 1.0) * (-0.5 * z * z).exp()
+            }),
+            WeightTerm::Table { points, sign } => {
+                if !sign.matches(x) {
+                    return None;
+                }
+                let (first, last) = (points.first()?, points.last()?);
+                if k < first.0 || k > last.0 {
+                    return None;
+                }
+                points.windows(2).find_map(|pair| {
+                    let ((k0, w0), (k1, w1)) = (pair[0], pair[1]);
+                    (k >= k0 && k <= k1).then(|| w0 + (w1 - w0) * (k - k0) / (k1 - k0))
+                })
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let positive = |w: f64| w.is_finite() && w > 0.0;
+        match self {
+            WeightTerm::Window {
+                min, max, weight, ..
+            } => {
+                if min.is_nan() || max.is_nan() || max <= min {
+                    return Err(format!("focus window [{min}, {max}]: require max > min"));
+                }
+                if !positive(*weight) {
+                    return Err(format!("focus window [{min}, {max}]: weight must be > 0"));
+                }
+            }
+            WeightTerm::Gaussian {
+                center,
+                sigma,
+                peak,
+                ..
+            } => {
+                if !(center.is_finite() && sigma.is_finite() && *sigma > 0.0) {
+                    return Err("focus gaussian: require finite center and sigma > 0".to_string());
+                }
+                if !positive(*peak) {
+                    return Err("focus gaussian: peak must be > 0".to_string());
+                }
+            }
+            WeightTerm::Table { points, .. } => {
+                if points.len() < 2 {
+                    return Err("focus table: need at least two (k, weight) points".to_string());
+                }
+                if points
+                    .iter()
+                    .any(|&(k, w)| !(k.is_finite() && w.is_finite() && w >= 0.0))
+                {
+                    return Err("focus table: require finite k and weights >= 0".to_string());
+                }
+                if points.windows(2).any(|p| p[1].0 <= p[0].0) {
+                    return Err("focus table: k values must be strictly increasing".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        let sign = |s: &SignFilter| match s {
+            SignFilter::Any => String::new(),
+            other => format!(" ({} x only)", other.name()),
+        };
+        match self {
+            WeightTerm::Window {
+                min,
+                max,
+                weight,
+                sign: s,
+            } => format!("window: k in [{min:.3}, {max:.3}] gets weight {weight:.3}{}", sign(s)),
+            WeightTerm::Gaussian {
+                center,
+                sigma,
+                peak,
+                sign: s,
+            } => format!(
+                "gaussian: weight `1 + ({peak:.3} - 1) * exp(-(k - {center:.3})^2 / (2 * {sigma:.3}^2))`{}",
+                sign(s)
+            ),
+            WeightTerm::Table { points, sign: s } => format!(
+                "table: piecewise-linear weight through {}{}",
+                points
+                    .iter()
+                    .map(|(k, w)| format!("(k={k:.3}, w={w:.3})"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                sign(s)
+            ),
+        }
+    }
+}
+
+/// Per-artifact paths from a config file; unset ones derive from `out_prefix`.
+#[derive(Debug, Default)]
+struct OutputPaths {
+    svg: Option<String>,
+    csv: Option<String>,
+    summary: Option<String>,
+    ranking: Option<String>,
+    pareto: Option<String>,
+    json: Option<String>,
+    html: Option<String>,
+    heatmap: Option<String>,
+}
+
+impl OutputPaths {
+    fn redirect(&mut self, global: &Global) {
+        for path in [
+            &mut self.svg,
+            &mut self.csv,
+            &mut self.summary,
+            &mut self.ranking,
+            &mut self.pareto,
+            &mut self.json,
+            &mut self.html,
+            &mut self.heatmap,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *path = global.output(path);
+        }
+    }
+}
+
+impl Config {
+    fn output_path(&self, explicit: &Option<String>, suffix: &str) -> String {
+        explicit
+            .clone()
+            .unwrap_or_else(|| format!("{}{suffix}", self.out_prefix))
+    }
+}
+
+struct Cli {
+    config: Config,
+    dump_config: bool,
+}
+
+#[derive(Debug, Clone)]
+struct FormatMetrics {
+    name: String,
+    total_bits: u32,
+    mean_rel_err: f64,
+    max_rel_err: f64,
+    underflow_frac: f64,
+    overflow_frac: f64,
+    inexact_frac: f64,
+    finite_frac: f64,
+    score: f64,
+}
+
+fn default_config() -> Config {
+    Config {
+        k_min: -20.0,
+        k_max: 20.0,
+        k_step: 0.1,
+        sweep: SweepMode::Log,
+        x_min: -1.0,
+        x_max: 2.0,
+        x_step: 0.01,
+        out_prefix: String::from("docs/soft_float_sweep"),
+        outputs: OutputPaths::default(),
+        machine: None,
+        html: false,
+        png: false,
+        dpi: 96.0,
+        tty_plot: false,
+        tty_ascii: false,
+        heatmap: None,
+        formats: default_presets()
+            .into_iter()
+            .map(Quantizer::Uniform)
+            .collect(),
+        focus: Vec::new(),
+        max_err_weight: 0.5,
+        underflow_penalty: 4.0,
+        overflow_penalty: 4.0,
+    }
+}
+
+fn check_keys(table: &Value, allowed: &[&str], ctx: &str) -> Result<(), String> {
+    let entries = table
+        .entries()
+        .ok_or_else(|| format!("{ctx}: expected a table"))?;
+    for (key, _) in entries {
+        if !allowed.contains(&key.as_str()) {
+            return Err(format!(
+                "{ctx}: unknown key '{key}' (expected one of: {})",
+                allowed.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn opt_f64(table: &Value, key: &str, ctx: &str) -> Result<Option<f64>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(v) => v
+            .as_f64()
+            .map(Some)
+            .ok_or_else(|| format!("{ctx}.{key}: expected a number")),
+    }
+}
+
+fn opt_str(table: &Value, key: &str, ctx: &str) -> Result<Option<String>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(v) => v
+            .as_str()
+            .map(|s| Some(s.to_string()))
+            .ok_or_else(|| format!("{ctx}.{key}: expected a string")),
+    }
+}
+
+fn req_int<T: TryFrom<i64>>(table: &Value, key: &str, ctx: &str) -> Result<T, String> {
+    let v = table
+        .get(key)
+        .ok_or_else(|| format!("{ctx}: missing '{key}'"))?;
+    v.as_i64()
+        .and_then(|i| T::try_from(i).ok())
+        .ok_or_else(|| format!("{ctx}.{key}: expected an integer in range"))
+}
+
+fn spec_from_value(table: &Value, default_name: &str, ctx: &str) -> Result<SoftFloatSpec, String> {
+    if let Some(preset_name) = opt_str(table, "preset", ctx)? {
+        check_keys(table, &["preset", "name"], ctx)?;
+        let mut spec = preset(&preset_name)
+            .ok_or_else(|| format!("{ctx}: unknown preset '{preset_name}'"))?;
+        if let Some(name) = opt_str(table, "name", ctx)? {
+            spec.name = name;
+        }
+        return Ok(spec);
+    }
+
+    check_keys(
+        table,
+        &["name", "mantissa_bits", "min_exp2", "max_exp2"],
+        ctx,
+    )?;
+    let name = opt_str(table, "name", ctx)?.unwrap_or_else(|| default_name.to_string());
+    let spec = SoftFloatSpec::new(
+        name,
+        req_int(table, "mantissa_bits", ctx)?,
+        req_int(table, "min_exp2", ctx)?,
+        req_int(table, "max_exp2", ctx)?,
+    );
+    spec.check().map_err(|e| format!("{ctx}: {e}"))?;
+    Ok(spec)
+}
+
+fn profile_from_value(table: &Value, ctx: &str) -> Result<PiecewiseQuantizer, String> {
+    check_keys(table, &["name", "fallback", "region"], ctx)?;
+    let name = opt_str(table, "name", ctx)?.ok_or_else(|| format!("{ctx}: missing 'name'"))?;
+    let fallback = table
+        .get("fallback")
+        .ok_or_else(|| format!("{ctx}: missing 'fallback'"))?;
+    let fallback = spec_from_value(fallback, "fallback", &format!("{ctx}.fallback"))?;
+
+    let mut regions = Vec::new();
+    let items = match table.get("region") {
+        None => &[][..],
+        Some(v) => v
+            .as_array()
+            .ok_or_else(|| format!("{ctx}.region: expected an array of tables"))?,
+    };
+    for (idx, item) in items.iter().enumerate() {
+        let rctx = format!("{ctx}.region[{idx}]");
+        let min = opt_f64(item, "min", &rctx)?.ok_or_else(|| format!("{rctx}: missing 'min'"))?;
+        let max = opt_f64(item, "max", &rctx)?.ok_or_else(|| format!("{rctx}: missing 'max'"))?;
+        if max <= min || min.is_nan() || max.is_nan() {
+            return Err(format!("{rctx}: require max > min"));
+        }
+        let mut spec_table = item.clone();
+        if let Value::Table(entries) = &mut spec_table {
+            entries.retain(|(k, _)| k != "min" && k != "max");
+        }
+        let spec = spec_from_value(&spec_table, &format!("region{idx}"), &rctx)?;
+        regions.push(Region { min, max, spec });
+    }
+
+    Ok(PiecewiseQuantizer {
+        name,
+        regions,
+        fallback,
+    })
+}
+
+fn weight_term_from_value(table: &Value, ctx: &str) -> Result<WeightTerm, String> {
+    let sign = match opt_str(table, "sign", ctx)? {
+        Some(s) => SignFilter::parse(&s).map_err(|e| format!("{ctx}.sign: {e}"))?,
+        None => SignFilter::Any,
+    };
+    let kind = opt_str(table, "kind", ctx)?.unwrap_or_else(|| "window".to_string());
+    let req = |key: &str| -> Result<f64, String> {
+        opt_f64(table, key, ctx)?.ok_or_else(|| format!("{ctx}: missing '{key}'"))
+    };
+
+    match kind.as_str() {
+        "window" => {
+            check_keys(table, &["kind", "sign", "min", "max", "weight"], ctx)?;
+            Ok(WeightTerm::Window {
+                min: req("min")?,
+                max: req("max")?,
+                weight: req("weight")?,
+                sign,
+            })
+        }
+        "gaussian" => {
+            check_keys(table, &["kind", "sign", "center", "sigma", "peak"], ctx)?;
+            Ok(WeightTerm::Gaussian {
+                center: req("center")?,
+                sigma: req("sigma")?,
+                peak: req("peak")?,
+                sign,
+            })
+        }
+        "table" => {
+            check_keys(table, &["kind", "sign", "points"], ctx)?;
+            let items = table
+                .get("points")
+                .and_then(Value::as_array)
+                .ok_or_else(|| format!("{ctx}.points: expected an array of [k, weight] pairs"))?;
+            let points = items
+                .iter()
+                .map(|p| match p.as_array() {
+                    Some([k, w]) => k.as_f64().zip(w.as_f64()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| format!("{ctx}.points: expected an array of [k, weight] pairs"))?;
+            Ok(WeightTerm::Table { points, sign })
+        }
+        other => Err(format!(
+            "{ctx}.kind: unknown '{other}', expected window, gaussian or table"
+        )),
+    }
+}
+
+fn weight_term_to_value(term: &WeightTerm) -> Value {
+    let mut t = Value::table();
+    let sign = match term {
+        WeightTerm::Window {
+            min,
+            max,
+            weight,
+            sign,
+        } => {
+            t.set("kind", Value::Str("window".to_string()));
+            t.set("min", Value::Float(*min));
+            t.set("max", Value::Float(*max));
+            t.set("weight", Value::Float(*weight));
+            sign
+        }
+        WeightTerm::Gaussian {
+            center,
+            sigma,
+            peak,
+            sign,
+        } => {
+            t.set("kind", Value::Str("gaussian".to_string()));
+            t.set("center", Value::Float(*center));
+            t.set("sigma", Value::Float(*sigma));
+            t.set("peak", Value::Float(*peak));
+            sign
+        }
+        WeightTerm::Table { points, sign } => {
+            t.set("kind", Value::Str("table".to_string()));
+            t.set(
+                "points",
+                Value::Array(
+                    points
+                        .iter()
+                        .map(|&(k, w)| Value::Array(vec![Value::Float(k), Value::Float(w)]))
+                        .collect(),
+                ),
+            );
+            sign
+        }
+    };
+    if *sign != SignFilter::Any {
+        t.set("sign", Value::Str(sign.name().to_string()));
+    }
+    t
+}
+
+/// Parses `a,b,c[,sign]` flag values into numbers plus an optional trailing sign.
+fn parse_numbers_and_sign(
+    flag: &str,
+    v: &str,
+    count: usize,
+) -> Result<(Vec<f64>, SignFilter), String> {
+    let parts: Vec<&str> = v.split(',').map(str::trim).collect();
+    if parts.len() != count && parts.len() != count + 1 {
+        return Err(format!(
+            "invalid {flag} '{v}', expected {count} numbers and an optional sign"
+        ));
+    }
+    let nums = parts[..count]
+        .iter()
+        .map(|p| p.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("invalid {flag} '{v}': {e}"))?;
+    let sign = match parts.get(count) {
+        Some(s) => SignFilter::parse(s).map_err(|e| format!("invalid {flag} '{v}': {e}"))?,
+        None => SignFilter::Any,
+    };
+    Ok((nums, sign))
+}
+
+fn parse_focus_table(v: &str) -> Result<WeightTerm, String> {
+    let mut points = Vec::new();
+    let mut sign = SignFilter::Any;
+    for part in v.split(',').map(str::trim) {
+        match part.split_once(':') {
+            Some((k, w)) => {
+                let k = k
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --focus-table '{v}': {e}"))?;
+                let w = w
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --focus-table '{v}': {e}"))?;
+                points.push((k, w));
+            }
+            None => {
+                sign = SignFilter::parse(part)
+                    .map_err(|e| format!("invalid --focus-table '{v}': {e}"))?;
+            }
+        }
+    }
+    Ok(WeightTerm::Table { points, sign })
+}
+
+/// Applies a parsed config file on top of `config`; CLI flags are applied afterwards.
+fn apply_config_file(config: &mut Config, root: &Value) -> Result<(), String> {
+    check_keys(
+        root,
+        &[
+            "k_min", "k_max", "k_step", "sweep", "x_min", "x_max", "x_step", "presets",
+            "focus", "score", "output", "format", "profile", "heatmap",
+        ],
+        "config",
+    )?;
+
+    if let Some(v) = opt_f64(root, "k_min", "config")? {
+        config.k_min = v;
+    }
+    if let Some(v) = opt_f64(root, "k_max", "config")? {
+        config.k_max = v;
+    }
+    if let Some(v) = opt_f64(root, "k_step", "config")? {
+        config.k_step = v;
+    }
+    if let Some(v) = opt_str(root, "sweep", "config")? {
+        config.sweep = SweepMode::parse(&v).map_err(|e| format!("config.sweep: {e}"))?;
+    }
+    if let Some(v) = opt_f64(root, "x_min", "config")? {
+        config.x_min = v;
+    }
+    if let Some(v) = opt_f64(root, "x_max", "config")? {
+        config.x_max = v;
+    }
+    if let Some(v) = opt_f64(root, "x_step", "config")? {
+        config.x_step = v;
+    }
+
+    // `[focus]` (one term) and `[[focus]]` (any number of terms) are both accepted.
+    match root.get("focus") {
+        None => {}
+        Some(Value::Array(items)) => {
+            for (idx, item) in items.iter().enumerate() {
+                config
+                    .focus
+                    .push(weight_term_from_value(item, &format!("focus[{idx}]"))?);
+            }
+        }
+        Some(item) => config.focus.push(weight_term_from_value(item, "focus")?),
+    }
+
+    if let Some(table) = root.get("heatmap") {
+        check_keys(table, &["mantissa_bits", "exponent_bits", "metric"], "heatmap")?;
+        let mut heatmap = config.heatmap.take().unwrap_or_default();
+        let bit_range = |key: &str| -> Result<Option<(u32, u32)>, String> {
+            let Some(v) = table.get(key) else {
+                return Ok(None);
+            };
+            match v.as_array() {
+                Some([lo, hi]) => match (lo.as_i64(), hi.as_i64()) {
+                    (Some(lo), Some(hi)) if lo >= 0 && hi >= 0 => Ok(Some((lo as u32, hi as u32))),
+                    _ => Err(format!("heatmap.{key}: expected two non-negative integers")),
+                },
+                _ => Err(format!("heatmap.{key}: expected [lo, hi]")),
+            }
+        };
+        if let Some(r) = bit_range("mantissa_bits")? {
+            heatmap.mantissa_bits = r;
+        }
+        if let Some(r) = bit_range("exponent_bits")? {
+            heatmap.exponent_bits = r;
+        }
+        if let Some(v) = opt_str(table, "metric", "heatmap")? {
+            heatmap.metric = HeatMetric::parse(&v).map_err(|e| format!("heatmap.metric: {e}"))?;
+        }
+        config.heatmap = Some(heatmap);
+    }
+
+    if let Some(score) = root.get("score") {
+        check_keys(
+            score,
+            &["max_err_weight", "underflow_penalty", "overflow_penalty"],
+            "score",
+        )?;
+        if let Some(v) = opt_f64(score, "max_err_weight", "score")? {
+            config.max_err_weight = v;
+        }
+        if let Some(v) = opt_f64(score, "underflow_penalty", "score")? {
+            config.underflow_penalty = v;
+        }
+        if let Some(v) = opt_f64(score, "overflow_penalty", "score")? {
+            config.overflow_penalty = v;
+        }
+    }
+
+    if let Some(output) = root.get("output") {
+        check_keys(
+            output,
+            &[
+                "prefix",
+                "svg",
+                "csv",
+                "summary",
+                "ranking",
+                "pareto",
+                "json",
+                "format_out",
+                "html",
+                "heatmap",
+                "png",
+                "dpi",
+            ],
+            "output",
+        )?;
+        if let Some(v) = opt_str(output, "prefix", "output")? {
+            config.out_prefix = v;
+        }
+        let o = &mut config.outputs;
+        o.svg = opt_str(output, "svg", "output")?.or(o.svg.take());
+        o.csv = opt_str(output, "csv", "output")?.or(o.csv.take());
+        o.summary = opt_str(output, "summary", "output")?.or(o.summary.take());
+        o.ranking = opt_str(output, "ranking", "output")?.or(o.ranking.take());
+        o.pareto = opt_str(output, "pareto", "output")?.or(o.pareto.take());
+        o.json = opt_str(output, "json", "output")?.or(o.json.take());
+        o.heatmap = opt_str(output, "heatmap", "output")?.or(o.heatmap.take());
+        match output.get("html") {
+            None => {}
+            Some(Value::Bool(b)) => config.html = *b,
+            Some(Value::Str(path)) => {
+                config.html = true;
+                o.html = Some(path.clone());
+            }
+            Some(_) => return Err("output.html: expected true, false or a path".to_string()),
+        }
+        match output.get("png") {
+            None => {}
+            Some(Value::Bool(b)) => config.png = *b,
+            Some(_) => return Err("output.png: expected true or false".to_string()),
+        }
+        if let Some(dpi) = opt_f64(output, "dpi", "output")? {
+            config.dpi = dpi;
+        }
+        if let Some(v) = opt_str(output, "format_out", "output")? {
+            config.machine =
+                Some(MachineFormat::parse(&v).map_err(|e| format!("output.format_out: {e}"))?);
+        }
+    }
+
+    let presets = match root.get("presets") {
+        None => true,
+        Some(v) => v
+            .as_bool()
+            .ok_or_else(|| "config.presets: expected true or false".to_string())?,
+    };
+    if !presets {
+        config.formats.clear();
+    }
+
+    for (key, build) in [("format", false), ("profile", true)] {
+        let Some(list) = root.get(key) else {
+            continue;
+        };
+        let items = list
+            .as_array()
+            .ok_or_else(|| format!("config.{key}: expected an array of tables ([[{key}]])"))?;
+        for (idx, item) in items.iter().enumerate() {
+            let ctx = format!("{key}[{idx}]");
+            let q = if build {
+                Quantizer::Piecewise(profile_from_value(item, &ctx)?)
+            } else {
+                Quantizer::Uniform(spec_from_value(item, &format!("format{idx}"), &ctx)?)
+            };
+            config.formats.push(q);
+        }
+    }
+
+    Ok(())
+}
+
+fn spec_to_value(spec: &SoftFloatSpec) -> Value {
+    let mut t = Value::table();
+    t.set("name", Value::Str(spec.name.clone()));
+    t.set("mantissa_bits", Value::Int(spec.mantissa_bits as i64));
+    t.set("min_exp2", Value::Int(spec.min_exp2 as i64));
+    t.set("max_exp2", Value::Int(spec.max_exp2 as i64));
+    t
+}
+
+/// Effective config as a file that `--config` reproduces exactly.
+fn config_to_value(config: &Config) -> Value {
+    let mut root = Value::table();
+    root.set("k_min", Value::Float(config.k_min));
+    root.set("k_max", Value::Float(config.k_max));
+    root.set("k_step", Value::Float(config.k_step));
+    root.set("sweep", Value::Str(config.sweep.name().to_string()));
+    if config.sweep == SweepMode::Linear {
+        root.set("x_min", Value::Float(config.x_min));
+        root.set("x_max", Value::Float(config.x_max));
+        root.set("x_step", Value::Float(config.x_step));
+    }
+    root.set("presets", Value::Bool(false));
+
+    if !config.focus.is_empty() {
+        root.set(
+            "focus",
+            Value::Array(config.focus.iter().map(weight_term_to_value).collect()),
+        );
+    }
+
+    let mut score = Value::table();
+    score.set("max_err_weight", Value::Float(config.max_err_weight));
+    score.set("underflow_penalty", Value::Float(config.underflow_penalty));
+    score.set("overflow_penalty", Value::Float(config.overflow_penalty));
+    root.set("score", score);
+
+    let mut output = Value::table();
+    output.set("prefix", Value::Str(config.out_prefix.clone()));
+    let o = &config.outputs;
+    for (key, path) in [
+        ("svg", &o.svg),
+        ("csv", &o.csv),
+        ("summary", &o.summary),
+        ("ranking", &o.ranking),
+        ("pareto", &o.pareto),
+        ("json", &o.json),
+        ("heatmap", &o.heatmap),
+    ] {
+        if let Some(p) = path {
+            output.set(key, Value::Str(p.clone()));
+        }
+    }
+    if let Some(machine) = config.machine {
+        output.set("format_out", Value::Str(machine.name().to_string()));
+    }
+    match (&o.html, config.html) {
+        (Some(path), true) => output.set("html", Value::Str(path.clone())),
+        (None, true) => output.set("html", Value::Bool(true)),
+        _ => {}
+    }
+    if config.png {
+        output.set("png", Value::Bool(true));
+        output.set("dpi", Value::Float(config.dpi));
+    }
+    root.set("output", output);
+
+    if let Some(h) = &config.heatmap {
+        let range = |(lo, hi): (u32, u32)| {
+            Value::Array(vec![Value::Int(lo as i64), Value::Int(hi as i64)])
+        };
+        let mut t = Value::table();
+        t.set("mantissa_bits", range(h.mantissa_bits));
+        t.set("exponent_bits", range(h.exponent_bits));
+        t.set("metric", Value::Str(h.metric.name().to_string()));
+        root.set("heatmap", t);
+    }
+
+    let mut formats = Vec::new();
+    let mut profiles = Vec::new();
+    for q in &config.formats {
+        match q {
+            Quantizer::Uniform(spec) => formats.push(spec_to_value(spec)),
+            Quantizer::Piecewise(p) => {
+                let mut t = Value::table();
+                t.set("name", Value::Str(p.name.clone()));
+                t.set("fallback", spec_to_value(&p.fallback));
+                let regions = p
+                    .regions
+                    .iter()
+                    .map(|r| {
+                        let mut rt = Value::table();
+                        rt.set("min", Value::Float(r.min));
+                        rt.set("max", Value::Float(r.max));
+                        if let Value::Table(entries) = spec_to_value(&r.spec) {
+                            for (k, v) in entries {
+                                rt.set(k, v);
+                            }
+                        }
+                        rt
+                    })
+                    .collect();
+                t.set("region", Value::Array(regions));
+                profiles.push(t);
+            }
+        }
+    }
+    if !formats.is_empty() {
+        root.set("format", Value::Array(formats));
+    }
+    if !profiles.is_empty() {
+        root.set("profile", Value::Array(profiles));
+    }
+    root
+}
+
+fn parse_args(args: Vec<String>, global: &Global) -> Result<Cli, String> {
+    let mut config = default_config();
+    let mut dump_config = false;
+
+    // The config file is the base layer, so it is loaded before any other flag.
+    if let Some(pos) = args.iter().position(|a| a == "--config") {
+        let path = args
+            .get(pos + 1)
+            .ok_or_else(|| "--config requires a value".to_string())?;
+        let src = fs::read_to_string(path).map_err(|e| format!("cannot read '{path}': {e}"))?;
+        let root = parse_config_file(path, &src)?;
+        apply_config_file(&mut config, &root).map_err(|e| format!("{path}: {e}"))?;
+    }
+
+    let mut replace_on_format = true;
+    let mut focus_min: Option<f64> = None;
+    let mut focus_max: Option<f64> = None;
+    let mut focus_weight = 5.0;
+    let mut focus_sign = SignFilter::Any;
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                args.next();
+            }
+            "--dump-config" => {
+                dump_config = true;
+            }
+            "--k-min" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--k-min requires a value".to_string())?;
+                config.k_min = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --k-min '{v}': {e}"))?;
+            }
+            "--k-max" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--k-max requires a value".to_string())?;
+                config.k_max = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --k-max '{v}': {e}"))?;
+            }
+            "--k-step" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--k-step requires a value".to_string())?;
+                config.k_step = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --k-step '{v}': {e}"))?;
+            }
+            "--sweep" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--sweep requires a value".to_string())?;
+                config.sweep = SweepMode::parse(&v).map_err(|e| format!("invalid --sweep: {e}"))?;
+            }
+            "--x-min" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--x-min requires a value".to_string())?;
+                config.x_min = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --x-min '{v}': {e}"))?;
+            }
+            "--x-max" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--x-max requires a value".to_string())?;
+                config.x_max = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --x-max '{v}': {e}"))?;
+            }
+            "--x-step" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--x-step requires a value".to_string())?;
+                config.x_step = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --x-step '{v}': {e}"))?;
+            }
+            "--out" => {
+                config.out_prefix = args
+                    .next()
+                    .ok_or_else(|| "--out requires a value".to_string())?;
+                config.outputs = OutputPaths::default();
+            }
+            "--focus-min" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--focus-min requires a value".to_string())?;
+                focus_min = Some(
+                    v.parse::<f64>()
+                        .map_err(|e| format!("invalid --focus-min '{v}': {e}"))?,
+                );
+            }
+            "--focus-max" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--focus-max requires a value".to_string())?;
+                focus_max = Some(
+                    v.parse::<f64>()
+                        .map_err(|e| format!("invalid --focus-max '{v}': {e}"))?,
+                );
+            }
+            "--focus-weight" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--focus-weight requires a value".to_string())?;
+                focus_weight = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --focus-weight '{v}': {e}"))?;
+            }
+            "--focus-sign" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--focus-sign requires a value".to_string())?;
+                focus_sign =
+                    SignFilter::parse(&v).map_err(|e| format!("invalid --focus-sign: {e}"))?;
+            }
+            "--focus" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--focus requires a value".to_string())?;
+                let (n, sign) = parse_numbers_and_sign("--focus", &v, 3)?;
+                config.focus.push(WeightTerm::Window {
+                    min: n[0],
+                    max: n[1],
+                    weight: n[2],
+                    sign,
+                });
+            }
+            "--focus-gauss" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--focus-gauss requires a value".to_string())?;
+                let (n, sign) = parse_numbers_and_sign("--focus-gauss", &v, 3)?;
+                config.focus.push(WeightTerm::Gaussian {
+                    center: n[0],
+                    sigma: n[1],
+                    peak: n[2],
+                    sign,
+                });
+            }
+            "--focus-table" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--focus-table requires a value".to_string())?;
+                config.focus.push(parse_focus_table(&v)?);
+            }
+            "--maxerr-weight" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--maxerr-weight requires a value".to_string())?;
+                config.max_err_weight = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --maxerr-weight '{v}': {e}"))?;
+            }
+            "--underflow-penalty" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--underflow-penalty requires a value".to_string())?;
+                config.underflow_penalty = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --underflow-penalty '{v}': {e}"))?;
+            }
+            "--overflow-penalty" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--overflow-penalty requires a value".to_string())?;
+                config.overflow_penalty = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --overflow-penalty '{v}': {e}"))?;
+            }
+            "--format" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--format requires a value".to_string())?;
+                if replace_on_format {
+                    config.formats.clear();
+                    replace_on_format = false;
+                }
+                config.formats.push(Quantizer::Uniform(parse_format_arg(&v)?));
+            }
+            "--add-format" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--add-format requires a value".to_string())?;
+                config.formats.push(Quantizer::Uniform(parse_format_arg(&v)?));
+            }
+            "--json" => {
+                config.machine = Some(MachineFormat::Json);
+            }
+            "--html" => {
+                config.html = true;
+            }
+            "--png" => {
+                config.png = true;
+            }
+            "--tty-plot" => {
+                config.tty_plot = true;
+            }
+            "--tty-ascii" => {
+                config.tty_plot = true;
+                config.tty_ascii = true;
+            }
+            "--dpi" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--dpi requires a value".to_string())?;
+                config.dpi = v
+                    .parse::<f64>()
+                    .map_err(|e| format!("invalid --dpi '{v}': {e}"))?;
+                config.png = true;
+            }
+            "--heatmap" => {
+                config.heatmap.get_or_insert_with(Heatmap::default);
+            }
+            "--heatmap-mantissa" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--heatmap-mantissa requires a value".to_string())?;
+                config.heatmap.get_or_insert_with(Heatmap::default).mantissa_bits =
+                    parse_bit_range(&v, "--heatmap-mantissa")?;
+            }
+            "--heatmap-exp-bits" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--heatmap-exp-bits requires a value".to_string())?;
+                config.heatmap.get_or_insert_with(Heatmap::default).exponent_bits =
+                    parse_bit_range(&v, "--heatmap-exp-bits")?;
+            }
+            "--heatmap-metric" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--heatmap-metric requires a value".to_string())?;
+                config.heatmap.get_or_insert_with(Heatmap::default).metric =
+                    HeatMetric::parse(&v).map_err(|e| format!("invalid --heatmap-metric: {e}"))?;
+            }
+            "--format-out" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--format-out requires a value".to_string())?;
+                config.machine =
+                    Some(MachineFormat::parse(&v).map_err(|e| format!("invalid --format-out: {e}"))?);
+            }
+            "--no-presets" => {
+                config.formats.clear();
+                replace_on_format = false;
+            }
+            "--help" | "-h" => {
+                return Err(help_text());
+            }
+            _ => {
+                return Err(format!("unknown argument '{arg}'\n\n{}", help_text()));
+            }
+        }
+    }
+
+    if let (Some(a), Some(b)) = (focus_min, focus_max) {
+        if a.is_nan() || b.is_nan() || b <= a {
+            return Err("require --focus-max > --focus-min".to_string());
+        }
+        if !(focus_weight.is_finite() && focus_weight >= 1.0) {
+            return Err("--focus-weight must be >= 1".to_string());
+        }
+        config.focus.push(WeightTerm::Window {
+            min: a,
+            max: b,
+            weight: focus_weight,
+            sign: focus_sign,
+        });
+    }
+
+    // `--out-dir` wins over every path, whether it came from the defaults, a config or `--out`.
+    config.out_prefix = global.output(&config.out_prefix);
+    config.outputs.redirect(global);
+
+    validate(&config)?;
+    Ok(Cli {
+        config,
+        dump_config,
+    })
+}
+
+fn validate(config: &Config) -> Result<(), String> {
+    if config.formats.is_empty() {
+        return Err("no formats configured; use --format or remove --no-presets".to_string());
+    }
+    if !(config.dpi.is_finite() && (24.0..=1200.0).contains(&config.dpi)) {
+        return Err("--dpi must be in [24, 1200]".to_string());
+    }
+    if !(config.k_step.is_finite() && config.k_step > 0.0) {
+        return Err("--k-step must be > 0".to_string());
+    }
+    if !(config.k_min.is_finite() && config.k_max.is_finite() && config.k_max > config.k_min) {
+        return Err("require finite k range with --k-max > --k-min".to_string());
+    }
+    if config.sweep == SweepMode::Linear {
+        if !(config.x_step.is_finite() && config.x_step > 0.0) {
+            return Err("--x-step must be > 0".to_string());
+        }
+        if !(config.x_min.is_finite() && config.x_max.is_finite() && config.x_max > config.x_min)
+        {
+            return Err("require finite x range with --x-max > --x-min".to_string());
+        }
+    }
+    for term in &config.focus {
+        term.validate()?;
+    }
+    if let Some(h) = &config.heatmap {
+        let (m_lo, m_hi) = h.mantissa_bits;
+        let (e_lo, e_hi) = h.exponent_bits;
+        if m_lo > m_hi || m_hi > 52 {
+            return Err("heatmap mantissa bits must satisfy lo <= hi <= 52".to_string());
+        }
+        if e_lo > e_hi || e_lo < 2 || e_hi > 15 {
+            return Err("heatmap exponent bits must satisfy 2 <= lo <= hi <= 15".to_string());
+        }
+    }
+    if !(config.max_err_weight.is_finite() && config.max_err_weight >= 0.0) {
+        return Err("--maxerr-weight must be >= 0".to_string());
+    }
+    if !(config.underflow_penalty.is_finite() && config.underflow_penalty >= 0.0) {
+        return Err("--underflow-penalty must be >= 0".to_string());
+    }
+    if !(config.overflow_penalty.is_finite() && config.overflow_penalty >= 0.0) {
+        return Err("--overflow-penalty must be >= 0".to_string());
+    }
+    Ok(())
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest sweep [options]       Write the sweep charts, tables and reports",
+        "  prectest rank [options]        Print the ranking table only",
+        "  cargo run --bin soft_float_explorer -- [options]",
+        "",
+        "Options:",
+        "  --config <path.toml|path.json> Load settings from a file; other flags override it",
+        "  --dump-config                  Print the effective config as TOML and exit",
+        "  --k-min <f64>                  Default: -20",
+        "  --k-max <f64>                  Default: 20",
+        "  --k-step <f64>                 Default: 0.1",
+        "  --sweep log|signed|linear      Default: log (x = 10^k); signed adds -10^k and 0",
+        "  --x-min <f64>                  Linear sweep lower x, default: -1",
+        "  --x-max <f64>                  Linear sweep upper x, default: 2",
+        "  --x-step <f64>                 Linear sweep step, default: 0.01",
+        "  --out <path-prefix>            Default: docs/soft_float_sweep (clears file output paths)",
+        "  --json                         Also write <prefix>.json (config, specs, metrics, samples)",
+        "  --format-out json|jsonl        Machine-readable layout; jsonl writes <prefix>.jsonl",
+        "  --html                         Also write <prefix>.html (interactive, self-contained)",
+        "  --png                          Also write a .png next to every .svg (built-in rasterizer)",
+        "  --tty-plot                     Also print the error curves and ranking as terminal charts",
+        "  --tty-ascii                    Like --tty-plot, with ASCII instead of braille characters",
+        "  --dpi <f64>                    PNG resolution, 96 = one pixel per SVG unit; default: 96 (implies --png)",
+        "  --heatmap                      Also write <prefix>_heatmap.svg over a mantissa x exponent grid",
+        "  --heatmap-mantissa lo:hi       Mantissa bits in the grid, default: 1:16 (implies --heatmap)",
+        "  --heatmap-exp-bits lo:hi       Exponent field widths, default: 2:8 (implies --heatmap)",
+        "  --heatmap-metric score|mean|max|clip  Colored metric, default: score (implies --heatmap)",
+        "  --focus-min <f64>              Optional focus interval lower k",
+        "  --focus-max <f64>              Optional focus interval upper k",
+        "  --focus-weight <f64>           Default: 5 (>=1)",
+        "  --focus-sign any|pos|neg       Sign filter for the --focus-min/--focus-max window",
+        "  --focus k_min,k_max,w[,sign]   Add a constant-weight window (repeatable)",
+        "  --focus-gauss c,sigma,peak[,sign] Add a Gaussian weight in k (repeatable)",
+        "  --focus-table k:w,k:w,...[,sign] Add a piecewise-linear weight in k (repeatable)",
+        "  --maxerr-weight <f64>          Default: 0.5",
+        "  --underflow-penalty <f64>      Default: 4",
+        "  --overflow-penalty <f64>       Default: 4",
+        "  --no-presets                   Start with no built-in formats",
+        "  --format <preset|name,m,min_e,max_e>     Replace presets with one format",
+        "  --add-format <preset|name,m,min_e,max_e> Add another format",
+        "",
+        "Examples:",
+        "  cargo run --bin soft_float_explorer",
+        "  cargo run --bin soft_float_explorer -- --format custom,11,-40,40",
+        "  cargo run --bin soft_float_explorer -- --no-presets --add-format a,5,-10,10 --add-format b,12,-20,20",
+        "  cargo run --bin soft_float_explorer -- --focus -6,-3,4 --focus 0,2,8 --focus-gauss 5,1.5,3",
+        "  cargo run --bin soft_float_explorer -- --sweep linear --x-min -1 --x-max 2",
+        "  cargo run --bin soft_float_explorer -- --config sweep.toml --k-step 0.05 --dump-config",
+        "  cargo run --bin soft_float_explorer -- --heatmap --heatmap-metric mean",
+        "  cargo run -- rank --format bf16_like --add-format fp16_like --out-dir /tmp/sweep",
+    ]
+    .join("\n")
+}
+
+fn sample_k_values(k_min: f64, k_max: f64, step: f64) -> Vec<f64> {
+    let mut out = Vec::new();
+    let mut k = k_min;
+    while k <= k_max + step * 0.5 {
+        out.push(k);
+        k += step;
+    }
+    out
+}
+
+fn sweep_samples(config: &Config) -> Vec<Sample> {
+    let ks = sample_k_values(config.k_min, config.k_max, config.k_step);
+    match config.sweep {
+        SweepMode::Log => ks.iter().map(|&k| Sample { k, x: 10f64.powf(k) }).collect(),
+        SweepMode::Signed => {
+            let mut out: Vec<Sample> = ks
+                .iter()
+                .rev()
+                .map(|&k| Sample {
+                    k,
+                    x: -10f64.powf(k),
+                })
+                .collect();
+            out.push(Sample {
+                k: f64::NEG_INFINITY,
+                x: 0.0,
+            });
+            out.extend(ks.iter().map(|&k| Sample { k, x: 10f64.powf(k) }));
+            out
+        }
+        SweepMode::Linear => {
+            let n = ((config.x_max - config.x_min) / config.x_step + 0.5).floor() as usize;
+            (0..=n)
+                .map(|i| {
+                    let x = config.x_min + i as f64 * config.x_step;
+                    Sample {
+                        k: x.abs().log10(),
+                        x,
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+fn sweep_label(config: &Config) -> String {
+    match config.sweep {
+        SweepMode::Log => format!("k in [{:.1}, {:.1}]", config.k_min, config.k_max),
+        SweepMode::Signed => format!(
+            "x = ±10^k and 0, k in [{:.1}, {:.1}]",
+            config.k_min, config.k_max
+        ),
+        SweepMode::Linear => format!("x in [{}, {}]", config.x_min, config.x_max),
+    }
+}
+
+/// Largest weight among the focus terms covering this sample, or 1 if none do.
+fn sample_weight(config: &Config, k: f64, x: f64) -> f64 {
+    config
+        .focus
+        .iter()
+        .filter_map(|term| term.weight_at(k, x))
+        .reduce(f64::max)
+        .unwrap_or(1.0)
+}
+
+fn relative_error(x: f64, q: f64) -> f64 {
+    if x != 0.0 {
+        (q - x).abs() / x.abs()
+    } else if q == 0.0 {
+        0.0
+    } else {
+        f64::INFINITY
+    }
+}
+
+fn compute_metrics(fmt: &Quantizer, config: &Config, samples: &[Sample]) -> FormatMetrics {
+    let mut total_weight = 0.0f64;
+    let mut finite_count = 0usize;
+    let mut underflow_weight = 0.0f64;
+    let mut overflow_weight = 0.0f64;
+    let mut inexact_weight = 0.0f64;
+    let mut rel_sum_weighted = 0.0f64;
+    let mut rel_weight_total = 0.0f64;
+    let mut rel_max = 0.0f64;
+
+    for &Sample { k, x } in samples {
+        let w = sample_weight(config, k, x);
+        total_weight += w;
+        let (q, flags) = fmt.quantize_flags(x, Tininess::BeforeRounding);
+
+        if flags.contains(Flags::INEXACT) {
+            inexact_weight += w;
+        }
+        if flags.contains(Flags::UNDERFLOW) {
+            underflow_weight += w;
+        }
+        if flags.contains(Flags::OVERFLOW) {
+            overflow_weight += w;
+        }
+        if !q.is_finite() {
+            continue;
+        }
+
+        let rel = relative_error(x, q);
+        if rel.is_finite() {
+            finite_count += 1;
+            rel_sum_weighted += rel * w;
+            rel_weight_total += w;
+            if rel > rel_max {
+                rel_max = rel;
+            }
+        }
+    }
+
+    let finite_frac = finite_count as f64 / samples.len() as f64;
+    let underflow_frac = if total_weight > 0.0 {
+        underflow_weight / total_weight
+    } else {
+        0.0
+    };
+    let overflow_frac = if total_weight > 0.0 {
+        overflow_weight / total_weight
+    } else {
+        0.0
+    };
+    let inexact_frac = if total_weight > 0.0 {
+        inexact_weight / total_weight
+    } else {
+        0.0
+    };
+
+    let mean_rel_err = if rel_weight_total > 0.0 {
+        rel_sum_weighted / rel_weight_total
+    } else {
+        f64::INFINITY
+    };
+    let max_rel_err = if finite_count > 0 { rel_max } else { f64::INFINITY };
+
+    // Lower is better:
+    // - average and worst relative error matter
+    // - underflow/overflow rates are strongly penalized
+    let score = if finite_count > 0 {
+        mean_rel_err.max(1e-30).log10()
+            + config.max_err_weight * max_rel_err.max(1e-30).log10()
+            + config.underflow_penalty * underflow_frac
+            + config.overflow_penalty * overflow_frac
+    } else {
+        f64::INFINITY
+    };
+
+    FormatMetrics {
+        name: fmt.name().to_string(),
+        total_bits: fmt.total_bits(),
+        mean_rel_err,
+        max_rel_err,
+        underflow_frac,
+        overflow_frac,
+        inexact_frac,
+        finite_frac,
+        score,
+    }
+}
+
+fn ranked_metrics(config: &Config, samples: &[Sample]) -> Vec<FormatMetrics> {
+    let mut metrics: Vec<FormatMetrics> = config
+        .formats
+        .iter()
+        .map(|fmt| compute_metrics(fmt, config, samples))
+        .collect();
+
+    metrics.sort_by(|a, b| a.score.total_cmp(&b.score));
+    metrics
+}
+
+fn write_csv(config: &Config, samples: &[Sample], csv_path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut out = String::from("format,k,x,quantized,abs_error,rel_error\n");
+
+    for fmt in &config.formats {
+        for &Sample { k, x } in samples {
+            let q = fmt.quantize(x);
+            let abs_err = (q - x).abs();
+            let rel_err = if x != 0.0 { abs_err / x.abs() } else { 0.0 };
+            out.push_str(&format!(
+                "{},{:.6},{:.16e},{:.16e},{:.16e},{:.16e}\n",
+                fmt.name(), k, x, q, abs_err, rel_err
+            ));
+        }
+    }
+
+    fs::write(csv_path, out)?;
+    Ok(())
+}
+
+fn write_summary(config: &Config, summary_path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut out = String::new();
+    out.push_str("---\n");
+    out.push_str("title: Soft Float Sweep Summary\n");
+    out.push_str("---\n\n");
+    out.push_str("# Soft Float Sweep Summary\n\n");
+    out.push_str("This file is generated by `soft_float_explorer`.\n\n");
+    out.push_str("## Config\n\n");
+    match config.sweep {
+        SweepMode::Log => out.push_str(&format!(
+            "- k range: [{:.2}, {:.2}] step {:.3}\n",
+            config.k_min, config.k_max, config.k_step
+        )),
+        SweepMode::Signed => out.push_str(&format!(
+            "- signed sweep: x = -10^k, 0 and +10^k for k in [{:.2}, {:.2}] step {:.3}\n",
+            config.k_min, config.k_max, config.k_step
+        )),
+        SweepMode::Linear => out.push_str(&format!(
+            "- linear sweep: x in [{}, {}] step {}\n",
+            config.x_min, config.x_max, config.x_step
+        )),
+    }
+    out.push_str(&format!("- number of formats: {}\n\n", config.formats.len()));
+
+    out.push_str("## Formats\n\n");
+    out.push_str("| name | mantissa bits | min exp2 | max exp2 | min normal | max finite | epsilon at 1 |\n");
+    out.push_str("| --- | ---: | ---: | ---: | ---: | ---: | ---: |\n");
+    let mut rows: Vec<(String, &SoftFloatSpec)> = Vec::new();
+    for q in &config.formats {
+        match q {
+            Quantizer::Uniform(spec) => rows.push((spec.name.clone(), spec)),
+            Quantizer::Piecewise(p) => {
+                for r in &p.regions {
+                    rows.push((format!("{} [{}, {})", p.name, r.min, r.max), &r.spec));
+                }
+                rows.push((format!("{} (fallback)", p.name), &p.fallback));
+            }
+        }
+    }
+    for (name, fmt) in rows {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {:.3e} | {:.3e} | {:.3e} |\n",
+            name,
+            fmt.mantissa_bits,
+            fmt.min_exp2,
+            fmt.max_exp2,
+            fmt.min_normal(),
+            fmt.max_finite(),
+            fmt.epsilon_at_one()
+        ));
+    }
+
+    fs::write(summary_path, out)?;
+    Ok(())
+}
+
+fn write_ranking(
+    config: &Config,
+    samples: &[Sample],
+    ranking_path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let metrics = ranked_metrics(config, samples);
+
+    let mut out = String::new();
+    out.push_str("---\n");
+    out.push_str("title: Soft Float Ranking\n");
+    out.push_str("---\n\n");
+    out.push_str("# Soft Float Ranking\n\n");
+    out.push_str("This file is generated by `soft_float_explorer`.\n\n");
+    out.push_str("Formats are ranked by a heuristic score (lower is better):\n\n");
+    out.push_str(&format!(
+        "- `score = log10(mean_rel_err) + {:.3}*log10(max_rel_err) + {:.3}*underflow_frac + {:.3}*overflow_frac`\n",
+        config.max_err_weight, config.underflow_penalty, config.overflow_penalty
+    ));
+    match config.focus.as_slice() {
+        [] => out.push_str("- Focus weighting disabled: all k samples weighted equally\n"),
+        [WeightTerm::Window {
+            min,
+            max,
+            weight,
+            sign: SignFilter::Any,
+        }] => out.push_str(&format!(
+            "- Focus weighting enabled: k in [{:.3}, {:.3}] gets weight {:.3} in mean error and clipping rates\n",
+            min, max, weight
+        )),
+        terms => {
+            out.push_str("- Focus weighting enabled in mean error and clipping rates; a sample covered by several terms takes the largest weight, uncovered samples weigh 1:\n");
+            for term in terms {
+                out.push_str(&format!("  - {}\n", term.describe()));
+            }
+        }
+    }
+    out.push_str("- This favors low relative error while penalizing clipping to zero/infinity.\n\n");
+
+    out.push_str("| rank | format | score | mean rel err | max rel err | underflow % | overflow % | inexact % | finite % |\n");
+    out.push_str("| ---: | --- | ---: | ---: | ---: | ---: | ---: | ---: | ---: |\n");
+    for (idx, m) in metrics.iter().enumerate() {
+        out.push_str(&format!(
+            "| {} | {} | {:.4} | {:.3e} | {:.3e} | {:.2}% | {:.2}% | {:.2}% | {:.2}% |\n",
+            idx + 1,
+            m.name,
+            m.score,
+            m.mean_rel_err,
+            m.max_rel_err,
+            m.underflow_frac * 100.0,
+            m.overflow_frac * 100.0,
+            m.inexact_frac * 100.0,
+            m.finite_frac * 100.0,
+        ));
+    }
+
+    fs::write(ranking_path, out)?;
+    Ok(())
+}
+
+fn sweep_figure(config: &Config, samples: &[Sample]) -> Figure {
+    let width = 1400.0;
+    let height = 860.0;
+    let (x0, x1, y0, y1) = (90.0, width - 40.0, 80.0, height - 90.0);
+    let err_floor = 1e-18;
+
+    // Log sweeps plot against k; signed and linear sweeps use a symlog x axis.
+    let (title, x_axis) = match config.sweep {
+        SweepMode::Log => (
+            "Soft float precision sweep (relative error at x = 10^k)".to_string(),
+            Axis::linear("k where x = 10^k", config.k_min, config.k_max).ticks(Ticks::Every(5.0)),
+        ),
+        _ => {
+            let (lin, lo, hi) = match config.sweep {
+                SweepMode::Linear => (config.x_step, config.x_min, config.x_max),
+                _ => {
+                    let top = 10f64.powf(config.k_max);
+                    (10f64.powf(config.k_min), -top, top)
+                }
+            };
+            (
+                format!("Soft float precision sweep (relative error, {})", sweep_label(config)),
+                Axis::symlog(
+                    format!("x (symmetric log axis, linear within ±{lin:.3e})"),
+                    lo,
+                    hi,
+                    lin,
+                )
+                .ticks(Ticks::Auto(12)),
+            )
+        }
+    };
+    let x_of = |s: &Sample| match config.sweep {
+        SweepMode::Log => s.k,
+        _ => s.x,
+    };
+
+    let mut panel = Panel::new(
+        Rect::new(x0, y0, x1, y1),
+        x_axis,
+        Axis::linear("log10(relative error)", -18.0, 0.5).ticks(Ticks::Every(1.0)),
+    );
+    let mut legend = Vec::new();
+    for (idx, fmt) in config.formats.iter().enumerate() {
+        let color = palette_color(idx);
+        let points = samples
+            .iter()
+            .map(|sample| {
+                let x = sample.x;
+                let q = fmt.quantize(x);
+                let rel_err = if x != 0.0 { (q - x).abs() / x.abs() } else { 0.0 };
+                (x_of(sample), rel_err.max(err_floor).log10())
+            })
+            .collect();
+        panel.series.push(Series::line(fmt.name(), color, points));
+        legend.push((format!("{} ({})", fmt.name(), fmt.describe()), color.to_string()));
+    }
+
+    Figure {
+        panels: vec![panel],
+        legend: Some(Legend {
+            layout: LegendLayout::Column {
+                x: x1 - 210.0,
+                y: 58.0,
+                spacing: 22.0,
+            },
+            font_size: 14.0,
+            entries: legend,
+        }),
+        ..Figure::new(width, height).title(title)
+    }
+}
+
+/// Writes `figure` to `svg_path`, plus the same path with `.png` when PNG output is on.
+fn save_figure(config: &Config, figure: &Figure, svg_path: &Path) -> Result<(), Box<dyn Error>> {
+    figure.write_svg(svg_path)?;
+    if config.png {
+        figure.write_png(&svg_path.with_extension("png"), config.dpi)?;
+    }
+    Ok(())
+}
+
+fn write_svg(config: &Config, samples: &[Sample], svg_path: &Path) -> Result<(), Box<dyn Error>> {
+    save_figure(config, &sweep_figure(config, samples), svg_path)
+}
+
+fn print_tty(config: &Config, samples: &[Sample]) {
+    let opts = TtyOptions::detect(config.tty_ascii);
+    let figure = sweep_figure(config, samples);
+    if let Some(panel) = figure.panels.into_iter().next() {
+        let panel = Panel {
+            caption: figure.title,
+            ..panel
+        };
+        println!("\n{}", render_panel(&panel, &opts));
+    }
+
+    let bars: Vec<Bar> = ranked_metrics(config, samples)
+        .iter()
+        .enumerate()
+        .map(|(rank, m)| {
+            let idx = config.formats.iter().position(|f| f.name() == m.name);
+            Bar {
+                label: format!("{}. {} ({} bits)", rank + 1, m.name, m.total_bits),
+                value: m.score,
+                note: format!("{:.3} (mean rel err {:.2e})", m.score, m.mean_rel_err),
+                color: palette_color(idx.unwrap_or(rank)).to_string(),
+            }
+        })
+        .collect();
+    print!(
+        "{}",
+        render_bars("Ranking by score (lower is better)", &bars, &opts)
+    );
+}
+
+fn write_html(config: &Config, samples: &[Sample], html_path: &Path) -> Result<(), Box<dyn Error>> {
+    let figure = sweep_figure(config, samples);
+    let title = figure.title.clone().unwrap_or_default();
+    let mut panel = figure.panels.into_iter().next().ok_or("sweep figure has no panel")?;
+    panel.x.label = match config.sweep {
+        SweepMode::Log => "k (x = 10^k)".to_string(),
+        _ => "x".to_string(),
+    };
+
+    // The x axis already shows k (log sweeps) or x (signed/linear), so list the rest.
+    let log_sweep = matches!(config.sweep, SweepMode::Log);
+    let mut columns = vec!["quantized", "abs error", "rel error", "total bits"];
+    if log_sweep {
+        columns.insert(0, "x");
+    }
+    let tooltips = config
+        .formats
+        .iter()
+        .map(|fmt| Tooltip {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: samples
+                .iter()
+                .map(|s| {
+                    let q = fmt.quantize(s.x);
+                    let mut row = vec![
+                        q,
+                        (q - s.x).abs(),
+                        relative_error(s.x, q),
+                        fmt.total_bits() as f64,
+                    ];
+                    if log_sweep {
+                        row.insert(0, s.x);
+                    }
+                    row
+                })
+                .collect(),
+        })
+        .collect();
+    for (series, fmt) in panel.series.iter_mut().zip(&config.formats) {
+        series.name = format!("{} ({}, {} bits)", fmt.name(), fmt.describe(), fmt.total_bits());
+    }
+
+    let chart = Chart {
+        title: "Relative error by format".to_string(),
+        panel,
+        tooltips,
+    };
+    let subtitle = format!(
+        "{}; {} formats; errors below 1e-18 are drawn at 1e-18",
+        sweep_label(config),
+        config.formats.len()
+    );
+    fs::write(html_path, render_page(&title, &subtitle, &[chart]))?;
+    Ok(())
+}
+
+/// Marks formats that are not dominated on (total bits, value); lower is better for both.
+fn pareto_front(points: &[(u32, f64)]) -> Vec<bool> {
+    points
+        .iter()
+        .map(|&(bits, val)| {
+            val.is_finite()
+                && !points
+                    .iter()
+                    .any(|&(b, v)| v.is_finite() && b <= bits && v <= val && (b < bits || v < val))
+        })
+        .collect()
+}
+
+type MetricFn = fn(&FormatMetrics) -> f64;
+
+fn write_pareto_svg(
+    config: &Config,
+    metrics: &[FormatMetrics],
+    svg_path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let panel_w = 440.0;
+    let panel_h = 520.0;
+    let gap = 90.0;
+    let left = 90.0;
+    let top = 110.0;
+    let width = left + 3.0 * panel_w + 2.0 * gap + 40.0;
+    let height = top + panel_h + 90.0;
+
+    let metric_panels: [(&str, MetricFn); 3] = [
+        ("score (lower is better)", |m| m.score),
+        ("log10(mean relative error)", |m| {
+            m.mean_rel_err.max(1e-18).log10()
+        }),
+        ("log10(max relative error)", |m| {
+            m.max_rel_err.max(1e-18).log10()
+        }),
+    ];
+
+    let bits_min = metrics.iter().map(|m| m.total_bits).min().unwrap_or(0) as f64 - 1.0;
+    let bits_max = metrics.iter().map(|m| m.total_bits).max().unwrap_or(0) as f64 + 1.0;
+    let bits_step = ((bits_max - bits_min) / 10.0).ceil().max(1.0);
+
+    let rects = Rect::new(left, top, width - 40.0, top + panel_h).grid(1, 3, gap, 0.0);
+    let mut panels = Vec::new();
+    for ((y_label, value_of), rect) in metric_panels.iter().zip(rects) {
+        let points: Vec<(u32, f64)> = metrics
+            .iter()
+            .map(|m| (m.total_bits, value_of(m)))
+            .collect();
+        let optimal = pareto_front(&points);
+
+        let (mut y_min, mut y_max) = points
+            .iter()
+            .map(|p| p.1)
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        if !(y_min.is_finite() && y_max.is_finite()) {
+            y_min = 0.0;
+            y_max = 1.0;
+        }
+        let pad = ((y_max - y_min) * 0.08).max(0.5);
+
+        let mut panel = Panel::new(
+            rect,
+            Axis::linear("total bits (sign + exponent + mantissa)", bits_min, bits_max)
+                .ticks(Ticks::Every(bits_step)),
+            Axis::linear(*y_label, y_min - pad, y_max + pad),
+        );
+
+        // Pareto staircase: sorted by bits, each step only ever improves the value.
+        let mut front: Vec<(f64, f64)> = points
+            .iter()
+            .zip(&optimal)
+            .filter(|(_, &opt)| opt)
+            .map(|(&(b, v), _)| (b as f64, v))
+            .collect();
+        front.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if !front.is_empty() {
+            panel
+                .series
+                .push(Series::step("Pareto front", "#888", front).width(1.5).dashed());
+        }
+
+        for (idx, (m, &(bits, val))) in metrics.iter().zip(&points).enumerate() {
+            if !val.is_finite() {
+                continue;
+            }
+            let radius = if optimal[idx] { 6.0 } else { 5.0 };
+            panel.series.push(Series::scatter(
+                &m.name,
+                palette_color(idx),
+                vec![(bits as f64, val)],
+                radius,
+                optimal[idx],
+            ));
+            if optimal[idx] {
+                panel.labels.push(PointLabel {
+                    x: bits as f64,
+                    y: val,
+                    dx: 9.0,
+                    dy: -8.0,
+                    text: format!("{} ({}b)", m.name, bits),
+                    size: 13.0,
+                    bold: true,
+                    halo: false,
+                });
+            }
+        }
+        panels.push(panel);
+    }
+
+    let figure = Figure {
+        title_size: 30.0,
+        subtitle: Some(
+            "Filled, labeled points are not beaten on both size and error by any other format; hollow points are dominated."
+                .to_string(),
+        ),
+        panels,
+        ..Figure::new(width, height).title(format!(
+            "Bits vs. error: Pareto-optimal formats ({})",
+            sweep_label(config)
+        ))
+    };
+    save_figure(config, &figure, svg_path)
+}
+
+fn write_heatmap_svg(
+    config: &Config,
+    heatmap: &Heatmap,
+    samples: &[Sample],
+    svg_path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let (m_lo, m_hi) = heatmap.mantissa_bits;
+    let (e_lo, e_hi) = heatmap.exponent_bits;
+    let metric = heatmap.metric;
+
+    let mut grid = Vec::new();
+    for w in e_lo..=e_hi {
+        for m in m_lo..=m_hi {
+            let spec = SoftFloatSpec::ieee_like(format!("m{m}e{w}"), m, w);
+            let metrics = compute_metrics(&Quantizer::Uniform(spec), config, samples);
+            grid.push((m, w, metric.value(&metrics)));
+        }
+    }
+
+    let (v_min, v_max) = grid
+        .iter()
+        .map(|c| c.2)
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    let (v_min, v_max) = if v_min.is_finite() {
+        (v_min, if v_max > v_min { v_max } else { v_min + 1.0 })
+    } else {
+        (0.0, 1.0)
+    };
+    let best = grid
+        .iter()
+        .filter(|c| c.2.is_finite())
+        .min_by(|a, b| a.2.total_cmp(&b.2).then(a.0.cmp(&b.0)).then(a.1.cmp(&b.1)));
+
+    let width = 1400.0;
+    let height = 860.0;
+    let rect = Rect::new(90.0, 110.0, width - 190.0, height - 90.0);
+    let x_ticks = if m_hi - m_lo <= 32 {
+        Ticks::Every(1.0)
+    } else {
+        Ticks::Auto(16)
+    };
+    let mut panel = Panel::new(
+        rect,
+        Axis::linear("mantissa bits", m_lo as f64 - 0.5, m_hi as f64 + 0.5).ticks(x_ticks),
+        Axis::linear("exponent bits (IEEE-style bias)", e_lo as f64 - 0.5, e_hi as f64 + 0.5)
+            .ticks(Ticks::Every(1.0)),
+    );
+
+    let value_label = |v: f64| match metric {
+        HeatMetric::MeanErr | HeatMetric::MaxErr => format!("{v:.1}"),
+        _ if v.abs() >= 100.0 => format!("{v:.0}"),
+        _ => format!("{v:.2}"),
+    };
+    for &(m, w, v) in &grid {
+        let color = if v.is_finite() {
+            colormap((v - v_min) / (v_max - v_min))
+        } else {
+            "#bbbbbb".to_string()
+        };
+        panel.cells.push(Cell {
+            x0: m as f64 - 0.5,
+            x1: m as f64 + 0.5,
+            y0: w as f64 - 0.5,
+            y1: w as f64 + 0.5,
+            color,
+            label: v.is_finite().then(|| value_label(v)),
+        });
+    }
+    panel.colorbar = Some(ColorBar {
+        label: format!("{} (lower is better)", metric.label()),
+        min: v_min,
+        max: v_max,
+        colors: (0..64).map(|i| colormap(i as f64 / 63.0)).collect(),
+    });
+
+    if let Some(&(m, w, _)) = best {
+        panel.series.push(
+            Series::line(
+                "best",
+                "#d50000",
+                vec![
+                    (m as f64 - 0.5, w as f64 - 0.5),
+                    (m as f64 + 0.5, w as f64 - 0.5),
+                    (m as f64 + 0.5, w as f64 + 0.5),
+                    (m as f64 - 0.5, w as f64 + 0.5),
+                    (m as f64 - 0.5, w as f64 - 0.5),
+                ],
+            )
+            .width(3.0),
+        );
+    }
+
+    // Configured uniform formats on top; "~" when their exponent range is not the IEEE one.
+    for q in &config.formats {
+        let Quantizer::Uniform(spec) = q else {
+            continue;
+        };
+        let (m, w) = (spec.mantissa_bits, spec.exponent_bits());
+        if !(m_lo..=m_hi).contains(&m) || !(e_lo..=e_hi).contains(&w) {
+            continue;
+        }
+        let ieee = SoftFloatSpec::ieee_like("", m, w);
+        let exact = ieee.min_exp2 == spec.min_exp2 && ieee.max_exp2 == spec.max_exp2;
+        panel.series.push(Series::scatter(
+            &spec.name,
+            "#ffffff",
+            vec![(m as f64, w as f64)],
+            5.0,
+            true,
+        ));
+        panel.labels.push(PointLabel {
+            x: m as f64,
+            y: w as f64,
+            dx: 7.0,
+            dy: -7.0,
+            text: if exact {
+                spec.name.clone()
+            } else {
+                format!("~{}", spec.name)
+            },
+            size: 12.0,
+            bold: true,
+            halo: true,
+        });
+    }
+
+    let best_note = best
+        .map(|&(m, w, v)| {
+            format!(
+                "Best (red outline): m={m}, exponent bits={w}, {} bits total, {} = {}.",
+                1 + m + w,
+                metric.name(),
+                value_label(v)
+            )
+        })
+        .unwrap_or_default();
+    let figure = Figure {
+        title_size: 28.0,
+        subtitle: Some(format!(
+            "Each cell is an IEEE-style format without subnormals; white dots are the configured formats. {best_note}"
+        )),
+        panels: vec![panel],
+        ..Figure::new(width, height).title(format!(
+            "Design space: {} over mantissa x exponent bits ({})",
+            metric.label(),
+            sweep_label(config)
+        ))
+    };
+    save_figure(config, &figure, svg_path)
+}
+
+fn metrics_json(m: &FormatMetrics, rank: usize) -> Value {
+    let mut t = Value::table();
+    t.set("rank", Value::Int(rank as i64));
+    t.set("score", Value::Float(m.score));
+    t.set("mean_rel_err", Value::Float(m.mean_rel_err));
+    t.set("max_rel_err", Value::Float(m.max_rel_err));
+    t.set("underflow_frac", Value::Float(m.underflow_frac));
+    t.set("overflow_frac", Value::Float(m.overflow_frac));
+    t.set("inexact_frac", Value::Float(m.inexact_frac));
+    t.set("finite_frac", Value::Float(m.finite_frac));
+    t
+}
+
+/// Schema `prectest.sweep`: `config`, then `formats` (spec fields plus
+/// `metrics`, in config order) and `samples` (one per format and input).
+fn write_machine(
+    config: &Config,
+    samples: &[Sample],
+    format: MachineFormat,
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let metrics = ranked_metrics(config, samples);
+
+    let mut header = report_header("prectest.sweep", "soft_float_explorer");
+    header.set("config", config_to_value(config));
+
+    let formats = config
+        .formats
+        .iter()
+        .map(|q| {
+            let mut t = quantizer_json(q);
+            if let Some((rank, m)) = metrics
+                .iter()
+                .enumerate()
+                .find(|(_, m)| m.name == q.name())
+            {
+                t.set("metrics", metrics_json(m, rank + 1));
+            }
+            t
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    for fmt in &config.formats {
+        for &Sample { k, x } in samples {
+            let q = fmt.quantize(x);
+            let mut t = Value::table();
+            t.set("format", Value::Str(fmt.name().to_string()));
+            t.set("k", Value::Float(k));
+            t.set("x", Value::Float(x));
+            t.set("quantized", Value::Float(q));
+            t.set("abs_error", Value::Float((q - x).abs()));
+            t.set("rel_error", Value::Float(relative_error(x, q)));
+            rows.push(t);
+        }
+    }
+
+    let out = render(
+        format,
+        header,
+        vec![("formats", "format", formats), ("samples", "sample", rows)],
+    );
+    fs::write(path, out)?;
+    Ok(())
+}
+
+pub fn main(args: Vec<String>, global: &Global) -> Result<(), Box<dyn Error>> {
+    let Cli {
+        config,
+        dump_config,
+    } = match parse_args(args, global) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    if dump_config {
+        print!("{}", to_toml(&config_to_value(&config)));
+        return Ok(());
+    }
+
+    let samples = sweep_samples(&config);
+
+    let svg_path_s = config.output_path(&config.outputs.svg, ".svg");
+    let csv_path_s = config.output_path(&config.outputs.csv, ".csv");
+    let md_path_s = config.output_path(&config.outputs.summary, "_summary.md");
+    let ranking_path_s = config.output_path(&config.outputs.ranking, "_ranking.md");
+    let pareto_path_s = config.output_path(&config.outputs.pareto, "_pareto.svg");
+    let machine_path_s = config.machine.map(|m| {
+        config.output_path(&config.outputs.json, &format!(".{}", m.extension()))
+    });
+    let heatmap_path_s = config
+        .heatmap
+        .as_ref()
+        .map(|_| config.output_path(&config.outputs.heatmap, "_heatmap.svg"));
+    let html_path_s = config
+        .html
+        .then(|| config.output_path(&config.outputs.html, ".html"));
+
+    for path in [
+        &svg_path_s,
+        &csv_path_s,
+        &md_path_s,
+        &ranking_path_s,
+        &pareto_path_s,
+    ]
+    .into_iter()
+    .chain(&machine_path_s)
+    .chain(&html_path_s)
+    .chain(&heatmap_path_s)
+    {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    write_svg(&config, &samples, Path::new(&svg_path_s))?;
+    write_csv(&config, &samples, Path::new(&csv_path_s))?;
+    write_summary(&config, Path::new(&md_path_s))?;
+    write_ranking(&config, &samples, Path::new(&ranking_path_s))?;
+    write_pareto_svg(
+        &config,
+        &ranked_metrics(&config, &samples),
+        Path::new(&pareto_path_s),
+    )?;
+
+    let wrote_figure = |path: &str| {
+        global.wrote(path);
+        if config.png {
+            global.wrote(Path::new(path).with_extension("png").display());
+        }
+    };
+    wrote_figure(&svg_path_s);
+    global.wrote(&csv_path_s);
+    global.wrote(&md_path_s);
+    global.wrote(&ranking_path_s);
+    wrote_figure(&pareto_path_s);
+
+    if let (Some(format), Some(path)) = (config.machine, &machine_path_s) {
+        write_machine(&config, &samples, format, Path::new(path))?;
+        global.wrote(path);
+    }
+    if let (Some(heatmap), Some(path)) = (&config.heatmap, &heatmap_path_s) {
+        write_heatmap_svg(&config, heatmap, &samples, Path::new(path))?;
+        wrote_figure(path);
+    }
+    if let Some(path) = &html_path_s {
+        write_html(&config, &samples, Path::new(path))?;
+        global.wrote(path);
+    }
+    if config.tty_plot {
+        print_tty(&config, &samples);
+    }
+
+    Ok(())
+}
+
+fn ranking_table(metrics: &[FormatMetrics]) -> String {
+    let name_w = metrics
+        .iter()
+        .map(|m| m.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("format".len());
+    let mut out = format!(
+        "{:>4}  {:<name_w$}  {:>4}  {:>8}  {:>12}  {:>12}  {:>9}  {:>9}\n",
+        "rank", "format", "bits", "score", "mean_rel", "max_rel", "underflow", "overflow"
+    );
+    for (rank, m) in metrics.iter().enumerate() {
+        out.push_str(&format!(
+            "{:>4}  {:<name_w$}  {:>4}  {:>8.3}  {:>12.3e}  {:>12.3e}  {:>8.2}%  {:>8.2}%\n",
+            rank + 1,
+            m.name,
+            m.total_bits,
+            m.score,
+            m.mean_rel_err,
+            m.max_rel_err,
+            100.0 * m.underflow_frac,
+            100.0 * m.overflow_frac
+        ));
+    }
+    out
+}
+
+/// `prectest rank`: the sweep's ranking printed as a table, without writing files.
+pub fn rank(args: Vec<String>, global: &Global) -> Result<(), Box<dyn Error>> {
+    let Cli {
+        config,
+        dump_config,
+    } = match parse_args(args, global) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+    if dump_config {
+        print!("{}", to_toml(&config_to_value(&config)));
+        return Ok(());
+    }
+
+    let samples = sweep_samples(&config);
+    print!("{}", ranking_table(&ranked_metrics(&config, &samples)));
+    if config.tty_plot {
+        print_tty(&config, &samples);
+    }
+    Ok(())
+}
+
+/// Design space for `prectest search`, taken out of the arguments before the
+/// sweep options are parsed.
+struct Search {
+    mantissa_bits: (u32, u32),
+    exponent_bits: (u32, u32),
+    max_bits: u32,
+    top: usize,
+}
+
+fn parse_search_args(args: Vec<String>) -> Result<(Search, Vec<String>), String> {
+    let mut search = Search {
+        mantissa_bits: (1, 23),
+        exponent_bits: (2, 8),
+        max_bits: 16,
+        top: 10,
+    };
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mantissa" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--mantissa requires a value".to_string())?;
+                search.mantissa_bits = parse_bit_range(&v, "--mantissa")?;
+            }
+            "--exp-bits" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--exp-bits requires a value".to_string())?;
+                search.exponent_bits = parse_bit_range(&v, "--exp-bits")?;
+            }
+            "--max-bits" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--max-bits requires a value".to_string())?;
+                search.max_bits = v
+                    .parse::<u32>()
+                    .map_err(|e| format!("invalid --max-bits '{v}': {e}"))?;
+            }
+            "--top" => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "--top requires a value".to_string())?;
+                search.top = v
+                    .parse::<usize>()
+                    .map_err(|e| format!("invalid --top '{v}': {e}"))?;
+            }
+            "--help" | "-h" => return Err(search_help_text()),
+            _ => rest.push(arg),
+        }
+    }
+    let (m_lo, m_hi) = search.mantissa_bits;
+    let (e_lo, e_hi) = search.exponent_bits;
+    if m_lo > m_hi || m_hi > 52 {
+        return Err("--mantissa must satisfy lo <= hi <= 52".to_string());
+    }
+    if e_lo > e_hi || e_lo < 2 || e_hi > 15 {
+        return Err("--exp-bits must satisfy 2 <= lo <= hi <= 15".to_string());
+    }
+    Ok((search, rest))
+}
+
+fn search_help_text() -> String {
+    [
+        "Usage:",
+        "  prectest search [search options] [sweep options]",
+        "",
+        "Ranks every IEEE-like layout (1 sign bit, m mantissa bits, w exponent bits)",
+        "that fits in --max-bits, scored over the sweep described by the sweep options.",
+        "",
+        "Search options:",
+        "  --mantissa lo:hi               Mantissa bits to try, default: 1:23",
+        "  --exp-bits lo:hi               Exponent field widths to try, default: 2:8",
+        "  --max-bits <n>                 Largest total width, default: 16",
+        "  --top <n>                      Rows to print, default: 10",
+        "",
+        "Example:",
+        "  cargo run -- search --max-bits 12 --focus -3,3,8",
+    ]
+    .join("\n")
+}
+
+/// `prectest search`: scores every IEEE-like layout within a bit budget and
+/// prints the best ones.
+pub fn search(args: Vec<String>, global: &Global) -> Result<(), Box<dyn Error>> {
+    let parsed = parse_search_args(args)
+        .and_then(|(search, rest)| Ok((search, parse_args(rest, global)?.config)));
+    let (search, config) = match parsed {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    let samples = sweep_samples(&config);
+    let mut metrics = Vec::new();
+    for w in search.exponent_bits.0..=search.exponent_bits.1 {
+        for m in search.mantissa_bits.0..=search.mantissa_bits.1 {
+            if 1 + m + w > search.max_bits {
+                continue;
+            }
+            let spec = SoftFloatSpec::ieee_like(format!("m{m}e{w}"), m, w);
+            metrics.push(compute_metrics(&Quantizer::Uniform(spec), &config, &samples));
+        }
+    }
+    if metrics.is_empty() {
+        return Err(format!("no layout fits in {} bits", search.max_bits).into());
+    }
+    metrics.sort_by(|a, b| a.score.total_cmp(&b.score));
+    metrics.truncate(search.top.max(1));
+    print!("{}", ranking_table(&metrics));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, Global};
+    use std::fs;
+
+    #[test]
+    fn out_dir_wins_over_config_paths() {
+        let path =
+            std::env::temp_dir().join(format!("prectest_out_dir_{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            "[output]\nprefix = \"docs/data/domain_focus\"\nranking = \"docs/ranking.md\"\n",
+        )
+        .unwrap();
+        let global = Global {
+            out_dir: Some("/tmp/o".to_string()),
+            quiet: true,
+        };
+        let args = vec!["--config".to_string(), path.display().to_string()];
+        let config = parse_args(args, &global).unwrap().config;
+        fs::remove_file(&path).unwrap();
+        assert_eq!(config.out_prefix, "/tmp/o/domain_focus");
+        assert_eq!(
+            config.output_path(&config.outputs.svg, ".svg"),
+            "/tmp/o/domain_focus.svg"
+        );
+        assert_eq!(config.outputs.ranking.as_deref(), Some("/tmp/o/ranking.md"));
+
+        let args = vec!["--out".to_string(), "docs/other".to_string()];
+        let config = parse_args(args, &global).unwrap().config;
+        assert_eq!(config.out_prefix, "/tmp/o/other");
+    }
+}
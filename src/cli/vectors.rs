@@ -0,0 +1,267 @@
+use crate::soft_float::{Flags, SoftFloatSpec, Tininess};
+use crate::vectors::{generate, parse, replay, write, Layout, ParseOptions, RoundingMode};
+use crate::verify::Op;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use super::{parse_format_arg, Global};
+
+struct ExportConfig {
+    spec: SoftFloatSpec,
+    ops: Vec<Op>,
+    count: usize,
+    layout: Layout,
+    tininess: Tininess,
+    out: String,
+}
+
+struct ImportConfig {
+    path: String,
+    options: ParseOptions,
+    shown: usize,
+}
+
+fn parse_layout(v: &str) -> Result<Layout, String> {
+    match v {
+        "prectest" => Ok(Layout::Prectest),
+        "testfloat" => Ok(Layout::TestFloat),
+        other => Err(format!(
+            "invalid --layout '{other}', expected prectest or testfloat"
+        )),
+    }
+}
+
+fn parse_ops(v: &str, ops: &mut Vec<Op>) -> Result<(), String> {
+    for name in v.split(',') {
+        ops.push(Op::parse(name.trim())?);
+    }
+    Ok(())
+}
+
+fn parse_export(args: Vec<String>, global: &Global) -> Result<ExportConfig, String> {
+    let mut spec = None;
+    let mut ops = Vec::new();
+    let mut count = 100_000;
+    let mut layout = Layout::Prectest;
+    let mut tininess = Tininess::BeforeRounding;
+    let mut out = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.as_str() {
+            "--format" => spec = Some(parse_format_arg(&value("--format")?)?),
+            "--op" => parse_ops(&value("--op")?, &mut ops)?,
+            "--count" => {
+                let v = value("--count")?;
+                count = v
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|n| *n >= 1.0 && n.fract() == 0.0)
+                    .ok_or_else(|| format!("invalid --count '{v}'"))?
+                    as usize;
+            }
+            "--layout" => layout = parse_layout(&value("--layout")?)?,
+            "--tininess" => tininess = Tininess::parse(&value("--tininess")?)?,
+            "--out" => out = Some(value("--out")?),
+            "--help" | "-h" => return Err(help_text()),
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    let spec = match spec {
+        Some(s) => s,
+        None => parse_format_arg("tiny8")?,
+    };
+    if ops.is_empty() {
+        ops = Op::ALL.to_vec();
+    }
+    if layout == Layout::TestFloat && ops.len() != 1 {
+        return Err("--layout testfloat holds one operation per file; pass a single --op".into());
+    }
+    let default = format!("docs/vectors_{}.txt", spec.name);
+    Ok(ExportConfig {
+        out: out.unwrap_or_else(|| global.output(&default)),
+        spec,
+        ops,
+        count,
+        layout,
+        tininess,
+    })
+}
+
+fn parse_import(args: Vec<String>) -> Result<ImportConfig, String> {
+    let mut path = None;
+    let mut options = ParseOptions {
+        spec: None,
+        tininess: None,
+        layout: None,
+        op: None,
+        rounding: RoundingMode::NearestAway,
+    };
+    let mut shown = 10;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.as_str() {
+            "--format" => options.spec = Some(parse_format_arg(&value("--format")?)?),
+            "--op" => options.op = Some(Op::parse(&value("--op")?)?),
+            "--rounding" => options.rounding = RoundingMode::parse(&value("--rounding")?)?,
+            "--layout" => options.layout = Some(parse_layout(&value("--layout")?)?),
+            "--tininess" => options.tininess = Some(Tininess::parse(&value("--tininess")?)?),
+            "--show" => {
+                let v = value("--show")?;
+                shown = v
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid --show '{v}'"))?;
+            }
+            "--help" | "-h" => return Err(help_text()),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    let path = path.ok_or_else(|| format!("missing vector file\n\n{}", help_text()))?;
+    Ok(ImportConfig {
+        path,
+        options,
+        shown,
+    })
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest vectors export [options]",
+        "  prectest vectors import <file> [options]",
+        "",
+        "Exports test vectors computed by the soft float model, or replays vectors",
+        "from hardware or another library through it and reports disagreements.",
+        "One vector per line, hexadecimal: `op rounding operands... result flags`,",
+        "e.g. `add rmm 3c00 3c00 4000 00` in fp16_like. Flags: 01 inexact, 02 underflow,",
+        "04 overflow, 08 div-by-zero, 10 invalid. The model rounds to nearest with ties",
+        "away from zero (rmm); vectors in other modes are skipped on import.",
+        "",
+        "Export options:",
+        "  --format <preset|name,m,min_e,max_e>  Default: tiny8",
+        "  --op <add|mul|div|fma|sqrt>  Repeatable or comma-separated, default: all",
+        "  --count <n>        Vectors per operation; every input combination when there",
+        "                     are at most n (default: 100000)",
+        "  --layout <prectest|testfloat>  testfloat drops the op and rounding columns,",
+        "                     like testfloat_gen output (one --op per file)",
+        "  --tininess <before|after>  When underflow is raised for tiny results (default:",
+        "                     before rounding, the model's own)",
+        "  --out <path>       Default: docs/vectors_<format>.txt",
+        "",
+        "Import options:",
+        "  --format <f>       Format of the file, if it has no `# format` header",
+        "  --tininess <before|after>  Overrides the `# tininess` header (default: before)",
+        "  --layout <prectest|testfloat>  Default: detected from the first vector",
+        "  --op <op>          Operation of a testfloat-layout file",
+        "  --rounding <mode>  Rounding mode of a testfloat-layout file (default: rmm)",
+        "  --show <n>         Disagreements to print (default: 10)",
+        "",
+        "Examples:",
+        "  cargo run -- vectors export --format fp16_like --op add,mul --count 1e6",
+        "  cargo run -- vectors export --format tiny8 --op add --layout testfloat --out add.tv",
+        "  cargo run -- vectors import add.tv --format tiny8 --op add",
+    ]
+    .join("\n")
+}
+
+fn export(config: &ExportConfig, global: &Global) -> Result<(), Box<dyn Error>> {
+    let mut all = Vec::new();
+    for &op in &config.ops {
+        let (vectors, exhaustive) = generate(&config.spec, op, config.count, config.tininess)?;
+        let scope = if exhaustive {
+            "every input"
+        } else {
+            "random inputs"
+        };
+        println!(
+            "{} {}: {} vectors ({scope})",
+            config.spec.name,
+            op.name(),
+            vectors.len()
+        );
+        all.extend(vectors);
+    }
+    if let Some(parent) = Path::new(&config.out).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let text = write(&config.spec, &all, config.layout, config.tininess);
+    fs::write(&config.out, text)?;
+    global.wrote(&config.out);
+    Ok(())
+}
+
+fn import(config: &ImportConfig) -> Result<(), Box<dyn Error>> {
+    let text = fs::read_to_string(&config.path)
+        .map_err(|e| format!("cannot read {}: {e}", config.path))?;
+    let file = parse(&text, &config.options)?;
+    let spec = &file.spec;
+    let result = replay(&file, config.shown);
+    println!(
+        "{}: {} vectors in {}, tininess {} rounding",
+        config.path,
+        file.vectors.len(),
+        spec.name,
+        file.tininess.name()
+    );
+    println!("  checked             {}", result.checked);
+    if result.skipped > 0 {
+        println!(
+            "  skipped             {} (rounding modes other than rmm)",
+            result.skipped
+        );
+    }
+    println!("  result differs      {}", result.result_mismatches);
+    println!("  only flags differ   {}", result.flag_mismatches);
+    if result.disagreements.is_empty() {
+        return Ok(());
+    }
+    let width = spec.total_bits().div_ceil(4) as usize;
+    let show = |bits: u64, flags: Flags| {
+        let value = spec.decode(bits).unwrap_or(f64::NAN);
+        format!("{bits:0width$x} ({value:e}), flags {}", flags.names())
+    };
+    println!("\ndisagreements (file, then model):");
+    for d in &result.disagreements {
+        let inputs: Vec<f64> = d
+            .vector
+            .operands
+            .iter()
+            .map(|&c| spec.decode(c).unwrap_or(f64::NAN))
+            .collect();
+        println!("  line {}: {}", d.line, d.vector.op.describe(&inputs));
+        println!("    file   {}", show(d.vector.result, d.vector.flags));
+        println!("    model  {}", show(d.result, d.flags));
+    }
+    Ok(())
+}
+
+pub fn main(mut args: Vec<String>, global: &Global) -> Result<(), Box<dyn Error>> {
+    let action = if args.is_empty() {
+        String::new()
+    } else {
+        args.remove(0)
+    };
+    let run = match action.as_str() {
+        "export" => parse_export(args, global).map(|c| export(&c, global)),
+        "import" => parse_import(args).map(|c| import(&c)),
+        "--help" | "-h" => Err(help_text()),
+        _ => Err(format!("expected export or import\n\n{}", help_text())),
+    };
+    match run {
+        Ok(result) => result,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    }
+}
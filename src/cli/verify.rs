@@ -0,0 +1,247 @@
+use crate::arith::Format;
+use crate::soft_float::{SoftFloatSpec, Tininess};
+use crate::verify::{verify, Kind, Op, Reference, Report, Ties, Underflow};
+use std::error::Error;
+use super::{parse_arith_format, Global};
+
+struct Config {
+    specs: Vec<SoftFloatSpec>,
+    ops: Vec<Op>,
+    reference: Reference,
+    limit: u64,
+    samples: u64,
+    examples: usize,
+}
+
+fn parse_count(name: &str, v: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid {name} '{v}'");
+    let n = match v.split_once('^') {
+        Some(("2", power)) => 1u64
+            .checked_shl(power.parse().map_err(|_| invalid())?)
+            .ok_or_else(invalid)?,
+        _ => v
+            .parse::<f64>()
+            .ok()
+            .filter(|n| *n >= 1.0 && n.fract() == 0.0)
+            .ok_or_else(invalid)? as u64,
+    };
+    Ok(n)
+}
+
+fn parse_args(args: Vec<String>) -> Result<Config, String> {
+    let mut config = Config {
+        specs: Vec::new(),
+        ops: Vec::new(),
+        reference: Reference::IEEE,
+        limit: 1 << 32,
+        samples: 10_000_000,
+        examples: 3,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.as_str() {
+            "--format" => match parse_arith_format(&value("--format")?)? {
+                Format::Soft(spec) => config.specs.push(spec),
+                other => {
+                    return Err(format!(
+                        "--format {} is a hardware type; verify checks soft float formats",
+                        other.name()
+                    ))
+                }
+            },
+            "--op" => {
+                for name in value("--op")?.split(',') {
+                    config.ops.push(Op::parse(name.trim())?);
+                }
+            }
+            "--ties" => {
+                config.reference.ties = match value("--ties")?.as_str() {
+                    "even" => Ties::Even,
+                    "away" => Ties::Away,
+                    other => {
+                        return Err(format!("invalid --ties '{other}', expected even or away"))
+                    }
+                }
+            }
+            "--underflow" => {
+                config.reference.underflow = match value("--underflow")?.as_str() {
+                    "gradual" | "subnormals" => Underflow::Gradual,
+                    "flush" => Underflow::Flush,
+                    other => {
+                        return Err(format!(
+                            "invalid --underflow '{other}', expected gradual or flush"
+                        ))
+                    }
+                }
+            }
+            "--tininess" => config.reference.tininess = Tininess::parse(&value("--tininess")?)?,
+            "--model" => {
+                config.reference.ties = Reference::MODEL.ties;
+                config.reference.underflow = Reference::MODEL.underflow;
+            }
+            "--limit" => config.limit = parse_count("--limit", &value("--limit")?)?,
+            "--samples" => config.samples = parse_count("--samples", &value("--samples")?)?,
+            "--examples" => {
+                let v = value("--examples")?;
+                config.examples = v
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid --examples '{v}'"))?;
+            }
+            "--help" | "-h" => return Err(help_text()),
+            _ => return Err(format!("unknown argument '{arg}'\n\n{}", help_text())),
+        }
+    }
+    if config.specs.is_empty() {
+        for name in ["tiny8", "fp8_e4m3", "fp8_e5m2"] {
+            if let Format::Soft(spec) = parse_arith_format(name)? {
+                config.specs.push(spec);
+            }
+        }
+    }
+    if config.ops.is_empty() {
+        config.ops = Op::ALL.to_vec();
+    }
+    Ok(config)
+}
+
+fn help_text() -> String {
+    [
+        "Usage:",
+        "  prectest verify [options]",
+        "",
+        "Runs every input combination of each operation through the soft float",
+        "arithmetic and compares the result bit for bit (including the sign of zero)",
+        "with an exact integer reference, rounded once; equal results must also raise",
+        "the same exception flags (tininess before rounding). The default reference is",
+        "IEEE 754 hardware: ties to even, subnormals. The soft float model rounds ties",
+        "away from zero and has no subnormals, so those mismatches are expected; with",
+        "--model any mismatch left is a bug.",
+        "",
+        "Options:",
+        "  --format <preset|name,m,min_e,max_e>  Repeatable, default: tiny8, fp8_e4m3, fp8_e5m2",
+        "  --op <add|mul|div|fma|sqrt>  Repeatable or comma-separated, default: all",
+        "  --ties <even|away>           Reference tie rule (default: even)",
+        "  --underflow <gradual|flush>  Subnormals, or flush exact results below the",
+        "                               smallest normal to zero (default: gradual)",
+        "  --tininess <before|after>    Whether underflow is raised for results that are",
+        "                               tiny before or only after rounding (default: before)",
+        "  --model            Same as --ties away --underflow flush",
+        "  --limit <n>        Check exhaustively up to n combinations (default: 2^32)",
+        "  --samples <n>      Random combinations above the limit (default: 1e7)",
+        "  --examples <n>     Mismatches to show per kind (default: 3)",
+        "",
+        "Every pair of a 16-bit format is about 4*10^9 cases: minutes per operation.",
+        "",
+        "Examples:",
+        "  cargo run --release -- verify",
+        "  cargo run --release -- verify --format fp16_like --op add,mul",
+        "  cargo run --release -- verify --model",
+        "  cargo run --release -- verify --model --tininess after",
+        "  cargo run --release -- verify --format bf16_like --op sqrt,div --underflow flush",
+    ]
+    .join("\n")
+}
+
+fn print_examples(report: &Report) {
+    for kind in Kind::ALL {
+        let examples: Vec<_> = report.examples.iter().filter(|e| e.kind == kind).collect();
+        if examples.is_empty() {
+            continue;
+        }
+        println!(
+            "  {} {}, {} ({} cases):",
+            report.format.name(),
+            report.op.name(),
+            kind.name(),
+            report.count(kind)
+        );
+        for e in examples {
+            if kind == Kind::Flags {
+                println!(
+                    "    {} = {:e}, flags {}, correctly {}",
+                    report.op.describe(&e.inputs),
+                    e.got,
+                    e.got_flags.names(),
+                    e.expected_flags.names()
+                );
+            } else {
+                println!(
+                    "    {} = {:e}, correctly rounded {:e}",
+                    report.op.describe(&e.inputs),
+                    e.got,
+                    e.expected
+                );
+            }
+        }
+    }
+}
+
+pub fn main(args: Vec<String>, _global: &Global) -> Result<(), Box<dyn Error>> {
+    let config = match parse_args(args) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+    println!("reference: {}\n", config.reference.describe());
+
+    let mut reports = Vec::new();
+    for spec in &config.specs {
+        for &op in &config.ops {
+            reports.push(verify(
+                spec,
+                op,
+                config.reference,
+                config.limit,
+                config.samples,
+                config.examples,
+            )?);
+        }
+    }
+
+    let name_w = reports
+        .iter()
+        .map(|r| r.format.name().len())
+        .max()
+        .unwrap_or(0)
+        .max("format".len());
+    let mut header = format!(
+        "{:<name_w$}  {:<4}  {:>13}  {:<10}  {:>10}",
+        "format", "op", "cases", "mode", "mismatches"
+    );
+    for kind in Kind::ALL {
+        header.push_str(&format!("  {:>11}", kind.name()));
+    }
+    println!("{header}");
+    for r in &reports {
+        let mode = if r.exhaustive {
+            "exhaustive"
+        } else {
+            "sampled"
+        };
+        let mut row = format!(
+            "{:<name_w$}  {:<4}  {:>13}  {mode:<10}  {:>10}",
+            r.format.name(),
+            r.op.name(),
+            r.cases,
+            r.mismatches
+        );
+        for kind in Kind::ALL {
+            row.push_str(&format!("  {:>11}", r.count(kind)));
+        }
+        println!("{row}");
+    }
+
+    if reports.iter().any(|r| r.mismatches > 0) {
+        println!("\nexamples (got = implementation):");
+        for r in &reports {
+            print_examples(r);
+        }
+    }
+    Ok(())
+}
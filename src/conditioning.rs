@@ -0,0 +1,132 @@
+//! Telling an ill-conditioned problem from an unstable algorithm.
+//!
+//! The relative condition number `κ = Σ |x_i ∂f/∂x_i| / |f|` says how much
+//! relative input perturbations are magnified by the problem itself. Any
+//! algorithm working in a format with unit roundoff `u` must round its inputs,
+//! so an error of about `κ·u` is explained by conditioning; error beyond that
+//! comes from the algorithm. Derivatives are central finite differences on a
+//! double-double reference, where the step can be tiny without losing digits.
+
+use crate::arith::Format;
+use crate::double_double::DoubleDouble;
+use crate::eval::relative_error;
+
+/// Relative finite-difference step: small against the function's scale but
+/// far above double-double resolution.
+const STEP: f64 = 1.0 / (1u64 << 36) as f64;
+
+/// `f'(x)` by a central difference.
+pub fn derivative(f: impl Fn(DoubleDouble) -> DoubleDouble, x: DoubleDouble) -> DoubleDouble {
+    let h = DoubleDouble::from(if x.hi == 0.0 { STEP } else { x.hi.abs() * STEP });
+    (f(x + h) - f(x - h)) / (h + h)
+}
+
+/// `|x f'(x) / f(x)|`; infinite when `f(x) = 0` but the derivative is not.
+pub fn condition_number(f: impl Fn(DoubleDouble) -> DoubleDouble, x: DoubleDouble) -> f64 {
+    let y = f(x);
+    let slope = derivative(&f, x);
+    relative_sensitivity(&[x * slope], y)
+}
+
+/// Componentwise condition number of a function of several inputs.
+pub fn condition_number_multi(
+    f: impl Fn(&[DoubleDouble]) -> DoubleDouble,
+    inputs: &[DoubleDouble],
+) -> f64 {
+    let y = f(inputs);
+    let terms: Vec<DoubleDouble> = (0..inputs.len())
+        .map(|i| {
+            let g = |xi: DoubleDouble| {
+                let mut v = inputs.to_vec();
+                v[i] = xi;
+                f(&v)
+            };
+            inputs[i] * derivative(g, inputs[i])
+        })
+        .collect();
+    relative_sensitivity(&terms, y)
+}
+
+fn relative_sensitivity(terms: &[DoubleDouble], y: DoubleDouble) -> f64 {
+    let total = terms
+        .iter()
+        .fold(DoubleDouble::ZERO, |acc, t| acc + t.abs());
+    if total.hi == 0.0 {
+        0.0
+    } else if y.hi == 0.0 {
+        f64::INFINITY
+    } else {
+        (total / y.abs()).to_f64()
+    }
+}
+
+/// Forward and backward error of one evaluation, split into what the
+/// problem's conditioning explains and what the algorithm adds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ErrorAnalysis {
+    /// Relative error of the computed result.
+    pub forward_error: f64,
+    pub condition: f64,
+    /// `2^-p` for a format with `p` significand bits.
+    pub unit_roundoff: f64,
+    /// Relative input perturbation that would explain the result to first
+    /// order: `forward_error / condition`.
+    pub backward_error: f64,
+}
+
+impl ErrorAnalysis {
+    pub fn new(format: &Format, forward_error: f64, condition: f64) -> Self {
+        let backward_error = if forward_error == 0.0 {
+            0.0
+        } else if condition > 0.0 {
+            forward_error / condition
+        } else {
+            f64::INFINITY
+        };
+        Self {
+            forward_error,
+            condition,
+            unit_roundoff: 2f64.powi(-(format.precision() as i32)),
+            backward_error,
+        }
+    }
+
+    /// The part of the forward error that rounding the inputs accounts for: `min(error, κ·u)`.
+    pub fn explained(&self) -> f64 {
+        let bound = self.condition * self.unit_roundoff;
+        if bound.is_nan() {
+            0.0
+        } else {
+            self.forward_error.min(bound)
+        }
+    }
+
+    /// Backward error in units of `u`; a small number means a backward stable evaluation.
+    pub fn backward_ulps(&self) -> f64 {
+        self.backward_error / self.unit_roundoff
+    }
+
+    /// e.g. `error 1.000e0, of which 0.000e0 is explained by conditioning (κ = 0.000e0, backward error inf u)`.
+    pub fn summary(&self) -> String {
+        format!(
+            "error {:.3e}, of which {:.3e} is explained by conditioning (κ = {:.3e}, backward error {:.3} u)",
+            self.forward_error,
+            self.explained(),
+            self.condition,
+            self.backward_ulps()
+        )
+    }
+}
+
+/// Evaluates `computed` (the algorithm in `format`, fed the rounded input)
+/// against `exact` at `x`.
+pub fn analyze(
+    format: &Format,
+    exact: impl Fn(DoubleDouble) -> DoubleDouble,
+    computed: impl Fn(f64) -> f64,
+    x: DoubleDouble,
+) -> ErrorAnalysis {
+    let value = computed(format.round_reference(x));
+    let forward = relative_error(value, exact(x));
+    ErrorAnalysis::new(format, forward, condition_number(exact, x))
+}
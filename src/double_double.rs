@@ -0,0 +1,493 @@
+//! Double-double arithmetic: an unevaluated sum `hi + lo` of two `f64`s with
+//! `|lo| <= ulp(hi) / 2`, good for about 106 significant bits. Below about
+//! `1e-292` `lo` is subnormal and the precision drops, down to that of an
+//! `f64` in the subnormal range; [`DoubleDouble::is_full_precision`] tells.
+//!
+//! This is the high-precision reference the evaluation tools compare the
+//! reduced formats against. The algorithms are the usual error-free
+//! transformations (Knuth's two-sum, FMA two-product) as in the QD library.
+//! The elementary functions reduce the argument and sum a Taylor series
+//! (`exp`, `sin`, `cos`) or refine the `f64` result by Newton steps (`ln`,
+//! `atan2`); they are meant as a reference, not for speed.
+
+use crate::soft_float::exponent_of;
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+const LN2: DoubleDouble = DoubleDouble {
+    hi: std::f64::consts::LN_2,
+    lo: 2.3190468138462996e-17,
+};
+
+/// `π/2` to about 160 bits, split so that `k * part` is exact in double-double.
+const HALF_PI: [f64; 3] = [
+    std::f64::consts::FRAC_PI_2,
+    6.123233995736766e-17,
+    -1.4973849048591698e-33,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+/// `a + b` as a rounded sum and its exact rounding error.
+pub fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// Like [`two_sum`], assuming `|a| >= |b|`.
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// `a * b` as a rounded product and its exact rounding error.
+pub fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+impl DoubleDouble {
+    pub const ZERO: Self = Self { hi: 0.0, lo: 0.0 };
+    pub const ONE: Self = Self { hi: 1.0, lo: 0.0 };
+
+    /// Normalizes `hi + lo`; non-finite values keep `lo = 0`.
+    fn renorm(hi: f64, lo: f64) -> Self {
+        if !hi.is_finite() {
+            return Self { hi, lo: 0.0 };
+        }
+        let (hi, lo) = quick_two_sum(hi, lo);
+        Self { hi, lo }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.hi.is_finite()
+    }
+
+    pub fn is_nan(self) -> bool {
+        self.hi.is_nan()
+    }
+
+    pub fn abs(self) -> Self {
+        if self.hi < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+
+    pub fn sqrt(self) -> Self {
+        if self.hi <= 0.0 || !self.is_finite() {
+            return Self::from(self.hi.sqrt());
+        }
+        // One Newton step on the f64 square root doubles its precision.
+        let x = 1.0 / self.hi.sqrt();
+        let ax = Self::from(self.hi * x);
+        let residual = self - ax * ax;
+        ax + Self::from(residual.hi * (x * 0.5))
+    }
+
+    /// `self^n` by repeated squaring.
+    pub fn powi(self, n: i32) -> Self {
+        let mut result = Self::ONE;
+        let mut base = self;
+        let mut k = n.unsigned_abs();
+        while k > 0 {
+            if k & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            k >>= 1;
+        }
+        if n < 0 {
+            Self::ONE / result
+        } else {
+            result
+        }
+    }
+
+    /// `self * 10^n`, in steps small enough that no power of ten overflows.
+    fn scale10(self, n: i32) -> Self {
+        let mut x = self;
+        let mut n = n;
+        while n != 0 && x.hi != 0.0 && x.is_finite() {
+            let step = n.clamp(-256, 256);
+            let p = Self::from(10.0).powi(step.abs());
+            x = if step > 0 { x * p } else { x / p };
+            n -= step;
+        }
+        x
+    }
+
+    /// Whether `lo` can hold its full 53 bits, i.e. `self` is zero, not
+    /// finite or at least `2^-969` in magnitude.
+    pub fn is_full_precision(self) -> bool {
+        self.hi == 0.0 || !self.is_finite() || self.hi.abs() >= pow2(-969)
+    }
+
+    /// `self * 2^k`, exact unless the result overflows or becomes subnormal.
+    pub fn ldexp(self, k: i32) -> Self {
+        // Two steps so that neither factor overflows or underflows on its own.
+        let half = k / 2;
+        let (a, b) = (pow2(half), pow2(k - half));
+        Self {
+            hi: self.hi * a * b,
+            lo: self.lo * a * b,
+        }
+    }
+
+    pub fn exp(self) -> Self {
+        if self.is_nan() {
+            return self;
+        }
+        if self.hi > 709.79 {
+            return Self::from(f64::INFINITY);
+        }
+        if self.hi < -745.2 {
+            return Self::ZERO;
+        }
+        // exp(x) = 2^k * exp(r)^1024 with |r| <= ln2 / 2048.
+        let k = (self.hi / LN2.hi).round();
+        let r = (self - LN2 * Self::from(k)).ldexp(-10);
+        let mut sum = Self::ZERO;
+        let mut term = Self::ONE;
+        for n in 1..20 {
+            term = term * r / Self::from(n as f64);
+            sum = sum + term;
+            if term.hi.abs() < 1e-36 {
+                break;
+            }
+        }
+        // Square 1 + sum ten times, keeping the small part separate: (1 + s)^2 = 1 + (2s + s^2).
+        for _ in 0..10 {
+            sum = sum + sum + sum * sum;
+        }
+        (sum + Self::ONE).ldexp(k as i32)
+    }
+
+    /// Natural logarithm; NaN below zero and `-inf` at zero.
+    pub fn ln(self) -> Self {
+        if self.hi <= 0.0 || !self.is_finite() {
+            return Self::from(self.hi.ln());
+        }
+        // ln(m 2^e) = ln(m) + e ln2 with m in [1, 2), then Newton on exp(y) = m.
+        let e = exponent_of(self.hi);
+        let m = self.ldexp(-e);
+        let mut y = Self::from(m.hi.ln());
+        for _ in 0..2 {
+            y = y + m * (-y).exp() - Self::ONE;
+        }
+        y + LN2 * Self::from(e as f64)
+    }
+
+    /// `(sin(x), cos(x))`. The reduction carries about 160 bits of `π/2`,
+    /// so results are reliable for `|x|` up to about `2^50`; beyond that both are NaN.
+    pub fn sin_cos(self) -> (Self, Self) {
+        if !self.is_finite() || self.hi.abs() > 1e15 {
+            return (Self::from(f64::NAN), Self::from(f64::NAN));
+        }
+        let k = (self.hi / HALF_PI[0]).round();
+        let mut r = self;
+        for part in HALF_PI {
+            let (p, e) = two_prod(k, part);
+            r = r - Self { hi: p, lo: e };
+        }
+        let r2 = r * r;
+        let series = |mut term: Self, mut n: u32| {
+            let mut sum = term;
+            loop {
+                term = -(term * r2) / Self::from(((n + 1) * (n + 2)) as f64);
+                sum = sum + term;
+                n += 2;
+                if term.hi.abs() <= 1e-36 * sum.hi.abs() || n > 60 {
+                    return sum;
+                }
+            }
+        };
+        let (s, c) = (series(r, 1), series(Self::ONE, 0));
+        match (k as i64).rem_euclid(4) {
+            0 => (s, c),
+            1 => (c, -s),
+            2 => (-s, -c),
+            _ => (-c, s),
+        }
+    }
+
+    pub fn sin(self) -> Self {
+        self.sin_cos().0
+    }
+
+    pub fn cos(self) -> Self {
+        self.sin_cos().1
+    }
+
+    /// The angle of the point `(x, y)`, in `[-π, π]`.
+    pub fn atan2(self, x: Self) -> Self {
+        let y = self;
+        let mut z = Self::from(y.hi.atan2(x.hi));
+        if !z.is_finite() || (y.hi == 0.0 && x.hi == 0.0) || !x.is_finite() || !y.is_finite() {
+            return z;
+        }
+        // Newton on g(z) = x sin z - y cos z.
+        for _ in 0..2 {
+            let (s, c) = z.sin_cos();
+            z = z - (x * s - y * c) / (x * c + y * s);
+        }
+        z
+    }
+
+    /// `self^y` as `exp(y ln self)`; negative bases need an integer `y`.
+    pub fn pow(self, y: Self) -> Self {
+        let integral = y.hi.fract() == 0.0 && y.lo.fract() == 0.0;
+        if self.hi == 0.0 || !self.is_finite() || !y.is_finite() || (self.hi < 0.0 && !integral) {
+            return Self::from(self.hi.powf(y.hi));
+        }
+        let magnitude = (y * self.abs().ln()).exp();
+        let odd = integral && y.hi.abs() < 2f64.powi(53) && y.hi % 2.0 != 0.0;
+        if self.hi < 0.0 && odd {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// Parses a decimal literal such as `0.1`, `-2.5e-8` or `inf` to double-double precision.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let s = text.trim();
+        let err = || format!("invalid number '{text}'");
+        let (negative, body) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if !body.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            // inf, NaN and friends.
+            return s.parse::<f64>().map(Self::from).map_err(|_| err());
+        }
+        let (mantissa, exp) = match body.find(['e', 'E']) {
+            Some(i) => (&body[..i], body[i + 1..].parse::<i32>().map_err(|_| err())?),
+            None => (body, 0),
+        };
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return Err(err());
+        }
+        let mut value = Self::ZERO;
+        for c in int_part.chars().chain(frac_part.chars()) {
+            let d = c.to_digit(10).ok_or_else(err)?;
+            value = value * Self::from(10.0) + Self::from(d as f64);
+        }
+        let scale = exp.saturating_sub(frac_part.len() as i32);
+        let value = if value.hi == 0.0 || !value.is_finite() {
+            value
+        } else {
+            // Start from about 2^-100 (growing) or 2^600 (shrinking) so that no
+            // step of the scaling leaves the normal range.
+            let k = if scale >= 0 { -100 } else { 600 } - exponent_of(value.hi);
+            let scaled = value.ldexp(k).scale10(scale);
+            let result = scaled.ldexp(-k);
+            if result.is_full_precision() {
+                result
+            } else {
+                // `lo` is subnormal: take the correctly rounded `f64` and what is left.
+                let hi = body.parse::<f64>().map_err(|_| err())?;
+                let lo = (scaled - Self::from(hi).ldexp(k)).ldexp(-k).hi;
+                Self { hi, lo }
+            }
+        };
+        Ok(if negative { -value } else { value })
+    }
+
+    /// Scientific notation with `digits` significant digits, e.g. `1.000000000000000055511e-1`.
+    pub fn to_sci_string(self, digits: usize) -> String {
+        if !self.is_finite() || self.hi == 0.0 {
+            return format!("{:e}", self.hi);
+        }
+        let negative = self.hi < 0.0;
+        let mut x = self.abs();
+        let mut exp10 = x.hi.log10().floor() as i32;
+        x = x.scale10(-exp10);
+        // log10 may be off by one near powers of ten.
+        if x.hi >= 10.0 {
+            x = x / Self::from(10.0);
+            exp10 += 1;
+        } else if x.hi < 1.0 {
+            x = x * Self::from(10.0);
+            exp10 -= 1;
+        }
+        let mut out: Vec<u8> = Vec::with_capacity(digits + 1);
+        for _ in 0..=digits.max(1) {
+            let d = x.hi.floor().clamp(0.0, 9.0);
+            out.push(d as u8);
+            x = (x - Self::from(d)) * Self::from(10.0);
+        }
+        // Round half up on the extra digit, carrying left.
+        let round_up = out.pop().unwrap_or(0) >= 5;
+        if round_up {
+            let mut i = out.len();
+            loop {
+                if i == 0 {
+                    out.insert(0, 1);
+                    out.pop();
+                    exp10 += 1;
+                    break;
+                }
+                i -= 1;
+                if out[i] == 9 {
+                    out[i] = 0;
+                } else {
+                    out[i] += 1;
+                    break;
+                }
+            }
+        }
+        let text: String = out.iter().map(|d| char::from(b'0' + d)).collect();
+        let (lead, rest) = text.split_at(1);
+        let rest = rest.trim_end_matches('0');
+        let sign = if negative { "-" } else { "" };
+        if rest.is_empty() {
+            format!("{sign}{lead}e{exp10}")
+        } else {
+            format!("{sign}{lead}.{rest}e{exp10}")
+        }
+    }
+}
+
+/// `2^k` for any `k` with a finite, non-zero result (subnormals included).
+fn pow2(k: i32) -> f64 {
+    if k < -1022 {
+        f64::from_bits(1u64 << (k + 1074).clamp(0, 51))
+    } else {
+        f64::from_bits(((k.min(1023) + 1023) as u64) << 52)
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(hi: f64) -> Self {
+        Self { hi, lo: 0.0 }
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (s1, s2) = two_sum(self.hi, rhs.hi);
+        if !s1.is_finite() {
+            return Self::from(s1);
+        }
+        let (t1, t2) = two_sum(self.lo, rhs.lo);
+        let (s1, s2) = quick_two_sum(s1, s2 + t1);
+        Self::renorm(s1, s2 + t2)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let (p1, p2) = two_prod(self.hi, rhs.hi);
+        if !p1.is_finite() {
+            return Self::from(p1);
+        }
+        Self::renorm(p1, p2 + (self.hi * rhs.lo + self.lo * rhs.hi))
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        let q1 = self.hi / rhs.hi;
+        if !q1.is_finite() || rhs.hi == 0.0 {
+            return Self::from(q1);
+        }
+        let r = self - rhs * Self::from(q1);
+        let q2 = r.hi / rhs.hi;
+        let r = r - rhs * Self::from(q2);
+        let q3 = r.hi / rhs.hi;
+        Self::renorm(q1, q2) + Self::from(q3)
+    }
+}
+
+impl PartialOrd for DoubleDouble {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.hi.partial_cmp(&other.hi)? {
+            Ordering::Equal => self.lo.partial_cmp(&other.lo),
+            ord => Some(ord),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DoubleDouble;
+
+    #[test]
+    fn parse_rounds_like_f64_across_the_range() {
+        for text in [
+            "0.1",
+            "-2.5e-8",
+            "123.456e-5",
+            "1.7976931348623157e308",
+            "2.2250738585072014e-308",
+            "2.5e-308",
+            "1e-308",
+            "-1e-310",
+            "1e-320",
+            "5e-324",
+            "4.9e-324",
+            "1e-400",
+            "1e309",
+        ] {
+            let parsed = DoubleDouble::parse(text).unwrap();
+            let expected = text.parse::<f64>().unwrap();
+            assert_eq!(parsed.hi.to_bits(), expected.to_bits(), "{text}");
+        }
+    }
+
+    #[test]
+    fn parse_keeps_the_low_word() {
+        let x = DoubleDouble::parse("0.1").unwrap();
+        // 0.1 - f64(0.1) = -5.551115123125782702118158340454...e-18
+        assert!((x.lo + 5.551115123125783e-18).abs() < 2e-33);
+        let small = DoubleDouble::parse("1e-290").unwrap();
+        assert!(small.is_full_precision());
+        assert_eq!(small.to_sci_string(25), "1e-290");
+    }
+
+    #[test]
+    fn sci_string_below_the_normal_range() {
+        assert_eq!(DoubleDouble::from(1e-310).to_sci_string(10), "1e-310");
+        assert_eq!(DoubleDouble::from(2.5e-308).to_sci_string(10), "2.5e-308");
+        assert_eq!(DoubleDouble::from(5e-324).to_sci_string(4), "4.941e-324");
+        assert_eq!(DoubleDouble::from(1e308).to_sci_string(10), "1e308");
+        assert!(!DoubleDouble::from(1e-310).is_full_precision());
+    }
+}
@@ -0,0 +1,339 @@
+//! How accurate `sin`, `exp`, `ln` and friends are, in ULPs of the format.
+//!
+//! Inputs are sampled over a range, rounded into the format, and the function
+//! is evaluated the way a program would: `f32` and `f64` call the platform
+//! libm through std, soft formats round the `f64` result (so they measure
+//! the format, not a libm). Each result is compared with the double-double
+//! reference in [`DoubleDouble`], good to about 100 bits.
+
+use crate::arith::Format;
+use crate::double_double::DoubleDouble;
+use crate::soft_float::exponent_of;
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Exp,
+    Ln,
+    Pow,
+    Sqrt,
+    /// `x.atan2(y)` as in std: the angle of the point `(y, x)`.
+    Atan2,
+}
+
+impl Function {
+    pub const ALL: [Function; 7] = [
+        Function::Sin,
+        Function::Cos,
+        Function::Exp,
+        Function::Ln,
+        Function::Pow,
+        Function::Sqrt,
+        Function::Atan2,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Function::Sin => "sin",
+            Function::Cos => "cos",
+            Function::Exp => "exp",
+            Function::Ln => "ln",
+            Function::Pow => "pow",
+            Function::Sqrt => "sqrt",
+            Function::Atan2 => "atan2",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name == "log" {
+            return Ok(Function::Ln);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|f| f.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|f| f.name()).collect();
+                format!(
+                    "unknown function '{name}', expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// 2 for `pow` and `atan2`, 1 otherwise.
+    pub fn arity(self) -> usize {
+        match self {
+            Function::Pow | Function::Atan2 => 2,
+            _ => 1,
+        }
+    }
+
+    /// Ranges for the first and second argument when none are given.
+    pub fn default_ranges(self) -> (Range, Range) {
+        let fixed = Range::linear(1.0, 1.0);
+        match self {
+            Function::Sin | Function::Cos => (Range::linear(-10.0, 10.0), fixed),
+            Function::Exp => (Range::linear(-80.0, 80.0), fixed),
+            Function::Ln => (Range::log(1e-6, 1e6), fixed),
+            Function::Pow => (Range::log(0.1, 10.0), Range::linear(-20.0, 20.0)),
+            Function::Sqrt => (Range::log(1e-6, 1e6), fixed),
+            Function::Atan2 => (Range::linear(-10.0, 10.0), Range::linear(-10.0, 10.0)),
+        }
+    }
+
+    /// The exact result for `f64` arguments; NaN where it is undefined.
+    pub fn reference(self, x: f64, y: f64) -> DoubleDouble {
+        let dx = DoubleDouble::from(x);
+        match self {
+            Function::Sin => dx.sin(),
+            Function::Cos => dx.cos(),
+            Function::Exp => dx.exp(),
+            Function::Ln => dx.ln(),
+            Function::Pow => dx.pow(DoubleDouble::from(y)),
+            Function::Sqrt => dx.sqrt(),
+            Function::Atan2 => dx.atan2(DoubleDouble::from(y)),
+        }
+    }
+
+    /// The result a program computing in `format` gets; the arguments must
+    /// already be values of the format.
+    pub fn compute(self, format: &Format, x: f64, y: f64) -> f64 {
+        match format {
+            Format::F32 => {
+                let (x, y) = (x as f32, y as f32);
+                (match self {
+                    Function::Sin => x.sin(),
+                    Function::Cos => x.cos(),
+                    Function::Exp => x.exp(),
+                    Function::Ln => x.ln(),
+                    Function::Pow => x.powf(y),
+                    Function::Sqrt => x.sqrt(),
+                    Function::Atan2 => x.atan2(y),
+                }) as f64
+            }
+            Format::F64 => self.libm_f64(x, y),
+            Format::Soft(_) if self == Function::Sqrt => format.sqrt(x),
+            Format::Soft(_) => format.round(self.libm_f64(x, y)),
+        }
+    }
+
+    fn libm_f64(self, x: f64, y: f64) -> f64 {
+        match self {
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Exp => x.exp(),
+            Function::Ln => x.ln(),
+            Function::Pow => x.powf(y),
+            Function::Sqrt => x.sqrt(),
+            Function::Atan2 => x.atan2(y),
+        }
+    }
+}
+
+/// An input interval, sampled evenly or geometrically.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Range {
+    pub lo: f64,
+    pub hi: f64,
+    /// Geometric spacing; both ends must then be positive.
+    pub log: bool,
+}
+
+impl Range {
+    pub fn linear(lo: f64, hi: f64) -> Self {
+        Self { lo, hi, log: false }
+    }
+
+    pub fn log(lo: f64, hi: f64) -> Self {
+        Self { lo, hi, log: true }
+    }
+
+    /// Parses `lo:hi`, or a single value for a fixed argument.
+    pub fn parse(spec: &str, log: bool) -> Result<Self, String> {
+        let number = |s: &str| {
+            s.trim()
+                .parse::<f64>()
+                .map_err(|e| format!("invalid range '{spec}': {e}"))
+        };
+        let (lo, hi) = match spec.split_once(':') {
+            Some((a, b)) => (number(a)?, number(b)?),
+            None => {
+                let v = number(spec)?;
+                (v, v)
+            }
+        };
+        if !(lo.is_finite() && hi.is_finite()) || lo > hi {
+            return Err(format!("invalid range '{spec}': expected finite lo <= hi"));
+        }
+        if log && lo <= 0.0 {
+            return Err(format!("invalid range '{spec}': log spacing needs lo > 0"));
+        }
+        Ok(Self { lo, hi, log })
+    }
+
+    /// The point a fraction `t` in `[0, 1]` of the way through the range.
+    ///
+    /// With log spacing `t` only picks the binade; the point within it comes
+    /// from a hash of `t`. `exp` of an evenly spaced logarithm would make
+    /// `ln(x)` land on the grid almost exactly and flatter the libm under test.
+    pub fn at(&self, t: f64) -> f64 {
+        if !self.log {
+            return self.lo + t * (self.hi - self.lo);
+        }
+        let e = exponent_of((self.lo.ln() + t * (self.hi.ln() - self.lo.ln())).exp());
+        let binade = DoubleDouble::ONE.ldexp(e).hi;
+        let (a, b) = (self.lo.max(binade), self.hi.min(2.0 * binade));
+        let mut h = t.to_bits().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        h ^= h >> 31;
+        let u = (h >> 11) as f64 / (1u64 << 53) as f64;
+        (a + u * (b - a)).clamp(self.lo, self.hi)
+    }
+}
+
+/// One evaluated input.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub x: f64,
+    /// Second argument; unused by one-argument functions.
+    pub y: f64,
+    pub value: f64,
+    pub reference: DoubleDouble,
+    /// `|value - reference|` in ULPs of the format at the reference.
+    pub ulps: f64,
+}
+
+/// The samples of one function in one format.
+#[derive(Clone, Debug)]
+pub struct Accuracy {
+    pub function: Function,
+    pub format: Format,
+    pub samples: Vec<Sample>,
+    /// Inputs left out: undefined, or a result the format cannot hold.
+    pub skipped: usize,
+}
+
+impl Accuracy {
+    pub fn max_ulps(&self) -> f64 {
+        self.samples.iter().map(|s| s.ulps).fold(0.0, f64::max)
+    }
+
+    pub fn mean_ulps(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().map(|s| s.ulps).sum::<f64>() / self.samples.len() as f64
+    }
+
+    /// The `n` samples with the largest error, worst first.
+    pub fn worst(&self, n: usize) -> Vec<Sample> {
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(|a, b| b.ulps.partial_cmp(&a.ulps).unwrap_or(Ordering::Equal));
+        sorted.truncate(n);
+        sorted
+    }
+}
+
+/// The ULP of `format` at `x`: the spacing of the binade `x` is in, with
+/// subnormal spacing below the normal range. `None` for zero, non-finite
+/// values and values the format cannot hold.
+pub fn ulp_at(format: &Format, x: f64) -> Option<f64> {
+    if !x.is_finite() || x == 0.0 {
+        return None;
+    }
+    let e = exponent_of(x);
+    let (mantissa_bits, min_exp, max_exp) = match format {
+        Format::F32 => (23, -126, 127),
+        Format::F64 => (52, -1022, 1023),
+        Format::Soft(spec) => return spec.ulp_near(x.abs()),
+    };
+    (e <= max_exp).then(|| DoubleDouble::ONE.ldexp(e.max(min_exp) - mantissa_bits).hi)
+}
+
+/// `|value - reference|` in ULPs at the reference, or `None` when the
+/// reference is zero, undefined or outside the format.
+pub fn ulp_error(format: &Format, value: f64, reference: DoubleDouble) -> Option<f64> {
+    let ulp = ulp_at(format, reference.hi)?;
+    if !value.is_finite() {
+        return Some(f64::INFINITY);
+    }
+    Some(((DoubleDouble::from(value) - reference).abs() / DoubleDouble::from(ulp)).to_f64())
+}
+
+/// Evaluates `function` at `points` inputs: the first argument spread over
+/// `x` (see [`Range::at`]), the second (for two-argument functions) drawn pseudo-randomly from
+/// `y`, the same sequence on every run.
+pub fn measure(function: Function, format: &Format, x: Range, y: Range, points: usize) -> Accuracy {
+    let mut samples = Vec::with_capacity(points);
+    let mut skipped = 0;
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    for i in 0..points {
+        let t = if points > 1 {
+            i as f64 / (points - 1) as f64
+        } else {
+            0.0
+        };
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        let u = (seed >> 11) as f64 / (1u64 << 53) as f64;
+        let a = format.round(x.at(t));
+        let b = if function.arity() == 2 {
+            format.round(y.at(u))
+        } else {
+            0.0
+        };
+        let reference = function.reference(a, b);
+        let value = function.compute(format, a, b);
+        let ulps = if reference.hi == 0.0 && value == 0.0 {
+            Some(0.0)
+        } else {
+            ulp_error(format, value, reference)
+        };
+        match ulps {
+            Some(ulps) if a.is_finite() && b.is_finite() => samples.push(Sample {
+                x: a,
+                y: b,
+                value,
+                reference,
+                ulps,
+            }),
+            _ => skipped += 1,
+        }
+    }
+    Accuracy {
+        function,
+        format: format.clone(),
+        samples,
+        skipped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{measure, Function, Range};
+    use crate::arith::Format;
+
+    #[test]
+    fn log_spacing_does_not_flatter_ln() {
+        let fixed = Range::linear(1.0, 1.0);
+        let mean = |x| measure(Function::Ln, &Format::F64, x, fixed, 2000).mean_ulps();
+        let log = mean(Range::log(1e-6, 1e6));
+        let linear = mean(Range::linear(1e-6, 1e6));
+        assert!((log - linear).abs() < 0.05, "log {log}, linear {linear}");
+    }
+
+    #[test]
+    fn log_spacing_stays_in_range() {
+        let range = Range::log(0.3, 5.0);
+        for i in 0..=100 {
+            let x = range.at(i as f64 / 100.0);
+            assert!((0.3..=5.0).contains(&x), "{x}");
+        }
+        assert_eq!(Range::log(2.5, 2.5).at(0.5), 2.5);
+    }
+}
@@ -0,0 +1,474 @@
+//! Arithmetic expressions evaluated with rounding after every operation, next
+//! to a double-double reference, so a formula can be checked in several
+//! formats without writing Rust.
+//!
+//! Grammar: `+ - * /`, unary minus, `^` with a non-negative integer exponent
+//! (repeated multiplication), parentheses, `sqrt(..)`, `abs(..)`, decimal
+//! literals and variables.
+
+use crate::arith::Format;
+use crate::double_double::DoubleDouble;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Op {
+    fn symbol(self) -> char {
+        match self {
+            Op::Add => '+',
+            Op::Sub => '-',
+            Op::Mul => '*',
+            Op::Div => '/',
+        }
+    }
+
+    fn precedence(self) -> u8 {
+        match self {
+            Op::Add | Op::Sub => 1,
+            Op::Mul | Op::Div => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Func {
+    Sqrt,
+    Abs,
+}
+
+impl Func {
+    fn name(self) -> &'static str {
+        match self {
+            Func::Sqrt => "sqrt",
+            Func::Abs => "abs",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    /// A literal, kept as written so the reference uses its exact decimal value.
+    Num(String),
+    Var(String),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, u32),
+    Call(Func, Box<Expr>),
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(op, ..) => op.precedence(),
+            Expr::Neg(_) => 3,
+            Expr::Pow(..) => 4,
+            _ => 5,
+        }
+    }
+
+    /// Source text with only the parentheses needed to keep the evaluation order.
+    pub fn to_text(&self) -> String {
+        let wrap = |e: &Expr, min: u8| {
+            if e.precedence() < min {
+                format!("({})", e.to_text())
+            } else {
+                e.to_text()
+            }
+        };
+        match self {
+            Expr::Num(text) | Expr::Var(text) => text.clone(),
+            Expr::Neg(e) => format!("-{}", wrap(e, 3)),
+            // The right operand keeps its parentheses even for `+` and `*`:
+            // `a + (b + c)` rounds differently from `a + b + c`.
+            Expr::Binary(op, a, b) => format!(
+                "{} {} {}",
+                wrap(a, op.precedence()),
+                op.symbol(),
+                wrap(b, op.precedence() + 1)
+            ),
+            Expr::Pow(e, n) => format!("{}^{n}", wrap(e, 5)),
+            Expr::Call(f, e) => format!("{}({})", f.name(), e.to_text()),
+        }
+    }
+
+    /// Variable names in order of first use.
+    pub fn variables(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        self.collect_variables(&mut out);
+        out
+    }
+
+    fn collect_variables(&self, out: &mut Vec<String>) {
+        match self {
+            Expr::Num(_) => {}
+            Expr::Var(name) => {
+                if !out.contains(name) {
+                    out.push(name.clone());
+                }
+            }
+            Expr::Neg(e) | Expr::Pow(e, _) | Expr::Call(_, e) => e.collect_variables(out),
+            Expr::Binary(_, a, b) => {
+                a.collect_variables(out);
+                b.collect_variables(out);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(String),
+    Ident(String),
+    Sym(char),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(Token::Num(chars[start..i].iter().collect()));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/^()".contains(c) {
+            tokens.push(Token::Sym(c));
+            i += 1;
+        } else {
+            return Err(format!("unexpected character '{c}' in expression"));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Sym(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                Op::Add
+            } else if self.eat('-') {
+                Op::Sub
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                Op::Mul
+            } else if self.eat('/') {
+                Op::Div
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if !self.eat('^') {
+            return Ok(base);
+        }
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token::Num(text)) => {
+                self.pos += 1;
+                let n = text
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|&n| n <= 64)
+                    .ok_or_else(|| format!("exponent '{text}' must be an integer in 0..=64"))?;
+                Ok(Expr::Pow(Box::new(base), n))
+            }
+            _ => Err("'^' must be followed by an integer literal".to_string()),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "unexpected end of expression".to_string())?;
+        self.pos += 1;
+        match token {
+            Token::Num(text) => {
+                DoubleDouble::parse(&text)?;
+                Ok(Expr::Num(text))
+            }
+            Token::Ident(name) => {
+                if !self.eat('(') {
+                    return Ok(Expr::Var(name));
+                }
+                let func = match name.as_str() {
+                    "sqrt" => Func::Sqrt,
+                    "abs" => Func::Abs,
+                    _ => return Err(format!("unknown function '{name}' (known: sqrt, abs)")),
+                };
+                let arg = self.expr()?;
+                if !self.eat(')') {
+                    return Err(format!("missing ')' after {name}("));
+                }
+                Ok(Expr::Call(func, Box::new(arg)))
+            }
+            Token::Sym('(') => {
+                let inner = self.expr()?;
+                if !self.eat(')') {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(inner)
+            }
+            Token::Sym(c) => Err(format!("unexpected '{c}' in expression")),
+        }
+    }
+}
+
+pub fn parse_expr(src: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+    };
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(Token::Sym(c)) => Err(format!("unexpected '{c}' in expression")),
+        Some(Token::Num(t) | Token::Ident(t)) => Err(format!("unexpected '{t}' in expression")),
+    }
+}
+
+/// One operation of an [`Evaluation`], in evaluation order.
+#[derive(Clone, Debug)]
+pub struct Step {
+    /// The subexpression this operation computes.
+    pub text: String,
+    pub value: f64,
+    pub reference: DoubleDouble,
+    pub rel_error: f64,
+    /// Bits of the result that agree with the reference, at most the format's precision.
+    pub correct_bits: f64,
+    /// Correct bits of the least accurate operand minus those of the result.
+    pub bits_lost: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Evaluation {
+    pub format: Format,
+    pub value: f64,
+    pub reference: DoubleDouble,
+    pub rel_error: f64,
+    pub correct_bits: f64,
+    pub steps: Vec<Step>,
+}
+
+impl Evaluation {
+    /// The operation that lost the most bits, if any lost some.
+    pub fn worst_step(&self) -> Option<&Step> {
+        self.steps
+            .iter()
+            .filter(|s| s.bits_lost > 0.0)
+            .max_by(|a, b| a.bits_lost.total_cmp(&b.bits_lost))
+    }
+}
+
+/// `|value - reference| / |reference|`, 0 when both are zero and infinite
+/// when only the reference is.
+pub fn relative_error(value: f64, reference: DoubleDouble) -> f64 {
+    if value.is_nan() || reference.is_nan() {
+        return if value.is_nan() && reference.is_nan() {
+            0.0
+        } else {
+            f64::INFINITY
+        };
+    }
+    if !reference.is_finite() || !value.is_finite() {
+        return if value == reference.hi {
+            0.0
+        } else {
+            f64::INFINITY
+        };
+    }
+    let diff = (DoubleDouble::from(value) - reference).abs();
+    if reference.hi == 0.0 {
+        return if diff.hi == 0.0 { 0.0 } else { f64::INFINITY };
+    }
+    (diff / reference.abs()).to_f64()
+}
+
+/// `-log2(rel_error)`, clamped to `[0, precision]`.
+pub fn correct_bits(rel_error: f64, precision: u32) -> f64 {
+    if rel_error == 0.0 {
+        precision as f64
+    } else if rel_error >= 1.0 || rel_error.is_nan() {
+        0.0
+    } else {
+        (-rel_error.log2()).min(precision as f64)
+    }
+}
+
+fn lookup(vars: &[(String, DoubleDouble)], name: &str) -> Result<DoubleDouble, String> {
+    vars.iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| *v)
+        .ok_or_else(|| format!("no value for variable '{name}'"))
+}
+
+/// The expression evaluated in double-double precision.
+pub fn reference(expr: &Expr, vars: &[(String, DoubleDouble)]) -> Result<DoubleDouble, String> {
+    Ok(match expr {
+        Expr::Num(text) => DoubleDouble::parse(text)?,
+        Expr::Var(name) => lookup(vars, name)?,
+        Expr::Neg(e) => -reference(e, vars)?,
+        Expr::Binary(op, a, b) => {
+            let (a, b) = (reference(a, vars)?, reference(b, vars)?);
+            match op {
+                Op::Add => a + b,
+                Op::Sub => a - b,
+                Op::Mul => a * b,
+                Op::Div => a / b,
+            }
+        }
+        Expr::Pow(e, n) => reference(e, vars)?.powi(*n as i32),
+        Expr::Call(Func::Sqrt, e) => reference(e, vars)?.sqrt(),
+        Expr::Call(Func::Abs, e) => reference(e, vars)?.abs(),
+    })
+}
+
+/// Evaluates `expr` in `format`, rounding inputs and every operation result.
+pub fn evaluate(
+    expr: &Expr,
+    vars: &[(String, DoubleDouble)],
+    format: &Format,
+) -> Result<Evaluation, String> {
+    let mut steps = Vec::new();
+    let (value, reference, correct_bits) = eval_node(expr, vars, format, &mut steps)?;
+    Ok(Evaluation {
+        format: format.clone(),
+        value,
+        reference,
+        rel_error: relative_error(value, reference),
+        correct_bits,
+        steps,
+    })
+}
+
+/// Returns the node's value, its reference and its correct bits.
+fn eval_node(
+    expr: &Expr,
+    vars: &[(String, DoubleDouble)],
+    format: &Format,
+    steps: &mut Vec<Step>,
+) -> Result<(f64, DoubleDouble, f64), String> {
+    let precision = format.precision();
+    let input = |r: DoubleDouble| {
+        let v = format.round_reference(r);
+        (v, r, correct_bits(relative_error(v, r), precision))
+    };
+    let (value, reference, operand_bits) = match expr {
+        Expr::Num(text) => return Ok(input(DoubleDouble::parse(text)?)),
+        Expr::Var(name) => return Ok(input(lookup(vars, name)?)),
+        Expr::Neg(e) => {
+            // Exact in every format: no step.
+            let (v, r, bits) = eval_node(e, vars, format, steps)?;
+            return Ok((-v, -r, bits));
+        }
+        Expr::Binary(op, a, b) => {
+            let (va, ra, ba) = eval_node(a, vars, format, steps)?;
+            let (vb, rb, bb) = eval_node(b, vars, format, steps)?;
+            let (value, reference) = match op {
+                Op::Add => (format.add(va, vb), ra + rb),
+                Op::Sub => (format.sub(va, vb), ra - rb),
+                Op::Mul => (format.mul(va, vb), ra * rb),
+                Op::Div => (format.div(va, vb), ra / rb),
+            };
+            (value, reference, ba.min(bb))
+        }
+        Expr::Pow(e, n) => {
+            let (v, r, bits) = eval_node(e, vars, format, steps)?;
+            let mut value = if *n == 0 { 1.0 } else { v };
+            for _ in 1..*n {
+                value = format.mul(value, v);
+            }
+            (value, r.powi(*n as i32), bits)
+        }
+        Expr::Call(func, e) => {
+            let (v, r, bits) = eval_node(e, vars, format, steps)?;
+            match func {
+                Func::Sqrt => (format.sqrt(v), r.sqrt(), bits),
+                // Exact in every format: no step.
+                Func::Abs => return Ok((v.abs(), r.abs(), bits)),
+            }
+        }
+    };
+    let rel_error = relative_error(value, reference);
+    let bits = correct_bits(rel_error, precision);
+    steps.push(Step {
+        text: expr.to_text(),
+        value,
+        reference,
+        rel_error,
+        correct_bits: bits,
+        bits_lost: operand_bits - bits,
+    });
+    Ok((value, reference, bits))
+}
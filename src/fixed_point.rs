@@ -0,0 +1,237 @@
+//! Where repeated application of a rounded map ends up.
+//!
+//! A map such as `x -> (x / 3) * 3` evaluated in a format sends every value
+//! of the format to another value, so iterating it from any start must end in
+//! a fixed point, a cycle, or an overflow to infinity/NaN. [`analyze`] follows
+//! every start and records the transient (steps before reaching the
+//! fixed point or cycle) and which cycle it falls into. Each value's fate is
+//! memoized, so enumerating a small format visits every value about once.
+
+use crate::arith::Format;
+use crate::soft_float::SoftFloatSpec;
+use std::collections::HashMap;
+
+/// A fixed point (length 1) or longer cycle, with how many starts end in it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cycle {
+    /// Members in orbit order, starting from the smallest.
+    pub members: Vec<f64>,
+    /// Starts whose orbit ends in this cycle.
+    pub basin: u64,
+}
+
+impl Cycle {
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub format: Format,
+    /// Every value of the format was a start, rather than a sample.
+    pub exhaustive: bool,
+    pub inputs: u64,
+    /// Starts that are fixed points themselves.
+    pub already_fixed: u64,
+    /// Starts that reach a fixed point after at least one step.
+    pub converged: u64,
+    /// Starts that end in a cycle of length 2 or more.
+    pub cycling: u64,
+    /// Starts whose orbit overflows to infinity or NaN.
+    pub escaped: u64,
+    /// Starts still moving after the step limit.
+    pub unresolved: u64,
+    pub max_transient: u64,
+    /// A start with the longest transient.
+    pub longest_start: Option<f64>,
+    /// `(transient length, starts)` in increasing length.
+    pub transients: Vec<(u64, u64)>,
+    /// Largest basin first.
+    pub cycles: Vec<Cycle>,
+}
+
+impl Analysis {
+    pub fn fixed_points(&self) -> impl Iterator<Item = &Cycle> {
+        self.cycles.iter().filter(|c| c.len() == 1)
+    }
+
+    /// Smallest transient length that at least `q` of the settled starts do not exceed.
+    pub fn transient_quantile(&self, q: f64) -> u64 {
+        let settled: u64 = self.transients.iter().map(|t| t.1).sum();
+        let target = (q * settled as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for &(len, n) in &self.transients {
+            seen += n;
+            if seen >= target {
+                return len;
+            }
+        }
+        0
+    }
+}
+
+/// Where a value's orbit ends: a cycle index, or escape.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum End {
+    Cycle(usize),
+    Escape,
+}
+
+/// Folds `-0.0` into `0.0` so both are one state.
+fn key(x: f64) -> u64 {
+    if x == 0.0 {
+        0
+    } else {
+        x.to_bits()
+    }
+}
+
+/// Iterates `map` from every start, at most `max_steps` steps per orbit.
+pub fn analyze(
+    format: &Format,
+    map: impl Fn(f64) -> f64,
+    starts: &[f64],
+    exhaustive: bool,
+    max_steps: u64,
+) -> Analysis {
+    // Resolved states: steps to reach the cycle, and the end.
+    let mut known: HashMap<u64, (u64, End)> = HashMap::new();
+    let mut cycles: Vec<Cycle> = Vec::new();
+    let mut transients: HashMap<u64, u64> = HashMap::new();
+    let mut result = Analysis {
+        format: format.clone(),
+        exhaustive,
+        inputs: starts.len() as u64,
+        already_fixed: 0,
+        converged: 0,
+        cycling: 0,
+        escaped: 0,
+        unresolved: 0,
+        max_transient: 0,
+        longest_start: None,
+        transients: Vec::new(),
+        cycles: Vec::new(),
+    };
+
+    for &start in starts {
+        let mut path: Vec<f64> = Vec::new();
+        let mut on_path: HashMap<u64, usize> = HashMap::new();
+        let mut x = start;
+        // How the walk stopped: at a known state, a repeat on this path, an escape or the limit.
+        let resolved = loop {
+            if !x.is_finite() {
+                break Some((0, End::Escape));
+            }
+            if let Some(&known_end) = known.get(&key(x)) {
+                break Some(known_end);
+            }
+            if let Some(&i) = on_path.get(&key(x)) {
+                // A new cycle: path[i..] repeats.
+                let members = &path[i..];
+                let first = (0..members.len())
+                    .min_by(|&a, &b| members[a].total_cmp(&members[b]))
+                    .unwrap_or(0);
+                let mut ordered = members[first..].to_vec();
+                ordered.extend_from_slice(&members[..first]);
+                cycles.push(Cycle {
+                    members: ordered,
+                    basin: 0,
+                });
+                let end = End::Cycle(cycles.len() - 1);
+                for &m in &path[i..] {
+                    known.insert(key(m), (0, end));
+                }
+                path.truncate(i);
+                break Some((0, end));
+            }
+            if path.len() as u64 >= max_steps {
+                break None;
+            }
+            on_path.insert(key(x), path.len());
+            path.push(x);
+            x = format.round(map(x));
+        };
+        let Some((mut distance, end)) = resolved else {
+            result.unresolved += 1;
+            continue;
+        };
+        for &p in path.iter().rev() {
+            distance += 1;
+            known.insert(key(p), (distance, end));
+        }
+        let (transient, end) = known.get(&key(start)).copied().unwrap_or((0, End::Escape));
+        match end {
+            End::Escape => result.escaped += 1,
+            End::Cycle(c) => {
+                cycles[c].basin += 1;
+                *transients.entry(transient).or_default() += 1;
+                if transient > result.max_transient || result.longest_start.is_none() {
+                    result.max_transient = transient;
+                    result.longest_start = Some(start);
+                }
+                match (cycles[c].len(), transient) {
+                    (1, 0) => result.already_fixed += 1,
+                    (1, _) => result.converged += 1,
+                    _ => result.cycling += 1,
+                }
+            }
+        }
+    }
+
+    result.transients = transients.into_iter().collect();
+    result.transients.sort_unstable();
+    cycles.retain(|c| c.basin > 0);
+    cycles.sort_by(|a, b| {
+        b.basin
+            .cmp(&a.basin)
+            .then(a.members[0].total_cmp(&b.members[0]))
+    });
+    result.cycles = cycles;
+    result
+}
+
+/// Zero and every finite value of `spec` with both signs, or `None` above `limit` values.
+pub fn all_values(spec: &SoftFloatSpec, limit: u64) -> Option<Vec<f64>> {
+    let positive = spec.positive_values(limit / 2)?;
+    let mut out: Vec<f64> = positive.iter().rev().map(|&x| -x).collect();
+    out.push(0.0);
+    out.extend(positive);
+    Some(out)
+}
+
+/// `count` finite values of `format` spread over its whole range (uniform
+/// over exponents, then mantissas), the same on every run.
+pub fn sample_values(format: &Format, count: usize) -> Vec<f64> {
+    let (mantissa_bits, min_exp, max_exp) = match format {
+        Format::F32 => (23, -126, 127),
+        Format::F64 => (52, -1022, 1023),
+        Format::Soft(spec) => (spec.mantissa_bits, spec.min_exp2, spec.max_exp2),
+    };
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    (0..count)
+        .map(|_| {
+            let r = next();
+            let span = (max_exp - min_exp + 1) as u64;
+            let exp2 = min_exp + (r % span) as i32;
+            let mantissa = (next() >> 11) as f64 / (1u64 << 53) as f64;
+            let steps = 2f64.powi(mantissa_bits as i32);
+            let x = 2f64.powi(exp2) * (1.0 + (mantissa * steps).floor() / steps);
+            if r >> 63 == 1 {
+                -x
+            } else {
+                x
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,370 @@
+//! Self-contained interactive HTML reports.
+//!
+//! Each [`Chart`] reuses a [`Panel`] (axes, scales, series, colors) and adds
+//! per-point tooltip columns. The page embeds the data as JSON and draws it on
+//! a canvas with plain JavaScript: wheel to zoom, drag to pan, double-click to
+//! reset, hover for values, checkboxes to toggle series. Nothing is fetched.
+
+use crate::plot::{escape_xml, Axis, Panel, Scale, Style};
+use crate::value::{to_json, Value};
+
+/// Extra values shown when hovering a point; `rows[i]` belongs to `points[i]`.
+#[derive(Clone, Debug, Default)]
+pub struct Tooltip {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<f64>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Chart {
+    pub title: String,
+    pub panel: Panel,
+    /// One entry per `panel.series`; missing entries show only x and y.
+    pub tooltips: Vec<Tooltip>,
+}
+
+fn number(v: f64) -> Value {
+    if v.is_finite() {
+        Value::Float(v)
+    } else if v.is_nan() {
+        Value::Str("NaN".to_string())
+    } else if v > 0.0 {
+        Value::Str("inf".to_string())
+    } else {
+        Value::Str("-inf".to_string())
+    }
+}
+
+fn axis_json(axis: &Axis) -> Value {
+    let mut t = Value::table();
+    t.set("label", Value::Str(axis.label.clone()));
+    t.set("min", Value::Float(axis.min));
+    t.set("max", Value::Float(axis.max));
+    let (kind, lin) = match axis.scale {
+        Scale::Linear => ("linear", None),
+        Scale::Log10 => ("log10", None),
+        Scale::SymLog { lin } => ("symlog", Some(lin)),
+    };
+    t.set("scale", Value::Str(kind.to_string()));
+    if let Some(lin) = lin {
+        t.set("lin", Value::Float(lin));
+    }
+    t
+}
+
+fn chart_json(chart: &Chart) -> Value {
+    let mut t = Value::table();
+    t.set("title", Value::Str(chart.title.clone()));
+    t.set("x", axis_json(&chart.panel.x));
+    t.set("y", axis_json(&chart.panel.y));
+
+    let empty = Tooltip::default();
+    let series = chart
+        .panel
+        .series
+        .iter()
+        .enumerate()
+        .map(|(idx, s)| {
+            let tip = chart.tooltips.get(idx).unwrap_or(&empty);
+            let mut st = Value::table();
+            st.set("name", Value::Str(s.name.clone()));
+            st.set("color", Value::Str(s.color.clone()));
+            let style = match s.style {
+                Style::Line => "line",
+                Style::Step => "step",
+                Style::Points { .. } => "points",
+                Style::Ticks { .. } => "ticks",
+            };
+            st.set("style", Value::Str(style.to_string()));
+            st.set(
+                "columns",
+                Value::Array(tip.columns.iter().map(|c| Value::Str(c.clone())).collect()),
+            );
+            let points = s
+                .points
+                .iter()
+                .enumerate()
+                .map(|(i, &(x, y))| {
+                    let mut row = vec![number(x), number(y)];
+                    if let Some(extra) = tip.rows.get(i) {
+                        row.extend(extra.iter().map(|&v| number(v)));
+                    }
+                    Value::Array(row)
+                })
+                .collect();
+            st.set("points", Value::Array(points));
+            st
+        })
+        .collect();
+    t.set("series", Value::Array(series));
+    t
+}
+
+/// Renders the full page. `subtitle` is shown under the title as plain text.
+pub fn render_page(title: &str, subtitle: &str, charts: &[Chart]) -> String {
+    let mut data = Value::table();
+    data.set("charts", Value::Array(charts.iter().map(chart_json).collect()));
+    // `</` inside a script element would end it early; `<\/` is the same JSON string.
+    let json = to_json(&data).replace("</", "<\\/");
+
+    PAGE.replace("__TITLE__", &escape_xml(title))
+        .replace("__SUBTITLE__", &escape_xml(subtitle))
+        .replace("__DATA__", &json)
+}
+
+const PAGE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>__TITLE__</title>
+<style>
+body { font-family: sans-serif; margin: 24px; color: #222; }
+h1 { font-size: 26px; margin: 0 0 4px; }
+h2 { font-size: 18px; margin: 28px 0 6px; color: #444; }
+.sub { color: #555; margin: 0 0 8px; }
+.help { color: #777; font-size: 13px; }
+.controls { display: flex; flex-wrap: wrap; gap: 6px 18px; margin: 6px 0; font-size: 14px; }
+.controls label { cursor: pointer; white-space: nowrap; }
+.swatch { display: inline-block; width: 22px; height: 4px; margin: 0 6px 3px 2px; vertical-align: middle; }
+.wrap { position: relative; }
+canvas { width: 100%; height: 520px; border: 1px solid #ddd; cursor: crosshair; display: block; }
+.tip { position: absolute; pointer-events: none; background: rgba(255,255,255,0.96); border: 1px solid #999;
+       padding: 6px 8px; font-size: 12px; display: none; white-space: nowrap; box-shadow: 0 1px 4px rgba(0,0,0,0.2); }
+.tip table { border-collapse: collapse; }
+.tip td { padding: 0 6px 0 0; }
+.tip td.v { text-align: right; font-family: monospace; }
+button { font-size: 13px; }
+</style>
+</head>
+<body>
+<h1>__TITLE__</h1>
+<p class="sub">__SUBTITLE__</p>
+<p class="help">Wheel: zoom (Shift: x only, Alt: y only). Drag: pan. Double-click or Reset: full view. Hover a curve for exact values.</p>
+<div id="charts"></div>
+<script id="data" type="application/json">__DATA__</script>
+<script>
+(function () {
+  "use strict";
+  var DATA = JSON.parse(document.getElementById("data").textContent);
+  var M = { l: 80, r: 20, t: 16, b: 52 };
+
+  function tf(axis, v) {
+    if (typeof v !== "number") return NaN;
+    if (axis.scale === "log10") return v > 0 ? Math.log10(v) : NaN;
+    if (axis.scale === "symlog") return Math.sign(v) * Math.log10(1 + Math.abs(v) / axis.lin);
+    return v;
+  }
+  function inv(axis, t) {
+    if (axis.scale === "log10") return Math.pow(10, t);
+    if (axis.scale === "symlog") return Math.sign(t) * axis.lin * (Math.pow(10, Math.abs(t)) - 1);
+    return t;
+  }
+  function fmt(v) {
+    if (v === null || v === undefined) return "n/a";
+    if (typeof v === "string") return v;
+    if (v === 0) return "0";
+    var a = Math.abs(v);
+    if (a >= 1e6 || a < 1e-4) return v.toExponential(6);
+    return String(+v.toPrecision(10));
+  }
+  function tickLabel(axis, t, step) {
+    var v = inv(axis, t);
+    if (axis.scale === "linear") {
+      var d = step >= 1 ? 0 : Math.min(12, Math.ceil(-Math.log10(step)));
+      var s = v.toFixed(d);
+      return /^-?[0.]+$/.test(s) ? "0" : s;
+    }
+    return Math.abs(v) < 1e-12 ? "0" : String(+v.toPrecision(3));
+  }
+  function niceStep(lo, hi, n) {
+    var raw = Math.max(hi - lo, 1e-12) / n;
+    var mag = Math.pow(10, Math.floor(Math.log10(raw)));
+    var steps = [1, 2, 5, 10];
+    for (var i = 0; i < steps.length; i++) if (steps[i] * mag >= raw) return steps[i] * mag;
+    return 10 * mag;
+  }
+
+  function Chart(root, chart) {
+    var h2 = document.createElement("h2");
+    h2.textContent = chart.title;
+    root.appendChild(h2);
+    var controls = document.createElement("div");
+    controls.className = "controls";
+    root.appendChild(controls);
+    var wrap = document.createElement("div");
+    wrap.className = "wrap";
+    root.appendChild(wrap);
+    var canvas = document.createElement("canvas");
+    wrap.appendChild(canvas);
+    var tip = document.createElement("div");
+    tip.className = "tip";
+    wrap.appendChild(tip);
+
+    var series = chart.series.map(function (s) {
+      var tx = [], ty = [];
+      s.points.forEach(function (p) { tx.push(tf(chart.x, p[0])); ty.push(tf(chart.y, p[1])); });
+      return { src: s, tx: tx, ty: ty, visible: true };
+    });
+    var home = { x0: tf(chart.x, chart.x.min), x1: tf(chart.x, chart.x.max),
+                 y0: tf(chart.y, chart.y.min), y1: tf(chart.y, chart.y.max) };
+    var view = Object.assign({}, home);
+    var ctx = canvas.getContext("2d");
+    var W = 0, H = 0;
+
+    series.forEach(function (s) {
+      var label = document.createElement("label");
+      var box = document.createElement("input");
+      box.type = "checkbox";
+      box.checked = true;
+      box.addEventListener("change", function () { s.visible = box.checked; draw(); });
+      var sw = document.createElement("span");
+      sw.className = "swatch";
+      sw.style.background = s.src.color;
+      label.appendChild(box);
+      label.appendChild(sw);
+      label.appendChild(document.createTextNode(s.src.name));
+      controls.appendChild(label);
+    });
+    var reset = document.createElement("button");
+    reset.textContent = "Reset zoom";
+    reset.addEventListener("click", function () { view = Object.assign({}, home); draw(); });
+    controls.appendChild(reset);
+
+    function px(t) { return M.l + (t - view.x0) / (view.x1 - view.x0) * (W - M.l - M.r); }
+    function py(t) { return H - M.b - (t - view.y0) / (view.y1 - view.y0) * (H - M.t - M.b); }
+
+    function resize() {
+      var r = canvas.getBoundingClientRect(), dpr = window.devicePixelRatio || 1;
+      W = r.width; H = r.height;
+      canvas.width = Math.round(W * dpr); canvas.height = Math.round(H * dpr);
+      ctx.setTransform(dpr, 0, 0, dpr, 0, 0);
+      draw();
+    }
+
+    function draw() {
+      ctx.clearRect(0, 0, W, H);
+      ctx.font = "12px sans-serif";
+      ctx.lineWidth = 1;
+      var sx = niceStep(view.x0, view.x1, 8), sy = niceStep(view.y0, view.y1, 8), t;
+      ctx.fillStyle = "#222";
+      ctx.textAlign = "center";
+      for (t = Math.ceil(view.x0 / sx) * sx; t <= view.x1 + sx * 1e-9; t += sx) {
+        ctx.strokeStyle = "#ececec";
+        ctx.beginPath(); ctx.moveTo(px(t), M.t); ctx.lineTo(px(t), H - M.b); ctx.stroke();
+        ctx.fillText(tickLabel(chart.x, t, sx), px(t), H - M.b + 16);
+      }
+      ctx.textAlign = "right";
+      for (t = Math.ceil(view.y0 / sy) * sy; t <= view.y1 + sy * 1e-9; t += sy) {
+        ctx.strokeStyle = "#ececec";
+        ctx.beginPath(); ctx.moveTo(M.l, py(t)); ctx.lineTo(W - M.r, py(t)); ctx.stroke();
+        ctx.fillText(tickLabel(chart.y, t, sy), M.l - 6, py(t) + 4);
+      }
+      ctx.strokeStyle = "#222"; ctx.lineWidth = 2;
+      ctx.beginPath(); ctx.moveTo(M.l, M.t); ctx.lineTo(M.l, H - M.b); ctx.lineTo(W - M.r, H - M.b); ctx.stroke();
+      ctx.font = "14px sans-serif"; ctx.textAlign = "center";
+      ctx.fillText(chart.x.label, (M.l + W - M.r) / 2, H - 10);
+      ctx.save(); ctx.translate(16, (M.t + H - M.b) / 2); ctx.rotate(-Math.PI / 2);
+      ctx.fillText(chart.y.label, 0, 0); ctx.restore();
+
+      ctx.save();
+      ctx.beginPath(); ctx.rect(M.l, M.t, W - M.l - M.r, H - M.t - M.b); ctx.clip();
+      series.forEach(function (s) {
+        if (!s.visible) return;
+        ctx.strokeStyle = s.src.color; ctx.fillStyle = s.src.color; ctx.lineWidth = 2;
+        if (s.src.style === "points" || s.src.style === "ticks") {
+          for (var i = 0; i < s.tx.length; i++) {
+            if (!isFinite(s.tx[i]) || !isFinite(s.ty[i])) continue;
+            var cx = px(s.tx[i]), cy = py(s.ty[i]);
+            ctx.beginPath();
+            if (s.src.style === "ticks") { ctx.moveTo(cx, cy - 8); ctx.lineTo(cx, cy + 8); ctx.stroke(); }
+            else { ctx.arc(cx, cy, 4, 0, 2 * Math.PI); ctx.fill(); }
+          }
+          return;
+        }
+        ctx.beginPath();
+        var pen = false, lastY = 0;
+        for (var j = 0; j < s.tx.length; j++) {
+          if (!isFinite(s.tx[j]) || !isFinite(s.ty[j])) { pen = false; continue; }
+          var X = px(s.tx[j]), Y = py(s.ty[j]);
+          if (!pen) ctx.moveTo(X, Y);
+          else { if (s.src.style === "step") ctx.lineTo(X, lastY); ctx.lineTo(X, Y); }
+          pen = true; lastY = Y;
+        }
+        ctx.stroke();
+      });
+      ctx.restore();
+    }
+
+    function nearest(mx, my) {
+      var best = null, bestD = 144;
+      series.forEach(function (s) {
+        if (!s.visible) return;
+        for (var i = 0; i < s.tx.length; i++) {
+          var dx = px(s.tx[i]) - mx, dy = py(s.ty[i]) - my, d = dx * dx + dy * dy;
+          if (d < bestD) { bestD = d; best = { s: s, i: i }; }
+        }
+      });
+      return best;
+    }
+
+    function showTip(hit, mx, my) {
+      if (!hit) { tip.style.display = "none"; return; }
+      var s = hit.s.src, p = s.points[hit.i];
+      var rows = [[chart.x.label, p[0]], [chart.y.label, p[1]]];
+      s.columns.forEach(function (c, k) { rows.push([c, p[k + 2]]); });
+      tip.innerHTML = "";
+      var head = document.createElement("div");
+      head.style.color = s.color; head.style.fontWeight = "bold"; head.textContent = s.name;
+      tip.appendChild(head);
+      var table = document.createElement("table");
+      rows.forEach(function (r) {
+        var tr = document.createElement("tr"), a = document.createElement("td"), b = document.createElement("td");
+        a.textContent = r[0]; b.textContent = fmt(r[1]); b.className = "v";
+        tr.appendChild(a); tr.appendChild(b); table.appendChild(tr);
+      });
+      tip.appendChild(table);
+      tip.style.display = "block";
+      var left = mx + 14;
+      if (left + tip.offsetWidth > W) left = mx - 14 - tip.offsetWidth;
+      tip.style.left = left + "px";
+      tip.style.top = Math.max(0, my - tip.offsetHeight - 10) + "px";
+    }
+
+    var drag = null;
+    canvas.addEventListener("mousedown", function (e) {
+      drag = { x: e.offsetX, y: e.offsetY, v: Object.assign({}, view) };
+    });
+    window.addEventListener("mouseup", function () { drag = null; });
+    canvas.addEventListener("mouseleave", function () { tip.style.display = "none"; });
+    canvas.addEventListener("mousemove", function (e) {
+      if (drag) {
+        var kx = (drag.v.x1 - drag.v.x0) / (W - M.l - M.r), ky = (drag.v.y1 - drag.v.y0) / (H - M.t - M.b);
+        var dx = (e.offsetX - drag.x) * kx, dy = (e.offsetY - drag.y) * ky;
+        view = { x0: drag.v.x0 - dx, x1: drag.v.x1 - dx, y0: drag.v.y0 + dy, y1: drag.v.y1 + dy };
+        tip.style.display = "none";
+        draw();
+        return;
+      }
+      showTip(nearest(e.offsetX, e.offsetY), e.offsetX, e.offsetY);
+    });
+    canvas.addEventListener("wheel", function (e) {
+      e.preventDefault();
+      var f = e.deltaY > 0 ? 1.25 : 0.8;
+      var cx = view.x0 + (e.offsetX - M.l) / (W - M.l - M.r) * (view.x1 - view.x0);
+      var cy = view.y0 + (H - M.b - e.offsetY) / (H - M.t - M.b) * (view.y1 - view.y0);
+      if (!e.altKey) { view.x0 = cx + (view.x0 - cx) * f; view.x1 = cx + (view.x1 - cx) * f; }
+      if (!e.shiftKey) { view.y0 = cy + (view.y0 - cy) * f; view.y1 = cy + (view.y1 - cy) * f; }
+      draw();
+    }, { passive: false });
+    canvas.addEventListener("dblclick", function () { view = Object.assign({}, home); draw(); });
+    window.addEventListener("resize", resize);
+    resize();
+  }
+
+  var root = document.getElementById("charts");
+  DATA.charts.forEach(function (c) { Chart(root, c); });
+})();
+</script>
+</body>
+</html>
+"##;
@@ -0,0 +1,338 @@
+//! How one value is stored in a [`SoftFloatSpec`]: the fields it is encoded
+//! with, the representable values around it and why it rounds the way it does.
+//!
+//! The rounding model is the one of [`SoftFloatSpec::quantize`]: round to
+//! nearest with ties away from zero, magnitudes below the smallest normal
+//! flushed to zero, magnitudes past the largest finite value sent to infinity.
+
+use crate::soft_float::{exponent_of, Flags, SoftFloatSpec, Tininess};
+
+/// Which way [`SoftFloatSpec::quantize`] resolved a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// The value is representable as is.
+    Exact,
+    /// Less than half an ULP above the smaller-magnitude candidate.
+    TowardZero,
+    /// More than half an ULP above the smaller-magnitude candidate.
+    AwayFromZero,
+    /// Exactly halfway between the candidates; ties go away from zero.
+    TieAwayFromZero,
+    /// Below the smallest normal magnitude; flushed to zero (no subnormals).
+    Underflow,
+    /// Beyond the largest finite magnitude, or rounded past it.
+    Overflow,
+    /// NaN or infinity, passed through unchanged.
+    NotFinite,
+}
+
+impl Rounding {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Rounding::Exact => "exact, no rounding",
+            Rounding::TowardZero => "rounds toward zero (nearer candidate)",
+            Rounding::AwayFromZero => "rounds away from zero (nearer candidate)",
+            Rounding::TieAwayFromZero => "tie, rounds away from zero",
+            Rounding::Underflow => "below the smallest normal, flushed to zero",
+            Rounding::Overflow => "beyond the largest finite value, overflows to infinity",
+            Rounding::NotFinite => "not finite, passed through",
+        }
+    }
+}
+
+/// Everything [`inspect`] found out about one value in one format.
+#[derive(Clone, Debug)]
+pub struct Inspection {
+    pub spec: SoftFloatSpec,
+    pub value: f64,
+    pub stored: f64,
+    pub negative: bool,
+    /// Unbiased exponent of the stored value; `None` for zero, infinity and NaN.
+    pub exponent: Option<i32>,
+    /// Exponent field code, laid out as in [`SoftFloatSpec::exponent_bits`]:
+    /// 0 for zero, all ones for infinity and NaN.
+    pub biased_exponent: u64,
+    pub bias: i64,
+    /// Stored fraction bits (hidden leading 1 left out), most significant first.
+    pub mantissa: String,
+    /// Representable values next to the stored one; infinite when there is none.
+    pub below: f64,
+    pub above: f64,
+    /// Spacing of representable values in the binade of `value`, when it is in range.
+    pub ulp: Option<f64>,
+    /// The representable magnitudes bracketing `|value|` and its distance from
+    /// the smaller one in ULPs, when `value` is in range and not representable.
+    /// For values that flush, `0` and the smallest normal, and the distance as
+    /// a fraction of the gap between them.
+    pub candidates: Option<(f64, f64, f64)>,
+    pub rounding: Rounding,
+    /// Exception flags raised by storing the value.
+    pub flags: Flags,
+    pub abs_error: f64,
+    pub rel_error: f64,
+}
+
+impl Inspection {
+    /// Whether the stored value is above, below or equal to the input: "up", "down" or "none".
+    pub fn direction(&self) -> &'static str {
+        if self.stored > self.value {
+            "up"
+        } else if self.stored < self.value {
+            "down"
+        } else {
+            "none"
+        }
+    }
+
+    /// Sign, exponent and mantissa fields as binary digits separated by spaces.
+    pub fn bit_pattern(&self) -> String {
+        let width = self.spec.exponent_bits() as usize;
+        format!(
+            "{} {:0width$b} {}",
+            u8::from(self.negative),
+            self.biased_exponent,
+            self.mantissa
+        )
+    }
+
+    /// Multi-line, human-readable account of the fields above.
+    pub fn report(&self) -> String {
+        let spec = &self.spec;
+        let mut lines = vec![format!(
+            "{} in {} ({} mantissa bits, exponents {}..={}, {} bits)",
+            self.value,
+            spec.name,
+            spec.mantissa_bits,
+            spec.min_exp2,
+            spec.max_exp2,
+            spec.total_bits()
+        )];
+        let mut row = |label: &str, text: String| lines.push(format!("  {label:<16} {text}"));
+
+        row("input (exact)", exact_decimal(self.value));
+        row(
+            "sign",
+            format!(
+                "{} ({})",
+                if self.negative { '-' } else { '+' },
+                u8::from(self.negative)
+            ),
+        );
+        row(
+            "exponent",
+            match self.exponent {
+                Some(e) => format!("{e} (biased {}, bias {})", self.biased_exponent, self.bias),
+                None => format!("- (field {}, reserved)", self.biased_exponent),
+            },
+        );
+        let significand = match self.exponent {
+            Some(_) if self.mantissa.is_empty() => "1".to_string(),
+            Some(_) => format!("1.{}", self.mantissa),
+            None => "-".to_string(),
+        };
+        row(
+            "mantissa",
+            format!("{significand} (stored bits: {})", self.mantissa),
+        );
+        row("bit pattern", self.bit_pattern());
+        row(
+            "ulp",
+            match (self.ulp, self.rounding) {
+                (Some(ulp), _) => format!("{ulp:e} (2^{})", exponent_of(ulp)),
+                (None, Rounding::Underflow | Rounding::Overflow) => {
+                    "- (outside the format's exponent range)".to_string()
+                }
+                (None, _) => "-".to_string(),
+            },
+        );
+        row(
+            "neighbors",
+            format!("below {:e}, above {:e}", self.below, self.above),
+        );
+        if let Some((lower, upper, position)) = self.candidates {
+            let position = if self.rounding == Rounding::Underflow {
+                format!("{position:.6} of the way from the first to the second")
+            } else {
+                format!("{position:.6} ULP above the first")
+            };
+            row(
+                "candidates",
+                format!("{lower:e} and {upper:e} (|x| is {position})"),
+            );
+        }
+        row(
+            "rounding",
+            format!(
+                "{}, direction {}",
+                self.rounding.describe(),
+                self.direction()
+            ),
+        );
+        row("flags", self.flags.names());
+        row("stored", format!("{:e}", self.stored));
+        row("stored (exact)", exact_decimal(self.stored));
+        row("abs error", format!("{:e}", self.abs_error));
+        row("rel error", format!("{:e}", self.rel_error));
+        lines.join("\n")
+    }
+}
+
+/// Explains how `x` is stored in `spec`.
+pub fn inspect(spec: &SoftFloatSpec, x: f64) -> Inspection {
+    let (stored, flags) = spec.quantize_flags(x, Tininess::BeforeRounding);
+    let ax = x.abs();
+    let m = spec.mantissa_bits;
+    let bias = 1 - spec.min_exp2 as i64;
+    let all_ones = (1u64 << spec.exponent_bits()) - 1;
+
+    let mut ulp = None;
+    let mut candidates = None;
+    let rounding = if !x.is_finite() {
+        Rounding::NotFinite
+    } else if x == 0.0 {
+        Rounding::Exact
+    } else if exponent_of(ax) < spec.min_exp2 {
+        let min_normal = spec.min_normal();
+        candidates = Some((0.0, min_normal, ax / min_normal));
+        Rounding::Underflow
+    } else if exponent_of(ax) > spec.max_exp2 {
+        Rounding::Overflow
+    } else {
+        let step = 2f64.powi(exponent_of(ax) - m as i32);
+        ulp = Some(step);
+        // Both the remainder and the truncated magnitude are exact in f64.
+        let rem = if step > 0.0 { ax % step } else { 0.0 };
+        let lower = ax - rem;
+        let position = if step > 0.0 { rem / step } else { 0.0 };
+        if rem == 0.0 {
+            Rounding::Exact
+        } else {
+            candidates = Some((lower, lower + step, position));
+            if stored.is_infinite() {
+                Rounding::Overflow
+            } else if position < 0.5 {
+                Rounding::TowardZero
+            } else if position > 0.5 {
+                Rounding::AwayFromZero
+            } else {
+                Rounding::TieAwayFromZero
+            }
+        }
+    };
+
+    let a = stored.abs();
+    let (exponent, biased_exponent, mantissa) = if a == 0.0 {
+        (None, 0, "0".repeat(m as usize))
+    } else if a.is_nan() {
+        (
+            None,
+            all_ones,
+            format!("1{}", "0".repeat(m.saturating_sub(1) as usize)),
+        )
+    } else if a.is_infinite() {
+        (None, all_ones, "0".repeat(m as usize))
+    } else {
+        let e = exponent_of(a);
+        (Some(e), (e as i64 + bias) as u64, fraction_bits(a, e, m))
+    };
+
+    let (below, above) = neighbors(spec, a);
+    let (below, above) = if a == 0.0 {
+        (-above, above)
+    } else if stored < 0.0 {
+        (-above, -below)
+    } else {
+        (below, above)
+    };
+
+    // An exactly stored infinity has no error; `inf - inf` would say NaN.
+    let (abs_error, rel_error) = if stored == x {
+        (0.0, 0.0)
+    } else {
+        ((stored - x).abs(), (stored - x).abs() / ax)
+    };
+    Inspection {
+        spec: spec.clone(),
+        value: x,
+        stored,
+        negative: x.is_sign_negative(),
+        exponent,
+        biased_exponent,
+        bias,
+        mantissa,
+        below,
+        above,
+        ulp,
+        candidates,
+        rounding,
+        flags,
+        abs_error,
+        rel_error,
+    }
+}
+
+/// The first `m` bits after the binary point of `a / 2^e` (which is in `[1, 2)`).
+fn fraction_bits(a: f64, e: i32, m: u32) -> String {
+    // Scale in two steps so 2^-e cannot overflow for subnormal inputs.
+    let mut f = a * 2f64.powi(-e / 2) * 2f64.powi(-(e - e / 2)) - 1.0;
+    let mut out = String::with_capacity(m as usize);
+    for _ in 0..m {
+        f *= 2.0;
+        if f >= 1.0 {
+            out.push('1');
+            f -= 1.0;
+        } else {
+            out.push('0');
+        }
+    }
+    out
+}
+
+/// Representable magnitudes just below and above the magnitude `a`.
+fn neighbors(spec: &SoftFloatSpec, a: f64) -> (f64, f64) {
+    if a.is_nan() {
+        return (f64::NAN, f64::NAN);
+    }
+    if a == 0.0 {
+        return (0.0, spec.min_normal());
+    }
+    if a.is_infinite() {
+        return (spec.max_finite(), f64::INFINITY);
+    }
+    let e = exponent_of(a);
+    let step = 2f64.powi(e - spec.mantissa_bits as i32);
+    let above = if a >= spec.max_finite() {
+        f64::INFINITY
+    } else {
+        a + step
+    };
+    let below = if a == 2f64.powi(e) {
+        if e <= spec.min_exp2 {
+            0.0
+        } else {
+            a - step / 2.0
+        }
+    } else {
+        a - step
+    };
+    (below, above)
+}
+
+/// Every digit of the binary value `x` in decimal, e.g. `0.1` prints as
+/// `0.1000000000000000055511151231257827021181583404541015625`.
+pub fn exact_decimal(x: f64) -> String {
+    if !x.is_finite() || x == 0.0 {
+        return format!("{x}");
+    }
+    // x = odd * 2^exp, which needs exactly max(0, -exp) digits after the point.
+    let bits = x.to_bits();
+    let field = ((bits >> 52) & 0x7ff) as i32;
+    let frac = bits & ((1u64 << 52) - 1);
+    let (sig, exp) = if field == 0 {
+        (frac, -1074)
+    } else {
+        (frac | (1u64 << 52), field - 1075)
+    };
+    let digits = (-(exp + sig.trailing_zeros() as i32)).max(0) as usize;
+    format!("{x:.digits$}")
+}
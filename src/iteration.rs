@@ -0,0 +1,268 @@
+//! How rounding error grows over many iterations of a step function.
+//!
+//! A study runs the step twice side by side: once with every operation
+//! rounded into the format, once in double-double as the reference. The
+//! error is measured after every step (relative to the largest reference
+//! component), and kept on a log-spaced schedule so that runs of 10^7 steps
+//! stay small. The growth is then classified from the running maximum of the
+//! error: bounded, `~sqrt(n)` like a random walk, linear, a steeper power, or
+//! exponential.
+
+use crate::arith::Format;
+use crate::double_double::DoubleDouble;
+
+/// Error after one recorded step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub step: u64,
+    /// First state component in the format and in the reference.
+    pub value: f64,
+    pub reference: f64,
+    /// Largest componentwise difference.
+    pub abs_error: f64,
+    /// `abs_error` over the largest reference component.
+    pub rel_error: f64,
+    /// Largest `rel_error` over all steps so far, recorded or not.
+    pub max_rel_error: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Growth {
+    /// The format reproduced the reference exactly.
+    None,
+    Bounded,
+    /// `~sqrt(n)`: independent rounding errors of random sign.
+    RandomWalk,
+    /// `~n`: a systematic error per step.
+    Linear,
+    /// `~n^p` with `p` clearly above 1.
+    Polynomial,
+    /// Each step multiplies the error, as in chaotic or unstable iterations.
+    Exponential,
+}
+
+impl Growth {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Growth::None => "no error",
+            Growth::Bounded => "bounded",
+            Growth::RandomWalk => "~sqrt(n), random walk",
+            Growth::Linear => "~n, linear",
+            Growth::Polynomial => "faster than linear, polynomial",
+            Growth::Exponential => "exponential",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Classification {
+    pub growth: Growth,
+    /// Slope of log(error) against log(step).
+    pub slope: f64,
+    /// Growth rate per step of ln(error), for exponential growth.
+    pub rate: f64,
+    /// First step whose relative error reached 1/2; later steps are not classified.
+    pub saturated_at: Option<u64>,
+}
+
+impl Classification {
+    pub fn describe(&self) -> String {
+        let mut out = match self.growth {
+            Growth::None => return Growth::None.describe().to_string(),
+            Growth::Exponential => format!(
+                "{} (x2 every {:.1} steps)",
+                self.growth.describe(),
+                std::f64::consts::LN_2 / self.rate
+            ),
+            g => format!("{} (log-log slope {:.2})", g.describe(), self.slope),
+        };
+        if let Some(step) = self.saturated_at {
+            out.push_str(&format!(", no correct digits left from step {step}"));
+        }
+        out
+    }
+}
+
+/// One format's run.
+#[derive(Clone, Debug)]
+pub struct Study {
+    pub format: Format,
+    pub steps: u64,
+    pub samples: Vec<Sample>,
+    pub classification: Classification,
+}
+
+impl Study {
+    pub fn final_sample(&self) -> Option<&Sample> {
+        self.samples.last()
+    }
+}
+
+/// Runs `steps` iterations from `start`. `exact` advances the double-double
+/// state in place, `rounded` the state in `format`; both get the step index
+/// (starting at 1).
+pub fn study(
+    format: &Format,
+    start: &[DoubleDouble],
+    steps: u64,
+    mut exact: impl FnMut(&mut [DoubleDouble], u64),
+    mut rounded: impl FnMut(&Format, &mut [f64], u64),
+) -> Study {
+    let mut reference = start.to_vec();
+    let mut state: Vec<f64> = start.iter().map(|&x| format.round_reference(x)).collect();
+    let mut samples = Vec::new();
+    let mut max_rel = 0.0f64;
+    let mut next_record = 1;
+    for step in 1..=steps {
+        exact(&mut reference, step);
+        rounded(format, &mut state, step);
+        let (abs, rel) = state_error(&state, &reference);
+        max_rel = max_rel.max(rel);
+        if step == next_record || step == steps {
+            samples.push(Sample {
+                step,
+                value: state.first().copied().unwrap_or(0.0),
+                reference: reference.first().map_or(0.0, |r| r.to_f64()),
+                abs_error: abs,
+                rel_error: rel,
+                max_rel_error: max_rel,
+            });
+            // Every step up to 100, then about 100 per decade.
+            next_record = if step < 100 {
+                step + 1
+            } else {
+                (step as f64 * 1.0233).ceil() as u64
+            };
+        }
+    }
+    let classification = classify(&samples);
+    Study {
+        format: format.clone(),
+        steps,
+        samples,
+        classification,
+    }
+}
+
+fn state_error(state: &[f64], reference: &[DoubleDouble]) -> (f64, f64) {
+    let mut abs = 0.0f64;
+    let mut scale = 0.0f64;
+    for (&x, &r) in state.iter().zip(reference) {
+        let diff = (DoubleDouble::from(x) - r).abs().to_f64();
+        abs = if diff.is_nan() {
+            f64::INFINITY
+        } else {
+            abs.max(diff)
+        };
+        scale = scale.max(r.abs().to_f64());
+    }
+    let rel = if abs == 0.0 {
+        0.0
+    } else if scale == 0.0 {
+        f64::INFINITY
+    } else {
+        abs / scale
+    };
+    (abs, rel)
+}
+
+/// Least-squares slope and R² of `y` against `x`.
+fn fit(points: &[(f64, f64)]) -> (f64, f64) {
+    let n = points.len() as f64;
+    let (mx, my) = points
+        .iter()
+        .fold((0.0, 0.0), |(a, b), p| (a + p.0 / n, b + p.1 / n));
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for &(x, y) in points {
+        sxx += (x - mx) * (x - mx);
+        sxy += (x - mx) * (y - my);
+        syy += (y - my) * (y - my);
+    }
+    if sxx == 0.0 {
+        return (0.0, 0.0);
+    }
+    let slope = sxy / sxx;
+    let r2 = if syy == 0.0 {
+        1.0
+    } else {
+        sxy * sxy / (sxx * syy)
+    };
+    (slope, r2)
+}
+
+/// Classifies the running maximum error of `samples`, ignoring steps after it saturates.
+pub fn classify(samples: &[Sample]) -> Classification {
+    let saturated_at = samples
+        .iter()
+        .find(|s| s.max_rel_error >= 0.5)
+        .map(|s| s.step);
+    let growing: Vec<&Sample> = samples
+        .iter()
+        .filter(|s| s.max_rel_error > 0.0 && s.max_rel_error < 0.5)
+        .collect();
+    let none = Classification {
+        growth: Growth::None,
+        slope: 0.0,
+        rate: 0.0,
+        saturated_at,
+    };
+    if growing.is_empty() {
+        // Saturated from the first recorded step: nothing left to grow.
+        return if saturated_at.is_some() {
+            Classification {
+                growth: Growth::Bounded,
+                ..none
+            }
+        } else {
+            none
+        };
+    }
+    let log_log: Vec<(f64, f64)> = growing
+        .iter()
+        .map(|s| ((s.step as f64).ln(), s.max_rel_error.ln()))
+        .collect();
+    let log_lin: Vec<(f64, f64)> = growing
+        .iter()
+        .map(|s| (s.step as f64, s.max_rel_error.ln()))
+        .collect();
+    let (slope, r2_power) = fit(&log_log);
+    let (rate, r2_exp) = fit(&log_lin);
+    let growth = if slope > 1.5 && rate > 0.0 && r2_exp > r2_power {
+        Growth::Exponential
+    } else if slope < 0.2 {
+        Growth::Bounded
+    } else if slope < 0.75 {
+        Growth::RandomWalk
+    } else if slope < 1.5 {
+        Growth::Linear
+    } else {
+        Growth::Polynomial
+    };
+    Classification {
+        growth,
+        slope,
+        rate,
+        saturated_at,
+    }
+}
+
+/// One row per recorded step of every study:
+/// `format,step,value,reference,abs_error,rel_error,max_rel_error`.
+pub fn csv(studies: &[Study]) -> String {
+    let mut out = String::from("format,step,value,reference,abs_error,rel_error,max_rel_error\n");
+    for study in studies {
+        for s in &study.samples {
+            out.push_str(&format!(
+                "{},{},{:.17e},{:.17e},{:.6e},{:.6e},{:.6e}\n",
+                study.format.name(),
+                s.step,
+                s.value,
+                s.reference,
+                s.abs_error,
+                s.rel_error,
+                s.max_rel_error
+            ));
+        }
+    }
+    out
+}
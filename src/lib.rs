@@ -1 +1,23 @@
+pub mod arith;
+pub mod cancellation;
+pub mod cli;
+pub mod conditioning;
+pub mod double_double;
+pub mod elementary;
+pub mod eval;
+pub mod fixed_point;
+pub mod html;
+pub mod inspect;
+pub mod iteration;
+pub mod plot;
+pub mod png;
+pub mod poly;
+pub mod profile;
+pub mod raster;
+pub mod report;
+pub mod shadow;
 pub mod soft_float;
+pub mod tty;
+pub mod value;
+pub mod vectors;
+pub mod verify;
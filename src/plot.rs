@@ -0,0 +1,989 @@
+//! Small plotting layer shared by the binaries.
+//!
+//! A [`Figure`] describes panels, axes and series in data coordinates.
+//! [`Figure::shapes`] lays it out into pixel-space [`Shape`]s, and
+//! [`shapes_to_svg`] serializes those; [`crate::raster`] rasterizes the same
+//! list for PNG. Keeping the shape list as the only thing a backend sees
+//! means every output format draws the same chart.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const PALETTE: [&str; 10] = [
+    "#1565c0", "#c62828", "#2e7d32", "#6a1b9a", "#ef6c00", "#00695c", "#283593", "#ad1457",
+    "#0277bd", "#5d4037",
+];
+
+pub fn palette_color(idx: usize) -> &'static str {
+    PALETTE[idx % PALETTE.len()]
+}
+
+pub fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn map(value: f64, src_min: f64, src_max: f64, dst_min: f64, dst_max: f64) -> f64 {
+    let t = (value - src_min) / (src_max - src_min);
+    dst_min + t * (dst_max - dst_min)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scale {
+    Linear,
+    Log10,
+    /// Symmetric log: about linear within `±lin`, log10-like outside, odd in `x`.
+    SymLog {
+        lin: f64,
+    },
+}
+
+impl Scale {
+    pub fn apply(self, v: f64) -> f64 {
+        match self {
+            Scale::Linear => v,
+            Scale::Log10 => {
+                if v > 0.0 {
+                    v.log10()
+                } else {
+                    f64::NAN
+                }
+            }
+            Scale::SymLog { lin } => v.signum() * (1.0 + v.abs() / lin).log10(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Ticks {
+    /// Roughly this many ticks, placed according to the scale.
+    Auto(usize),
+    /// Every multiple of the step inside the range (linear axes).
+    Every(f64),
+    Values(Vec<f64>),
+    Labeled(Vec<(f64, String)>),
+    None,
+}
+
+#[derive(Clone, Debug)]
+pub struct Axis {
+    pub label: String,
+    pub min: f64,
+    pub max: f64,
+    pub scale: Scale,
+    pub ticks: Ticks,
+    /// No axis line, ticks, grid or label (e.g. the y axis of a number line).
+    pub hidden: bool,
+}
+
+impl Axis {
+    pub fn linear(label: impl Into<String>, min: f64, max: f64) -> Self {
+        Self {
+            label: label.into(),
+            min,
+            max,
+            scale: Scale::Linear,
+            ticks: Ticks::Auto(8),
+            hidden: false,
+        }
+    }
+
+    pub fn log10(label: impl Into<String>, min: f64, max: f64) -> Self {
+        Self {
+            scale: Scale::Log10,
+            ..Self::linear(label, min, max)
+        }
+    }
+
+    pub fn symlog(label: impl Into<String>, min: f64, max: f64, lin: f64) -> Self {
+        Self {
+            scale: Scale::SymLog { lin },
+            ..Self::linear(label, min, max)
+        }
+    }
+
+    pub fn ticks(mut self, ticks: Ticks) -> Self {
+        self.ticks = ticks;
+        self
+    }
+
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Data value to pixel position between `p0` (at `min`) and `p1` (at `max`).
+    pub fn project(&self, v: f64, p0: f64, p1: f64) -> f64 {
+        let lo = self.scale.apply(self.min);
+        let hi = self.scale.apply(self.max);
+        map(self.scale.apply(v), lo, hi, p0, p1)
+    }
+
+    pub fn tick_positions(&self) -> Vec<(f64, String)> {
+        let (lo, hi) = (self.min.min(self.max), self.min.max(self.max));
+        match &self.ticks {
+            Ticks::None => Vec::new(),
+            Ticks::Labeled(ticks) => ticks.clone(),
+            Ticks::Values(values) => {
+                let step = values
+                    .windows(2)
+                    .map(|w| (w[1] - w[0]).abs())
+                    .filter(|d| *d > 0.0)
+                    .fold(f64::INFINITY, f64::min);
+                let step = if step.is_finite() { step } else { 1.0 };
+                values.iter().map(|&v| (v, format_tick(v, step))).collect()
+            }
+            Ticks::Every(step) => {
+                let step = step.abs().max(f64::MIN_POSITIVE);
+                let first = (lo / step).ceil() as i64;
+                let last = (hi / step + 1e-9).floor() as i64;
+                (first..=last)
+                    .map(|i| {
+                        let v = i as f64 * step;
+                        (v, format_tick(v, step))
+                    })
+                    .collect()
+            }
+            Ticks::Auto(count) => match self.scale {
+                Scale::Linear => {
+                    let step = nice_step(lo, hi, *count);
+                    let first = (lo / step).ceil() as i64;
+                    let last = (hi / step + 1e-9).floor() as i64;
+                    (first..=last)
+                        .map(|i| {
+                            let v = i as f64 * step;
+                            (v, format_tick(v, step))
+                        })
+                        .collect()
+                }
+                Scale::Log10 => {
+                    let d0 = lo.log10().ceil() as i32;
+                    let d1 = hi.log10().floor() as i32;
+                    let stride = decade_stride(d0, d1, *count);
+                    (d0..=d1)
+                        .filter(|j| j.rem_euclid(stride) == 0)
+                        .map(|j| (10f64.powi(j), decade_label(j)))
+                        .collect()
+                }
+                Scale::SymLog { lin } => {
+                    let max_abs = lo.abs().max(hi.abs());
+                    let d0 = lin.log10().ceil() as i32;
+                    let d1 = max_abs.log10().floor() as i32;
+                    let stride = decade_stride(d0, d1, count.div_ceil(2));
+                    let span = self.scale.apply(hi) - self.scale.apply(lo);
+                    let mut out = Vec::new();
+                    if lo <= 0.0 && hi >= 0.0 {
+                        out.push((0.0, "0".to_string()));
+                    }
+                    for j in (d0..=d1).filter(|j| j.rem_euclid(stride) == 0) {
+                        let v = 10f64.powi(j);
+                        for signed in [-v, v] {
+                            let t = self.scale.apply(signed);
+                            // Ticks hugging zero would overprint the "0" label.
+                            if signed >= lo && signed <= hi && t.abs() >= 0.03 * span {
+                                let label = if signed < 0.0 {
+                                    format!("-{}", decade_label(j))
+                                } else {
+                                    decade_label(j)
+                                };
+                                out.push((signed, label));
+                            }
+                        }
+                    }
+                    out.sort_by(|a, b| a.0.total_cmp(&b.0));
+                    out
+                }
+            },
+        }
+    }
+}
+
+fn decade_stride(d0: i32, d1: i32, count: usize) -> i32 {
+    let n = (d1 - d0 + 1).max(1) as usize;
+    n.div_ceil(count.max(1)).max(1) as i32
+}
+
+/// `10^j` as `0.01` ... `1000` near one, `1e-6` style elsewhere.
+pub fn decade_label(j: i32) -> String {
+    if (-3..=3).contains(&j) {
+        format!("{}", 10f64.powi(j))
+    } else {
+        format!("1e{j}")
+    }
+}
+
+/// A 1/2/5 x 10^n step giving at most about `count` ticks over `[lo, hi]`.
+pub fn nice_step(lo: f64, hi: f64, count: usize) -> f64 {
+    let span = (hi - lo).abs().max(1e-12);
+    let raw = span / count.max(1) as f64;
+    let mag = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * mag)
+        .find(|s| *s >= raw)
+        .unwrap_or(10.0 * mag)
+}
+
+/// Formats `v` with just enough decimals to tell ticks `step` apart.
+pub fn format_tick(v: f64, step: f64) -> String {
+    let decimals = if step >= 1.0 {
+        0
+    } else {
+        ((-step.log10()).ceil() as usize).min(12)
+    };
+    let s = format!("{v:.decimals$}");
+    if s.trim_start_matches('-').chars().all(|c| c == '0' || c == '.') {
+        "0".to_string()
+    } else {
+        s
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    Line,
+    /// Horizontal then vertical segments between points.
+    Step,
+    Points {
+        radius: f64,
+        filled: bool,
+    },
+    /// A vertical mark `half_height` pixels above and below each point (rug plot).
+    Ticks {
+        half_height: f64,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Series {
+    pub name: String,
+    pub color: String,
+    pub width: f64,
+    pub style: Style,
+    pub dashed: bool,
+    pub points: Vec<(f64, f64)>,
+}
+
+impl Series {
+    pub fn line(name: impl Into<String>, color: impl Into<String>, points: Vec<(f64, f64)>) -> Self {
+        Self {
+            name: name.into(),
+            color: color.into(),
+            width: 2.0,
+            style: Style::Line,
+            dashed: false,
+            points,
+        }
+    }
+
+    pub fn step(name: impl Into<String>, color: impl Into<String>, points: Vec<(f64, f64)>) -> Self {
+        Self {
+            style: Style::Step,
+            ..Self::line(name, color, points)
+        }
+    }
+
+    pub fn scatter(
+        name: impl Into<String>,
+        color: impl Into<String>,
+        points: Vec<(f64, f64)>,
+        radius: f64,
+        filled: bool,
+    ) -> Self {
+        Self {
+            style: Style::Points { radius, filled },
+            ..Self::line(name, color, points)
+        }
+    }
+
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn dashed(mut self) -> Self {
+        self.dashed = true;
+        self
+    }
+}
+
+/// Text pinned to a data point, offset by `dx`/`dy` pixels.
+#[derive(Clone, Debug)]
+pub struct PointLabel {
+    pub x: f64,
+    pub y: f64,
+    pub dx: f64,
+    pub dy: f64,
+    pub text: String,
+    pub size: f64,
+    pub bold: bool,
+    /// White outline so the text stays readable over dark fills.
+    pub halo: bool,
+}
+
+/// Filled rectangle in data coordinates (heatmap cell) with an optional centered label.
+#[derive(Clone, Debug)]
+pub struct Cell {
+    pub x0: f64,
+    pub x1: f64,
+    pub y0: f64,
+    pub y1: f64,
+    pub color: String,
+    pub label: Option<String>,
+}
+
+/// Vertical color scale drawn to the right of a panel; `colors` run from `min` to `max`.
+#[derive(Clone, Debug)]
+pub struct ColorBar {
+    pub label: String,
+    pub min: f64,
+    pub max: f64,
+    pub colors: Vec<String>,
+}
+
+/// Viridis-like ramp: `t = 0` dark purple, `t = 1` yellow. Values outside `[0, 1]` are clamped.
+pub fn colormap(t: f64) -> String {
+    const STOPS: [(f64, f64, f64); 5] = [
+        (68.0, 1.0, 84.0),
+        (59.0, 82.0, 139.0),
+        (33.0, 145.0, 140.0),
+        (94.0, 201.0, 98.0),
+        (253.0, 231.0, 37.0),
+    ];
+    let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+    let pos = t * (STOPS.len() - 1) as f64;
+    let i = (pos.floor() as usize).min(STOPS.len() - 2);
+    let f = pos - i as f64;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    let mix = |u: f64, v: f64| (u + (v - u) * f).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+/// Black or white, whichever reads better on `#rrggbb`.
+pub fn contrast_text(color: &str) -> &'static str {
+    let hex = color.trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+            .unwrap_or(255) as f64
+    };
+    let luma = 0.299 * channel(0) + 0.587 * channel(2) + 0.114 * channel(4);
+    if luma > 140.0 {
+        "#000"
+    } else {
+        "#fff"
+    }
+}
+
+/// Shaded vertical strip between two x values, drawn behind everything else.
+#[derive(Clone, Debug)]
+pub struct Band {
+    pub min: f64,
+    pub max: f64,
+    pub color: String,
+    pub label: Option<String>,
+}
+
+/// Pixel rectangle of a panel's plot area.
+#[derive(Clone, Copy, Debug)]
+pub struct Rect {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+}
+
+impl Rect {
+    pub fn new(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+        Self { x0, y0, x1, y1 }
+    }
+
+    /// Splits `self` into `rows` x `cols` cells separated by the given gaps, row-major.
+    pub fn grid(self, rows: usize, cols: usize, gap_x: f64, gap_y: f64) -> Vec<Rect> {
+        let w = (self.x1 - self.x0 - gap_x * (cols as f64 - 1.0)) / cols as f64;
+        let h = (self.y1 - self.y0 - gap_y * (rows as f64 - 1.0)) / rows as f64;
+        let mut out = Vec::with_capacity(rows * cols);
+        for r in 0..rows {
+            for c in 0..cols {
+                let x0 = self.x0 + c as f64 * (w + gap_x);
+                let y0 = self.y0 + r as f64 * (h + gap_y);
+                out.push(Rect::new(x0, y0, x0 + w, y0 + h));
+            }
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Panel {
+    pub rect: Rect,
+    pub caption: Option<String>,
+    pub x: Axis,
+    pub y: Axis,
+    pub series: Vec<Series>,
+    pub labels: Vec<PointLabel>,
+    pub bands: Vec<Band>,
+    pub cells: Vec<Cell>,
+    pub colorbar: Option<ColorBar>,
+}
+
+impl Panel {
+    pub fn new(rect: Rect, x: Axis, y: Axis) -> Self {
+        Self {
+            rect,
+            caption: None,
+            x,
+            y,
+            series: Vec::new(),
+            labels: Vec::new(),
+            bands: Vec::new(),
+            cells: Vec::new(),
+            colorbar: None,
+        }
+    }
+
+    pub fn caption(mut self, text: impl Into<String>) -> Self {
+        self.caption = Some(text.into());
+        self
+    }
+
+    fn to_px(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let r = self.rect;
+        let px = self.x.project(x, r.x0, r.x1);
+        let py = self.y.project(y, r.y1, r.y0);
+        (px.is_finite() && py.is_finite()).then_some((px, py))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum LegendLayout {
+    /// Entries stacked downward from `(x, y)`.
+    Column { x: f64, y: f64, spacing: f64 },
+    /// Entries left to right from `(x, y)`, `spacing` pixels apart.
+    Row { x: f64, y: f64, spacing: f64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct Legend {
+    pub layout: LegendLayout,
+    pub font_size: f64,
+    /// `(label, color)` pairs.
+    pub entries: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Figure {
+    pub width: f64,
+    pub height: f64,
+    pub title: Option<String>,
+    pub title_size: f64,
+    pub subtitle: Option<String>,
+    pub panels: Vec<Panel>,
+    pub legend: Option<Legend>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// Backend-neutral drawing primitive in pixel coordinates (y grows downward).
+#[derive(Clone, Debug)]
+pub enum Shape {
+    Rect {
+        x: f64,
+        y: f64,
+        w: f64,
+        h: f64,
+        fill: String,
+    },
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        color: String,
+        width: f64,
+    },
+    Polyline {
+        points: Vec<(f64, f64)>,
+        color: String,
+        width: f64,
+        dashed: bool,
+    },
+    Circle {
+        cx: f64,
+        cy: f64,
+        r: f64,
+        fill: String,
+        stroke: String,
+        width: f64,
+    },
+    Text(Text),
+}
+
+#[derive(Clone, Debug)]
+pub struct Text {
+    pub x: f64,
+    pub y: f64,
+    pub text: String,
+    pub size: f64,
+    pub anchor: Anchor,
+    pub color: String,
+    pub bold: bool,
+    /// Rotated -90 degrees about `(x, y)` (vertical axis labels).
+    pub vertical: bool,
+    pub halo: bool,
+}
+
+const GRID: &str = "#ececec";
+const AXIS: &str = "#222";
+
+fn text(x: f64, y: f64, s: impl Into<String>, size: f64, anchor: Anchor) -> Text {
+    Text {
+        x,
+        y,
+        text: s.into(),
+        size,
+        anchor,
+        color: "#000".to_string(),
+        bold: false,
+        vertical: false,
+        halo: false,
+    }
+}
+
+impl Figure {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            title: None,
+            title_size: 34.0,
+            subtitle: None,
+            panels: Vec::new(),
+            legend: None,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Lays the figure out into drawing primitives.
+    pub fn shapes(&self) -> Vec<Shape> {
+        let mut out = vec![Shape::Rect {
+            x: 0.0,
+            y: 0.0,
+            w: self.width,
+            h: self.height,
+            fill: "white".to_string(),
+        }];
+
+        if let Some(title) = &self.title {
+            out.push(Shape::Text(text(self.width / 2.0, 42.0, title, self.title_size, Anchor::Middle)));
+        }
+        if let Some(sub) = &self.subtitle {
+            out.push(Shape::Text(Text {
+                color: "#555".to_string(),
+                ..text(self.width / 2.0, 70.0, sub, 14.0, Anchor::Middle)
+            }));
+        }
+
+        for panel in &self.panels {
+            panel_shapes(panel, &mut out);
+        }
+
+        if let Some(legend) = &self.legend {
+            legend_shapes(legend, &mut out);
+        }
+        out
+    }
+
+    pub fn to_svg(&self) -> String {
+        shapes_to_svg(self.width, self.height, &self.shapes())
+    }
+
+    pub fn write_svg(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_svg())
+    }
+
+    /// Rasterized at `dpi` (96 gives one pixel per SVG unit).
+    pub fn to_png(&self, dpi: f64) -> Vec<u8> {
+        crate::raster::shapes_to_png(
+            self.width,
+            self.height,
+            &self.shapes(),
+            dpi / crate::raster::BASE_DPI,
+        )
+    }
+
+    pub fn write_png(&self, path: &Path, dpi: f64) -> io::Result<()> {
+        fs::write(path, self.to_png(dpi))
+    }
+}
+
+fn panel_shapes(panel: &Panel, out: &mut Vec<Shape>) {
+    let Rect { x0, y0, x1, y1 } = panel.rect;
+    let grid_line = |x1: f64, y1: f64, x2: f64, y2: f64| Shape::Line {
+        x1,
+        y1,
+        x2,
+        y2,
+        color: GRID.to_string(),
+        width: 1.0,
+    };
+
+    for band in &panel.bands {
+        let a = panel.x.project(band.min, x0, x1).clamp(x0, x1);
+        let b = panel.x.project(band.max, x0, x1).clamp(x0, x1);
+        if !(a.is_finite() && b.is_finite()) || (b - a).abs() < 0.5 {
+            continue;
+        }
+        out.push(Shape::Rect {
+            x: a.min(b),
+            y: y0,
+            w: (b - a).abs(),
+            h: y1 - y0,
+            fill: band.color.clone(),
+        });
+        if let Some(label) = &band.label {
+            out.push(Shape::Text(Text {
+                color: "#555".to_string(),
+                ..text(a.min(b) + 4.0, y0 + 13.0, label, 11.0, Anchor::Start)
+            }));
+        }
+    }
+
+    let x_ticks = if panel.x.hidden {
+        Vec::new()
+    } else {
+        panel.x.tick_positions()
+    };
+    let y_ticks = if panel.y.hidden {
+        Vec::new()
+    } else {
+        panel.y.tick_positions()
+    };
+    for (v, label) in x_ticks {
+        let x = panel.x.project(v, x0, x1);
+        if !x.is_finite() {
+            continue;
+        }
+        out.push(grid_line(x, y0, x, y1));
+        out.push(Shape::Text(text(x, y1 + 22.0, label, 12.0, Anchor::Middle)));
+    }
+    for (v, label) in y_ticks {
+        let y = panel.y.project(v, y1, y0);
+        if !y.is_finite() {
+            continue;
+        }
+        out.push(grid_line(x0, y, x1, y));
+        out.push(Shape::Text(text(x0 - 8.0, y + 4.0, label, 12.0, Anchor::End)));
+    }
+
+    for cell in &panel.cells {
+        let (Some((ax, ay)), Some((bx, by))) =
+            (panel.to_px(cell.x0, cell.y0), panel.to_px(cell.x1, cell.y1))
+        else {
+            continue;
+        };
+        let (w, h) = ((bx - ax).abs(), (by - ay).abs());
+        out.push(Shape::Rect {
+            x: ax.min(bx),
+            y: ay.min(by),
+            w,
+            h,
+            fill: cell.color.clone(),
+        });
+        if let Some(label) = &cell.label {
+            let size = (h * 0.4).min(w / (label.chars().count() as f64 * 0.62)).min(12.0);
+            if size >= 7.0 {
+                out.push(Shape::Text(Text {
+                    color: contrast_text(&cell.color).to_string(),
+                    ..text((ax + bx) / 2.0, (ay + by) / 2.0 + size * 0.35, label, size, Anchor::Middle)
+                }));
+            }
+        }
+    }
+
+    if let Some(bar) = &panel.colorbar {
+        colorbar_shapes(bar, panel.rect, out);
+    }
+
+    let axis_line = |x1: f64, y1: f64, x2: f64, y2: f64| Shape::Line {
+        x1,
+        y1,
+        x2,
+        y2,
+        color: AXIS.to_string(),
+        width: 2.0,
+    };
+    if !panel.y.hidden {
+        out.push(axis_line(x0, y0, x0, y1));
+        out.push(Shape::Text(Text {
+            vertical: true,
+            ..text(x0 - 66.0, (y0 + y1) / 2.0, &panel.y.label, 16.0, Anchor::Middle)
+        }));
+    }
+    if !panel.x.hidden {
+        out.push(axis_line(x0, y1, x1, y1));
+        out.push(Shape::Text(text((x0 + x1) / 2.0, y1 + 48.0, &panel.x.label, 16.0, Anchor::Middle)));
+    }
+
+    if let Some(caption) = &panel.caption {
+        out.push(Shape::Text(Text {
+            color: "#444".to_string(),
+            ..text(x0 + 8.0, y0 - 12.0, caption, 18.0, Anchor::Start)
+        }));
+    }
+
+    for series in &panel.series {
+        let px: Vec<(f64, f64)> = series
+            .points
+            .iter()
+            .filter_map(|&(x, y)| panel.to_px(x, y))
+            .collect();
+        match series.style {
+            Style::Line | Style::Step => {
+                let points = if series.style == Style::Step {
+                    step_points(&px)
+                } else {
+                    px
+                };
+                out.push(Shape::Polyline {
+                    points,
+                    color: series.color.clone(),
+                    width: series.width,
+                    dashed: series.dashed,
+                });
+            }
+            Style::Ticks { half_height } => {
+                for (x, y) in px {
+                    out.push(Shape::Line {
+                        x1: x,
+                        y1: y - half_height,
+                        x2: x,
+                        y2: y + half_height,
+                        color: series.color.clone(),
+                        width: series.width,
+                    });
+                }
+            }
+            Style::Points { radius, filled } => {
+                for (cx, cy) in px {
+                    out.push(Shape::Circle {
+                        cx,
+                        cy,
+                        r: radius,
+                        fill: if filled {
+                            series.color.clone()
+                        } else {
+                            "white".to_string()
+                        },
+                        stroke: if filled {
+                            AXIS.to_string()
+                        } else {
+                            series.color.clone()
+                        },
+                        width: if filled { 1.5 } else { 2.0 },
+                    });
+                }
+            }
+        }
+    }
+
+    for label in &panel.labels {
+        if let Some((px, py)) = panel.to_px(label.x, label.y) {
+            out.push(Shape::Text(Text {
+                bold: label.bold,
+                halo: label.halo,
+                ..text(px + label.dx, py + label.dy, &label.text, label.size, Anchor::Start)
+            }));
+        }
+    }
+}
+
+fn colorbar_shapes(bar: &ColorBar, rect: Rect, out: &mut Vec<Shape>) {
+    let (bx0, bx1) = (rect.x1 + 24.0, rect.x1 + 44.0);
+    let n = bar.colors.len().max(1);
+    let step = (rect.y1 - rect.y0) / n as f64;
+    for (i, color) in bar.colors.iter().enumerate() {
+        out.push(Shape::Rect {
+            x: bx0,
+            y: rect.y1 - (i + 1) as f64 * step,
+            w: bx1 - bx0,
+            h: step + 0.5,
+            fill: color.clone(),
+        });
+    }
+    let axis = Axis::linear(bar.label.clone(), bar.min, bar.max).ticks(Ticks::Auto(6));
+    for (v, label) in axis.tick_positions() {
+        let y = axis.project(v, rect.y1, rect.y0);
+        out.push(Shape::Line {
+            x1: bx1,
+            y1: y,
+            x2: bx1 + 5.0,
+            y2: y,
+            color: AXIS.to_string(),
+            width: 1.0,
+        });
+        out.push(Shape::Text(text(bx1 + 8.0, y + 4.0, label, 12.0, Anchor::Start)));
+    }
+    out.push(Shape::Text(Text {
+        vertical: true,
+        ..text(bx1 + 66.0, (rect.y0 + rect.y1) / 2.0, &bar.label, 14.0, Anchor::Middle)
+    }));
+}
+
+/// Expands points into a staircase: hold `y` until the next `x`, then jump.
+pub fn step_points(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut out = Vec::with_capacity(points.len() * 2);
+    if let Some(&first) = points.first() {
+        out.push(first);
+    }
+    for pair in points.windows(2) {
+        out.push((pair[1].0, pair[0].1));
+        out.push(pair[1]);
+    }
+    out
+}
+
+fn legend_shapes(legend: &Legend, out: &mut Vec<Shape>) {
+    for (idx, (label, color)) in legend.entries.iter().enumerate() {
+        let (x, y) = match legend.layout {
+            LegendLayout::Column { x, y, spacing } => (x, y + idx as f64 * spacing),
+            LegendLayout::Row { x, y, spacing } => (x + idx as f64 * spacing, y),
+        };
+        out.push(Shape::Line {
+            x1: x,
+            y1: y,
+            x2: x + 40.0,
+            y2: y,
+            color: color.clone(),
+            width: 3.0,
+        });
+        out.push(Shape::Text(text(
+            x + 50.0,
+            y + legend.font_size * 0.35,
+            label,
+            legend.font_size,
+            Anchor::Start,
+        )));
+    }
+}
+
+pub fn shapes_to_svg(width: f64, height: f64, shapes: &[Shape]) -> String {
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+    );
+    for shape in shapes {
+        match shape {
+            Shape::Rect { x, y, w, h, fill } => {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{x:.2}\" y=\"{y:.2}\" width=\"{w:.2}\" height=\"{h:.2}\" fill=\"{fill}\"/>"
+                );
+            }
+            Shape::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+                width,
+            } => {
+                let _ = write!(
+                    svg,
+                    "<line x1=\"{x1:.2}\" y1=\"{y1:.2}\" x2=\"{x2:.2}\" y2=\"{y2:.2}\" stroke=\"{color}\" stroke-width=\"{width}\"/>"
+                );
+            }
+            Shape::Polyline {
+                points,
+                color,
+                width,
+                dashed,
+            } => {
+                let pts = points
+                    .iter()
+                    .map(|(x, y)| format!("{x:.2},{y:.2}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let dash = if *dashed {
+                    " stroke-dasharray=\"6 4\""
+                } else {
+                    ""
+                };
+                let _ = write!(
+                    svg,
+                    "<polyline fill=\"none\" stroke=\"{color}\" stroke-width=\"{width}\"{dash} points=\"{pts}\" />"
+                );
+            }
+            Shape::Circle {
+                cx,
+                cy,
+                r,
+                fill,
+                stroke,
+                width,
+            } => {
+                let _ = write!(
+                    svg,
+                    "<circle cx=\"{cx:.2}\" cy=\"{cy:.2}\" r=\"{r}\" fill=\"{fill}\" stroke=\"{stroke}\" stroke-width=\"{width}\"/>"
+                );
+            }
+            Shape::Text(Text {
+                x,
+                y,
+                text,
+                size,
+                anchor,
+                color,
+                bold,
+                vertical,
+                halo,
+            }) => {
+                let anchor = match anchor {
+                    Anchor::Start => "start",
+                    Anchor::Middle => "middle",
+                    Anchor::End => "end",
+                };
+                let weight = if *bold { " font-weight=\"bold\"" } else { "" };
+                let halo = if *halo {
+                    " stroke=\"white\" stroke-width=\"3\" paint-order=\"stroke\""
+                } else {
+                    ""
+                };
+                let transform = if *vertical {
+                    format!(" transform=\"rotate(-90 {x:.2},{y:.2})\"")
+                } else {
+                    String::new()
+                };
+                let _ = write!(
+                    svg,
+                    "<text x=\"{x:.2}\" y=\"{y:.2}\" font-family=\"sans-serif\" font-size=\"{size}\" fill=\"{color}\" text-anchor=\"{anchor}\"{weight}{halo}{transform}>{}</text>",
+                    escape_xml(text)
+                );
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
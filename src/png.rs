@@ -0,0 +1,225 @@
+//! Minimal PNG encoder: 8-bit RGB, one IDAT chunk, zlib stream with a
+//! single fixed-Huffman deflate block.
+//!
+//! The compressor only looks for repeats at a few fixed distances (previous
+//! byte, previous pixel, pixel above). After row filtering that is where
+//! almost all redundancy in a chart lives, so the files stay small without
+//! a general LZ77 matcher.
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut c = 0xffff_ffffu32;
+    for &b in bytes {
+        c = CRC_TABLE[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8);
+    }
+    c ^ 0xffff_ffff
+}
+
+pub fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65_521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// Encodes `rgb` (row-major, 3 bytes per pixel) as a PNG file.
+pub fn encode_rgb(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+    assert_eq!(
+        rgb.len(),
+        width as usize * height as usize * 3,
+        "pixel buffer does not match image size"
+    );
+    let filtered = filter_rows(width as usize * 3, rgb);
+
+    let mut zlib = vec![0x78, 0x01];
+    zlib.extend(deflate_fixed(&filtered, width as usize * 3 + 1));
+    zlib.extend(adler32(&filtered).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend(width.to_be_bytes());
+    ihdr.extend(height.to_be_bytes());
+    // Bit depth 8, color type 2 (RGB), deflate, adaptive filtering, no interlace.
+    ihdr.extend([8, 2, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut out, b"IHDR", &ihdr);
+    write_chunk(&mut out, b"IDAT", &zlib);
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// Prefixes every row with the filter (None, Sub or Up) that leaves the
+/// smallest sum of absolute residuals, the usual PNG heuristic.
+fn filter_rows(stride: usize, rgb: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(rgb.len() + rgb.len() / stride.max(1));
+    let zero_row = vec![0u8; stride];
+    let mut candidate = [vec![0u8; stride], vec![0u8; stride], vec![0u8; stride]];
+    for (y, row) in rgb.chunks(stride).enumerate() {
+        let above = if y == 0 {
+            &zero_row[..]
+        } else {
+            &rgb[(y - 1) * stride..y * stride]
+        };
+        for i in 0..stride {
+            let left = if i >= 3 { row[i - 3] } else { 0 };
+            candidate[0][i] = row[i];
+            candidate[1][i] = row[i].wrapping_sub(left);
+            candidate[2][i] = row[i].wrapping_sub(above[i]);
+        }
+        let cost = |r: &[u8]| -> u64 { r.iter().map(|&b| (b as i8).unsigned_abs() as u64).sum() };
+        let best = (0..3).min_by_key(|&f| cost(&candidate[f])).unwrap_or(0);
+        out.push(best as u8);
+        out.extend(&candidate[best]);
+    }
+    out
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const MAX_MATCH: usize = 258;
+const MAX_DIST: usize = 32_768;
+
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    /// Appends `len` bits of `value`, least significant first.
+    fn put(&mut self, value: u32, len: u32) {
+        self.acc |= (value as u64) << self.bits;
+        self.bits += len;
+        while self.bits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    /// Huffman codes are defined most significant bit first.
+    fn put_code(&mut self, code: u32, len: u32) {
+        self.put(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+fn put_literal(w: &mut BitWriter, sym: u32) {
+    match sym {
+        0..=143 => w.put_code(0x30 + sym, 8),
+        144..=255 => w.put_code(0x190 + sym - 144, 9),
+        256..=279 => w.put_code(sym - 256, 7),
+        _ => w.put_code(0xc0 + sym - 280, 8),
+    }
+}
+
+fn put_match(w: &mut BitWriter, len: usize, dist: usize) {
+    let li = LENGTH_BASE
+        .iter()
+        .rposition(|&b| b as usize <= len)
+        .unwrap_or(0);
+    put_literal(w, 257 + li as u32);
+    w.put(
+        (len - LENGTH_BASE[li] as usize) as u32,
+        LENGTH_EXTRA[li] as u32,
+    );
+    let di = DIST_BASE
+        .iter()
+        .rposition(|&b| b as usize <= dist)
+        .unwrap_or(0);
+    w.put_code(di as u32, 5);
+    w.put(
+        (dist - DIST_BASE[di] as usize) as u32,
+        DIST_EXTRA[di] as u32,
+    );
+}
+
+/// One final fixed-Huffman block; `row` is the filtered row length, used as
+/// the "pixel above" match distance.
+fn deflate_fixed(data: &[u8], row: usize) -> Vec<u8> {
+    let mut w = BitWriter {
+        out: Vec::with_capacity(data.len() / 8),
+        acc: 0,
+        bits: 0,
+    };
+    w.put(1, 1); // BFINAL
+    w.put(1, 2); // BTYPE = fixed Huffman
+
+    let distances = [1, 3, row];
+    let mut i = 0;
+    while i < data.len() {
+        let limit = MAX_MATCH.min(data.len() - i);
+        let mut best = (0, 0);
+        for &d in distances.iter().filter(|&&d| d <= i && d <= MAX_DIST) {
+            let len = (0..limit)
+                .take_while(|&k| data[i + k] == data[i + k - d])
+                .count();
+            if len > best.0 {
+                best = (len, d);
+            }
+        }
+        if best.0 >= 3 {
+            put_match(&mut w, best.0, best.1);
+            i += best.0;
+        } else {
+            put_literal(&mut w, data[i] as u32);
+            i += 1;
+        }
+    }
+    put_literal(&mut w, 256);
+    w.finish()
+}
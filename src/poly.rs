@@ -0,0 +1,382 @@
+//! Short polynomial approximations evaluated in a reduced format.
+//!
+//! A polynomial's error in a format has three sources, measured here one at
+//! a time on the same inputs: the approximation itself (exact coefficients,
+//! exact arithmetic, against the target function), quantizing the
+//! coefficients into the format (exact arithmetic on the rounded
+//! coefficients), and rounding every operation of the evaluation scheme.
+//! Coefficients can be given or fitted in double-double: interpolation at
+//! Chebyshev nodes, or a Remez exchange for the minimax polynomial.
+
+use crate::arith::Format;
+use crate::double_double::DoubleDouble;
+use crate::elementary::ulp_at;
+use crate::eval::relative_error;
+use std::f64::consts::PI;
+
+/// Monomial coefficients, constant term first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    pub coeffs: Vec<DoubleDouble>,
+}
+
+impl Polynomial {
+    /// Parses comma-separated decimal coefficients, constant term first.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let coeffs = text
+            .split(',')
+            .map(DoubleDouble::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if coeffs.is_empty() {
+            return Err("no coefficients".to_string());
+        }
+        Ok(Self { coeffs })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coeffs.len().saturating_sub(1)
+    }
+
+    /// Horner's rule in double-double.
+    pub fn eval_exact(&self, x: DoubleDouble) -> DoubleDouble {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(DoubleDouble::ZERO, |acc, &c| acc * x + c)
+    }
+
+    /// The coefficients rounded into `format`.
+    pub fn quantize(&self, format: &Format) -> Vec<f64> {
+        self.coeffs
+            .iter()
+            .map(|&c| format.round_reference(c))
+            .collect()
+    }
+}
+
+/// Order of operations for evaluating a polynomial.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    /// `c0 + x(c1 + x(c2 + ...))`: `n` multiplies and `n` adds in a chain.
+    Horner,
+    /// Pairs `c_2i + c_2i+1 x`, then combines pairs with `x^2`, `x^4`, ...;
+    /// shorter dependency chains at the cost of the powers of `x`.
+    Estrin,
+}
+
+impl Scheme {
+    pub fn name(self) -> &'static str {
+        match self {
+            Scheme::Horner => "Horner",
+            Scheme::Estrin => "Estrin",
+        }
+    }
+}
+
+/// Evaluates `coeffs` (constant term first) at `x`, rounding every operation into `format`.
+pub fn evaluate(coeffs: &[f64], x: f64, format: &Format, scheme: Scheme) -> f64 {
+    match scheme {
+        Scheme::Horner => coeffs
+            .iter()
+            .rev()
+            .copied()
+            .reduce(|acc, c| format.add(format.mul(acc, x), c))
+            .unwrap_or(0.0),
+        Scheme::Estrin => {
+            let mut terms = coeffs.to_vec();
+            let mut power = x;
+            while terms.len() > 1 {
+                terms = terms
+                    .chunks(2)
+                    .map(|pair| match pair {
+                        [a, b] => format.add(*a, format.mul(*b, power)),
+                        [a] => *a,
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                if terms.len() > 1 {
+                    power = format.mul(power, power);
+                }
+            }
+            terms.first().copied().unwrap_or(0.0)
+        }
+    }
+}
+
+/// Solves `a x = b` by Gaussian elimination with partial pivoting.
+fn solve(
+    mut a: Vec<Vec<DoubleDouble>>,
+    mut b: Vec<DoubleDouble>,
+) -> Result<Vec<DoubleDouble>, String> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| {
+                a[i][col]
+                    .abs()
+                    .partial_cmp(&a[j][col].abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(col);
+        if a[pivot][col].hi == 0.0 || !a[pivot][col].is_finite() {
+            return Err("singular system; are the fit points distinct?".to_string());
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let factor = a[row][col] / pivot_row[col];
+            for (dst, &src) in a[row][col..].iter_mut().zip(&pivot_row[col..]) {
+                *dst = *dst - factor * src;
+            }
+            let sub = factor * b[col];
+            b[row] = b[row] - sub;
+        }
+    }
+    let mut x = vec![DoubleDouble::ZERO; n];
+    for row in (0..n).rev() {
+        let mut sum = b[row];
+        for k in row + 1..n {
+            sum = sum - a[row][k] * x[k];
+        }
+        x[row] = sum / a[row][row];
+    }
+    Ok(x)
+}
+
+fn powers(x: f64, degree: usize) -> Vec<DoubleDouble> {
+    let x = DoubleDouble::from(x);
+    let mut row = vec![DoubleDouble::ONE];
+    for _ in 0..degree {
+        let last = row[row.len() - 1];
+        row.push(last * x);
+    }
+    row
+}
+
+/// Chebyshev nodes of `[lo, hi]` (`first_kind`), or the Chebyshev extrema, which include both ends.
+fn cosine_points(lo: f64, hi: f64, count: usize, first_kind: bool) -> Vec<f64> {
+    let (mid, half) = ((lo + hi) / 2.0, (hi - lo) / 2.0);
+    (0..count)
+        .map(|i| {
+            let theta = if first_kind {
+                (2 * i + 1) as f64 * PI / (2 * count) as f64
+            } else {
+                i as f64 * PI / (count - 1).max(1) as f64
+            };
+            mid - half * theta.cos()
+        })
+        .collect()
+}
+
+/// Interpolates `f` at the `degree + 1` Chebyshev nodes of `[lo, hi]`.
+pub fn fit_chebyshev(
+    f: &dyn Fn(f64) -> DoubleDouble,
+    lo: f64,
+    hi: f64,
+    degree: usize,
+) -> Result<Polynomial, String> {
+    let nodes = cosine_points(lo, hi, degree + 1, true);
+    let a = nodes.iter().map(|&x| powers(x, degree)).collect();
+    let b = nodes.iter().map(|&x| f(x)).collect();
+    Ok(Polynomial {
+        coeffs: solve(a, b)?,
+    })
+}
+
+/// The minimax polynomial of `f` on `[lo, hi]` by Remez exchange, and its
+/// maximum error on a dense grid. The error is relative when `f` has no zero
+/// on the grid, absolute otherwise.
+pub fn fit_minimax(
+    f: &dyn Fn(f64) -> DoubleDouble,
+    lo: f64,
+    hi: f64,
+    degree: usize,
+) -> Result<(Polynomial, f64), String> {
+    let grid = cosine_points(lo, hi, 4000, false);
+    let targets: Vec<DoubleDouble> = grid.iter().map(|&x| f(x)).collect();
+    let relative = targets.iter().all(|t| t.hi != 0.0 && t.is_finite());
+    let weight = |t: DoubleDouble| if relative { t.abs() } else { DoubleDouble::ONE };
+
+    let mut points = cosine_points(lo, hi, degree + 2, false);
+    let mut best: Option<(Polynomial, f64)> = None;
+    for _ in 0..30 {
+        // p(x_i) - f(x_i) = (-1)^i E w(x_i), with E as the last unknown.
+        let mut a = Vec::with_capacity(degree + 2);
+        let mut b = Vec::with_capacity(degree + 2);
+        for (i, &x) in points.iter().enumerate() {
+            let t = f(x);
+            let mut row = powers(x, degree);
+            let level = if i % 2 == 0 { -weight(t) } else { weight(t) };
+            row.push(level);
+            a.push(row);
+            b.push(t);
+        }
+        let mut solution = solve(a, b)?;
+        let levelled = solution.pop().map_or(0.0, |e| e.hi.abs());
+        let poly = Polynomial { coeffs: solution };
+
+        let errors: Vec<f64> = grid
+            .iter()
+            .zip(&targets)
+            .map(|(&x, &t)| ((poly.eval_exact(DoubleDouble::from(x)) - t) / weight(t)).to_f64())
+            .collect();
+        let max_error = errors.iter().fold(0.0f64, |m, e| m.max(e.abs()));
+        if best.as_ref().is_none_or(|b| max_error < b.1) {
+            best = Some((poly, max_error));
+        }
+        if max_error - levelled <= 1e-6 * max_error {
+            break;
+        }
+
+        // The largest error in every run of equal sign, trimmed to degree + 2
+        // alternating points by dropping the smaller end.
+        let mut extrema: Vec<(f64, f64)> = Vec::new();
+        for (&x, &e) in grid.iter().zip(&errors) {
+            if e == 0.0 {
+                continue;
+            }
+            match extrema.last_mut() {
+                Some(last) if last.1.signum() == e.signum() => {
+                    if e.abs() > last.1.abs() {
+                        *last = (x, e);
+                    }
+                }
+                _ => extrema.push((x, e)),
+            }
+        }
+        if extrema.len() < degree + 2 {
+            break;
+        }
+        while extrema.len() > degree + 2 {
+            if extrema[0].1.abs() < extrema[extrema.len() - 1].1.abs() {
+                extrema.remove(0);
+            } else {
+                extrema.pop();
+            }
+        }
+        points = extrema.iter().map(|p| p.0).collect();
+    }
+    best.ok_or_else(|| "minimax fit failed".to_string())
+}
+
+/// Worst error of one source over the samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ErrorSummary {
+    pub max_rel_error: f64,
+    /// In ULPs of the evaluation format at the reference value.
+    pub max_ulps: f64,
+    /// Input with the largest ULP error.
+    pub worst_x: f64,
+}
+
+impl ErrorSummary {
+    fn record(&mut self, format: &Format, x: f64, value: DoubleDouble, reference: DoubleDouble) {
+        // Neither error is defined at a zero of the reference.
+        if reference.hi == 0.0 {
+            return;
+        }
+        let diff = (value - reference).abs();
+        let rel = (diff / reference.abs()).to_f64();
+        self.max_rel_error = self.max_rel_error.max(rel);
+        if let Some(ulp) = ulp_at(format, reference.hi) {
+            let ulps = (diff / DoubleDouble::from(ulp)).to_f64();
+            if ulps > self.max_ulps {
+                self.max_ulps = ulps;
+                self.worst_x = x;
+            }
+        }
+    }
+}
+
+/// Error of one evaluation scheme.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SchemeErrors {
+    pub scheme: Scheme,
+    /// Against exact arithmetic on the quantized coefficients.
+    pub rounding: ErrorSummary,
+    /// Against the target (or the exact polynomial when there is none).
+    pub total: ErrorSummary,
+}
+
+/// Where a polynomial's error in one format comes from.
+#[derive(Clone, Debug)]
+pub struct Breakdown {
+    pub format: Format,
+    pub coeff_format: Format,
+    pub quantized: Vec<f64>,
+    pub points: usize,
+    /// Exact polynomial against the target; `None` without a target.
+    pub approximation: Option<ErrorSummary>,
+    /// Quantized coefficients against exact ones, both in exact arithmetic.
+    pub quantization: ErrorSummary,
+    pub schemes: Vec<SchemeErrors>,
+}
+
+/// Samples `points` inputs evenly over `interval`, rounded into `format`;
+/// inputs where a reference is zero do not count.
+/// Coefficients are rounded into `coeff_format`, the arithmetic into `format`.
+pub fn analyze(
+    poly: &Polynomial,
+    target: Option<&dyn Fn(f64) -> DoubleDouble>,
+    interval: (f64, f64),
+    format: &Format,
+    coeff_format: &Format,
+    schemes: &[Scheme],
+    points: usize,
+) -> Breakdown {
+    let (lo, hi) = interval;
+    let quantized = poly.quantize(coeff_format);
+    let rounded = Polynomial {
+        coeffs: quantized.iter().map(|&c| DoubleDouble::from(c)).collect(),
+    };
+    let mut approximation = target.map(|_| ErrorSummary::default());
+    let mut quantization = ErrorSummary::default();
+    let mut results: Vec<SchemeErrors> = schemes
+        .iter()
+        .map(|&scheme| SchemeErrors {
+            scheme,
+            rounding: ErrorSummary::default(),
+            total: ErrorSummary::default(),
+        })
+        .collect();
+    for i in 0..points {
+        let t = if points > 1 {
+            i as f64 / (points - 1) as f64
+        } else {
+            0.0
+        };
+        let x = format.round(lo + t * (hi - lo));
+        let dx = DoubleDouble::from(x);
+        let exact = poly.eval_exact(dx);
+        let exact_quantized = rounded.eval_exact(dx);
+        let reference = target.map_or(exact, |f| f(x));
+        if let (Some(summary), Some(_)) = (approximation.as_mut(), target) {
+            summary.record(format, x, exact, reference);
+        }
+        quantization.record(format, x, exact_quantized, exact);
+        for r in &mut results {
+            let value = DoubleDouble::from(evaluate(&quantized, x, format, r.scheme));
+            r.rounding.record(format, x, value, exact_quantized);
+            r.total.record(format, x, value, reference);
+        }
+    }
+    Breakdown {
+        format: format.clone(),
+        coeff_format: coeff_format.clone(),
+        quantized,
+        points,
+        approximation,
+        quantization,
+        schemes: results,
+    }
+}
+
+/// Relative change of each coefficient when quantized.
+pub fn coefficient_errors(poly: &Polynomial, quantized: &[f64]) -> Vec<f64> {
+    poly.coeffs
+        .iter()
+        .zip(quantized)
+        .map(|(&c, &q)| relative_error(q, c))
+        .collect()
+}
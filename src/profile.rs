@@ -0,0 +1,90 @@
+use crate::soft_float::{Flags, SoftFloatSpec, Tininess};
+
+/// A value interval `[min, max)` quantized with its own format.
+#[derive(Clone, Debug)]
+pub struct Region {
+    pub min: f64,
+    pub max: f64,
+    pub spec: SoftFloatSpec,
+}
+
+/// Picks a format by where `x` falls; values outside every region use `fallback`.
+#[derive(Clone, Debug)]
+pub struct PiecewiseQuantizer {
+    pub name: String,
+    pub regions: Vec<Region>,
+    pub fallback: SoftFloatSpec,
+}
+
+impl PiecewiseQuantizer {
+    pub fn quantize(&self, x: f64) -> f64 {
+        self.spec_for(x).quantize(x)
+    }
+
+    pub fn quantize_flags(&self, x: f64, tininess: Tininess) -> (f64, Flags) {
+        self.spec_for(x).quantize_flags(x, tininess)
+    }
+
+    fn spec_for(&self, x: f64) -> &SoftFloatSpec {
+        self.regions
+            .iter()
+            .find(|region| x >= region.min && x < region.max)
+            .map_or(&self.fallback, |region| &region.spec)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Quantizer {
+    Uniform(SoftFloatSpec),
+    Piecewise(PiecewiseQuantizer),
+}
+
+impl Quantizer {
+    pub fn name(&self) -> &str {
+        match self {
+            Quantizer::Uniform(s) => &s.name,
+            Quantizer::Piecewise(p) => &p.name,
+        }
+    }
+
+    pub fn quantize(&self, x: f64) -> f64 {
+        match self {
+            Quantizer::Uniform(s) => s.quantize(x),
+            Quantizer::Piecewise(p) => p.quantize(x),
+        }
+    }
+
+    /// [`SoftFloatSpec::quantize_flags`] in the format `x` falls in.
+    pub fn quantize_flags(&self, x: f64, tininess: Tininess) -> (f64, Flags) {
+        match self {
+            Quantizer::Uniform(s) => s.quantize_flags(x, tininess),
+            Quantizer::Piecewise(p) => p.quantize_flags(x, tininess),
+        }
+    }
+
+    /// Bits needed by the widest member format (region selection is not counted).
+    pub fn total_bits(&self) -> u32 {
+        match self {
+            Quantizer::Uniform(s) => s.total_bits(),
+            Quantizer::Piecewise(p) => p
+                .regions
+                .iter()
+                .map(|r| r.spec.total_bits())
+                .fold(p.fallback.total_bits(), u32::max),
+        }
+    }
+
+    /// Short parameter summary for legends, e.g. `m=10, e=[-14,15]`.
+    pub fn describe(&self) -> String {
+        match self {
+            Quantizer::Uniform(s) => {
+                format!("m={}, e=[{},{}]", s.mantissa_bits, s.min_exp2, s.max_exp2)
+            }
+            Quantizer::Piecewise(p) => format!(
+                "piecewise, {} regions, fallback m={}",
+                p.regions.len(),
+                p.fallback.mantissa_bits
+            ),
+        }
+    }
+}
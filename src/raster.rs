@@ -0,0 +1,479 @@
+//! Rasterizer for [`Shape`] lists, the PNG counterpart of
+//! [`shapes_to_svg`](crate::plot::shapes_to_svg).
+//!
+//! Everything is drawn with per-pixel coverage (anti-aliased) onto a white
+//! RGB canvas. Text uses a built-in monospaced bitmap font, so the output
+//! does not depend on fonts installed on the machine.
+
+use crate::plot::{Anchor, Shape, Text};
+use crate::png;
+
+/// Line segment between two device-pixel points.
+type Segment = ((f64, f64), (f64, f64));
+
+/// SVG user units are CSS pixels, 96 per inch.
+pub const BASE_DPI: f64 = 96.0;
+
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major RGB, 3 bytes per pixel.
+    pub rgb: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            rgb: vec![255; width * height * 3],
+        }
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode_rgb(self.width as u32, self.height as u32, &self.rgb)
+    }
+
+    fn blend(&mut self, x: i64, y: i64, color: [u8; 3], alpha: f64) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 || alpha <= 0.0 {
+            return;
+        }
+        let a = alpha.min(1.0);
+        let i = (y as usize * self.width + x as usize) * 3;
+        for (dst, src) in self.rgb[i..i + 3].iter_mut().zip(color) {
+            *dst = (*dst as f64 * (1.0 - a) + src as f64 * a).round() as u8;
+        }
+    }
+
+    /// Clamped pixel range covering `[lo, hi)` along an axis of length `n`.
+    fn span(lo: f64, hi: f64, n: usize) -> std::ops::Range<i64> {
+        let a = lo.floor().max(0.0) as i64;
+        let b = (hi.ceil().max(0.0) as i64).min(n as i64);
+        a..b.max(a)
+    }
+
+    fn fill_rect(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, color: [u8; 3]) {
+        let overlap =
+            |p: i64, lo: f64, hi: f64| (hi.min(p as f64 + 1.0) - lo.max(p as f64)).max(0.0);
+        for y in Self::span(y0, y1, self.height) {
+            let cy = overlap(y, y0, y1);
+            for x in Self::span(x0, x1, self.width) {
+                self.blend(x, y, color, cy * overlap(x, x0, x1));
+            }
+        }
+    }
+
+    /// Strokes connected segments through a coverage mask, so joints and
+    /// dense curves are not blended more than once.
+    fn stroke_segments(&mut self, segments: &[Segment], width: f64, color: [u8; 3]) {
+        if segments.is_empty() {
+            return;
+        }
+        // Hairlines keep one pixel of footprint and fade instead of vanishing.
+        let alpha = width.min(1.0);
+        let half = width.max(1.0) / 2.0;
+        let pad = half + 1.0;
+        let (mut bx0, mut by0, mut bx1, mut by1) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for &((ax, ay), (bx, by)) in segments {
+            bx0 = bx0.min(ax.min(bx));
+            by0 = by0.min(ay.min(by));
+            bx1 = bx1.max(ax.max(bx));
+            by1 = by1.max(ay.max(by));
+        }
+        let xs = Self::span(bx0 - pad, bx1 + pad, self.width);
+        let ys = Self::span(by0 - pad, by1 + pad, self.height);
+        let w = (xs.end - xs.start) as usize;
+        if w == 0 || ys.is_empty() {
+            return;
+        }
+        let mut mask = vec![0f32; w * (ys.end - ys.start) as usize];
+        for &((ax, ay), (bx, by)) in segments {
+            let sx = Self::span(ax.min(bx) - pad, ax.max(bx) + pad, self.width);
+            let sy = Self::span(ay.min(by) - pad, ay.max(by) + pad, self.height);
+            let (dx, dy) = (bx - ax, by - ay);
+            let len2 = dx * dx + dy * dy;
+            for y in sy {
+                for x in sx.clone() {
+                    let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+                    let t = if len2 > 0.0 {
+                        (((px - ax) * dx + (py - ay) * dy) / len2).clamp(0.0, 1.0)
+                    } else {
+                        0.0
+                    };
+                    let d = ((px - ax - t * dx).powi(2) + (py - ay - t * dy).powi(2)).sqrt();
+                    let cov = (half + 0.5 - d).clamp(0.0, 1.0) as f32;
+                    let m = &mut mask[(y - ys.start) as usize * w + (x - xs.start) as usize];
+                    *m = m.max(cov);
+                }
+            }
+        }
+        for (row, y) in mask.chunks(w).zip(ys) {
+            for (&cov, x) in row.iter().zip(xs.clone()) {
+                self.blend(x, y, color, cov as f64 * alpha);
+            }
+        }
+    }
+
+    fn circle(
+        &mut self,
+        cx: f64,
+        cy: f64,
+        r: f64,
+        fill: Option<[u8; 3]>,
+        stroke: Option<([u8; 3], f64)>,
+    ) {
+        let reach = r + stroke.map_or(0.0, |(_, w)| w / 2.0) + 1.0;
+        for y in Self::span(cy - reach, cy + reach, self.height) {
+            for x in Self::span(cx - reach, cx + reach, self.width) {
+                let d = ((x as f64 + 0.5 - cx).powi(2) + (y as f64 + 0.5 - cy).powi(2)).sqrt();
+                if let Some(color) = fill {
+                    self.blend(x, y, color, r + 0.5 - d);
+                }
+                if let Some((color, width)) = stroke {
+                    self.blend(x, y, color, width.max(1.0) / 2.0 + 0.5 - (d - r).abs());
+                }
+            }
+        }
+    }
+
+    fn text(&mut self, t: &Text, scale: f64) {
+        let glyphs: Vec<Option<[u16; CELL_H]>> = t.text.chars().map(glyph_rows).collect();
+        if glyphs.is_empty() {
+            return;
+        }
+        let f = t.size * scale / EM;
+        let advance = ADVANCE * f;
+        let total = advance * glyphs.len() as f64;
+        let u0 = match t.anchor {
+            Anchor::Start => 0.0,
+            Anchor::Middle => -total / 2.0,
+            Anchor::End => -total,
+        };
+        let (x, y) = (t.x * scale, t.y * scale);
+        let color = parse_color(&t.color).unwrap_or([0, 0, 0]);
+
+        // Bold thickens strokes by one font pixel; the halo is SVG's 3-unit white stroke.
+        let bold = if t.bold { 1.0 } else { 0.0 };
+        let mut passes = Vec::new();
+        if t.halo {
+            passes.push(([255, 255, 255], bold + 1.5 * scale / f));
+        }
+        passes.push((color, bold));
+
+        // Text box in (u along the baseline, v downwards) coordinates, then in pixels.
+        let pad = passes[0].1 * f + 1.0;
+        let (ua, ub) = (u0 - pad, u0 + total + pad);
+        let (va, vb) = (-BASELINE * f - pad, (CELL_H as f64 - BASELINE) * f + pad);
+        let (px0, px1, py0, py1) = if t.vertical {
+            (x + va, x + vb, y - ub, y - ua)
+        } else {
+            (x + ua, x + ub, y + va, y + vb)
+        };
+
+        const SS: usize = 3;
+        for (color, dilate) in passes {
+            for py in Self::span(py0, py1, self.height) {
+                for px in Self::span(px0, px1, self.width) {
+                    let mut hits = 0;
+                    for sy in 0..SS {
+                        for sx in 0..SS {
+                            let dx = px as f64 + (sx as f64 + 0.5) / SS as f64 - x;
+                            let dy = py as f64 + (sy as f64 + 0.5) / SS as f64 - y;
+                            let (u, v) = if t.vertical { (-dy, dx) } else { (dx, dy) };
+                            let pos = (u - u0) / advance;
+                            if pos < -1.0 || pos >= glyphs.len() as f64 + 1.0 {
+                                continue;
+                            }
+                            let gx = (u - u0) / f;
+                            let gy = v / f + BASELINE;
+                            if ink(&glyphs, gx, gy, dilate) {
+                                hits += 1;
+                            }
+                        }
+                    }
+                    self.blend(px, py, color, hits as f64 / (SS * SS) as f64);
+                }
+            }
+        }
+    }
+}
+
+/// Whether font pixel `(gx, gy)` of the run (x measured from the first glyph's
+/// origin) is inked, optionally dilated by `dilate` font pixels.
+fn ink(glyphs: &[Option<[u16; CELL_H]>], gx: f64, gy: f64, dilate: f64) -> bool {
+    let reach = dilate.ceil() as i64;
+    for oy in -reach..=reach {
+        for ox in -reach..=reach {
+            if (ox * ox + oy * oy) as f64 > dilate * dilate + 1e-9 {
+                continue;
+            }
+            let (x, y) = (gx - ox as f64, gy - oy as f64);
+            if y < 0.0 || y >= CELL_H as f64 || x < 0.0 {
+                continue;
+            }
+            let idx = (x / ADVANCE) as usize;
+            let col = (x - idx as f64 * ADVANCE) as usize;
+            if let Some(Some(rows)) = glyphs.get(idx) {
+                if col < CELL_W && rows[y as usize] >> (CELL_W - 1 - col) & 1 == 1 {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// `#rgb`, `#rrggbb`, `white`, `black`; `None` for `none` and anything unknown.
+pub fn parse_color(color: &str) -> Option<[u8; 3]> {
+    match color {
+        "white" => return Some([255, 255, 255]),
+        "black" => return Some([0, 0, 0]),
+        _ => {}
+    }
+    let hex = color.strip_prefix('#')?;
+    let digit = |i: usize| u8::from_str_radix(hex.get(i..i + 1)?, 16).ok();
+    match hex.len() {
+        3 => Some([digit(0)? * 17, digit(1)? * 17, digit(2)? * 17]),
+        6 => {
+            let byte = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            Some([byte(0)?, byte(2)?, byte(4)?])
+        }
+        _ => None,
+    }
+}
+
+/// Splits a polyline into the "on" pieces of SVG's `stroke-dasharray="6 4"`.
+fn dash_segments(points: &[(f64, f64)], on: f64, off: f64) -> Vec<Segment> {
+    let mut out = Vec::new();
+    let mut phase = 0.0;
+    for pair in points.windows(2) {
+        let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+        let len = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt();
+        let mut t = 0.0;
+        while t < len {
+            let in_dash = phase < on;
+            let left = if in_dash {
+                on - phase
+            } else {
+                on + off - phase
+            };
+            let step = left.min(len - t);
+            if in_dash {
+                let p = |s: f64| (ax + (bx - ax) * s / len, ay + (by - ay) * s / len);
+                out.push((p(t), p(t + step)));
+            }
+            t += step;
+            phase = (phase + step) % (on + off);
+        }
+    }
+    out
+}
+
+/// Draws `shapes` (in SVG user units) at `scale` device pixels per unit.
+pub fn render(width: f64, height: f64, shapes: &[Shape], scale: f64) -> Canvas {
+    let mut canvas = Canvas::new(
+        (width * scale).round().max(1.0) as usize,
+        (height * scale).round().max(1.0) as usize,
+    );
+    let pt = |x: f64, y: f64| (x * scale, y * scale);
+    for shape in shapes {
+        match shape {
+            Shape::Rect { x, y, w, h, fill } => {
+                if let Some(color) = parse_color(fill) {
+                    canvas.fill_rect(
+                        x * scale,
+                        y * scale,
+                        (x + w) * scale,
+                        (y + h) * scale,
+                        color,
+                    );
+                }
+            }
+            Shape::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+                width,
+            } => {
+                if let Some(color) = parse_color(color) {
+                    canvas.stroke_segments(&[(pt(*x1, *y1), pt(*x2, *y2))], width * scale, color);
+                }
+            }
+            Shape::Polyline {
+                points,
+                color,
+                width,
+                dashed,
+            } => {
+                let Some(color) = parse_color(color) else {
+                    continue;
+                };
+                let points: Vec<(f64, f64)> = points.iter().map(|&(x, y)| pt(x, y)).collect();
+                let segments = if *dashed {
+                    dash_segments(&points, 6.0 * scale, 4.0 * scale)
+                } else {
+                    points.windows(2).map(|p| (p[0], p[1])).collect()
+                };
+                canvas.stroke_segments(&segments, width * scale, color);
+            }
+            Shape::Circle {
+                cx,
+                cy,
+                r,
+                fill,
+                stroke,
+                width,
+            } => {
+                let stroke = parse_color(stroke).map(|c| (c, width * scale));
+                canvas.circle(cx * scale, cy * scale, r * scale, parse_color(fill), stroke);
+            }
+            Shape::Text(t) => canvas.text(t, scale),
+        }
+    }
+    canvas
+}
+
+pub fn shapes_to_png(width: f64, height: f64, shapes: &[Shape], scale: f64) -> Vec<u8> {
+    render(width, height, shapes, scale).to_png()
+}
+
+const CELL_W: usize = 12;
+const CELL_H: usize = 24;
+/// Font metrics in font pixels: baseline row, em size and horizontal advance.
+const BASELINE: f64 = 19.14;
+const EM: f64 = 20.62;
+const ADVANCE: f64 = 12.41;
+
+/// Glyph bitmap for `c`, `?` for characters the font lacks, `None` for blanks.
+fn glyph_rows(c: char) -> Option<[u16; CELL_H]> {
+    if c.is_whitespace() {
+        return None;
+    }
+    let hex = GLYPHS
+        .iter()
+        .find(|(g, _)| *g == c)
+        .or_else(|| GLYPHS.iter().find(|(g, _)| *g == '?'))?
+        .1;
+    let mut rows = [0u16; CELL_H];
+    for (row, digits) in rows.iter_mut().zip(hex.as_bytes().chunks(3)) {
+        *row = std::str::from_utf8(digits)
+            .ok()
+            .and_then(|d| u16::from_str_radix(d, 16).ok())
+            .unwrap_or(0);
+    }
+    Some(rows)
+}
+
+/// DejaVu Sans Mono (Bitstream Vera license) rasterized to 12x24 cells:
+/// 24 rows of 3 hex digits, most significant bit is the leftmost pixel.
+#[rustfmt::skip]
+const GLYPHS: [(char, &str); 106] = [
+    ('!', "000000000000060060060060060060060060060060000000020060060000000000000000"),
+    ('"', "000000000000198198198198198098000000000000000000000000000000000000000000"),
+    ('#', "0000000000000660660440c43ff7ff088188198ffeffe310330330220000000000000000"),
+    ('$', "0000000000200200201fc3ac3203203201e00fc02e0260260263ae1f8020020020000000"),
+    ('%', "0000000000000007c0440c60c606c03ce0381c071e03302102103301e000000000000000"),
+    ('&', "0000000000000f81f83003001801801c03e1663633c1b61e60e71e3fb040000000000000"),
+    ('\'', "000000000000060060060060060060000000000000000000000000000000000000000000"),
+    ('(', "0000000000000100300200600600600c00c00c00c00c0060060060060030030018000000"),
+    (')', "0000000000800c00c00600600200300300300300300300300300600600400c0080000000"),
+    ('*', "0000000000000200203261f80701f832c020020000000000000000000000000000000000"),
+    ('+', "0000000000000000000000600600600600607ff7fe060060060060000000000000000000"),
+    (',', "0000000000000000000000000000000000000000000000000700700600600c00c0000000"),
+    ('-', "0000000000000000000000000000000000000001f8000000000000000000000000000000"),
+    ('.', "000000000000000000000000000000000000000000000000070070070000000000000000"),
+    ('/', "00000000000000600c00c0180180300300600600600c00c0180180300300600000000000"),
+    ('0', "0000000000000f81fc30c30630670676677676670670630630c19c1f8020000000000000"),
+    ('1', "0000000000000f03f01300300300300300300300300300300301fe1fe000000000000000"),
+    ('2', "0000000000003f83fc00c00e00e00c00c0180300600e01c03803fc7fe000000000000000"),
+    ('3', "0000000000003f83fc00c00e00c00c0f80f800c00600600600e71c3f8040000000000000"),
+    ('4', "00000000000001c03c03c07c0dc09c19c31c31c61c7ff7fe01c01c01c000000000000000"),
+    ('5', "0000000000003fc3fc3003003003f03f821c00e00600600600c71c3f80c0000000000000"),
+    ('6', "0000000000000fc1fc3803003006707fc78e70670670630630638c1f8020000000000000"),
+    ('7', "0000000000007fe3fe00c00c00c0180180300300300600600e00c00c0000000000000000"),
+    ('8', "0000000000001f83fc30e30630630c1f81f830c30660660630638e1fc020000000000000"),
+    ('9', "0000000000001f83fc30c60660660670630e3fe1f600600c00c13c3f8040000000000000"),
+    (':', "000000000000000000000000060070070060000000000000070070070000000000000000"),
+    (';', "0000000000000000000000000600700700600000000000000700700600600c00c0000000"),
+    ('<', "00000000000000000000000000701e0f83c07007801f003e00f000000000000000000000"),
+    ('=', "0000000000000000000000000000007ff7fe0000007ff7fe000000000000000000000000"),
+    ('>', "0000000000000000000000006007c00f003e00701e0783e0700000000000000000000000"),
+    ('?', "0000000000001f83fc00c00e00c01c038070060060060000060060060000000000000000"),
+    ('@', "0000000000000000781fe30260361b47fc43cc3cc3cc3cc347f63d6003001c00fc000000"),
+    ('A', "0000000000000700700f00d009819819818c30c3fc3fe706606607603000000000000000"),
+    ('B', "0000000000003f83fc30e30630630e3fc3fc30e3063063073063fe3fc000000000000000"),
+    ('C', "00000000000007c1fe1803003007007007007007003003003801c60fe010000000000000"),
+    ('D', "0000000000007e07f871c70c70670670670670670670670e70c7f87f0000000000000000"),
+    ('E', "0000000000003fe3fe3003003003003fc3fe3003003003003003fe3fe000000000000000"),
+    ('F', "0000000000003fe3fe3803803803803fc3fe380380380380380380380000000000000000"),
+    ('G', "0000000000000fc1fe38030070060060061e61e6066063063061ce0fc010000000000000"),
+    ('H', "0000000000007067067067067067067fe7fe706706706706706706706000000000000000"),
+    ('I', "0000000000003fc3fc0600600600600600600600600600600603fc3fc000000000000000"),
+    ('J', "0000000000000fc0fc01c01c01c01c01c01c01c01c01c01c4186387f00c0000000000000"),
+    ('K', "00000000000070770e71c7187307607e07e073073871871c70e706707000000000000000"),
+    ('L', "0000000000003003003003003003003003003003003003003003fe3ff000000000000000"),
+    ('M', "00000000000070770f70f78b69b6936d3673663663603603603603603000000000000000"),
+    ('N', "0000000000007067867867866c66c666666662663661661e61e60e60e000000000000000"),
+    ('O', "0000000000000f81fc30c30670660660660660660670630630e39c1f8020000000000000"),
+    ('P', "0000000000003f83fc3063063073063063fe3f8300300300300300300000000000000000"),
+    ('Q', "0000000000000f81fc30c30670660660660660660670630630e39c1f803800c004000000"),
+    ('R', "0000000000007f07fc70c70e70e70e70c7f87f871c70c70e706707703000000000000000"),
+    ('S', "0000000000001fc3fc3006006003003e01f803c00e00600600671c3fc060000000000000"),
+    ('T', "000000000000fff7ff060060060060060060060060060060060060060000000000000000"),
+    ('U', "00000000000070670670670670670670670670670670670630639c1f8020000000000000"),
+    ('V', "00000000000060360660630630630c30c18c1981980980d80f00f0070000000000000000"),
+    ('W', "000000000000c03c03c03c036636736736f66d669669e39e38e38c30c000000000000000"),
+    ('X', "00000000000060730630c18c0d80f80700700f00d819838c30e606603000000000000000"),
+    ('Y', "00000000000060360630e30c19c1d80f0070060060060060060060060000000000000000"),
+    ('Z', "0000000000003ff3ff00600c01c0180300700600c00c01803003fe7ff000000000000000"),
+    ('[', "000000000078078060060060060060060060060060060060060060060060078078000000"),
+    ('\\', "0000000000006003003001801801c00c00c006006003003001801800c00c006000000000"),
+    (']', "0000000000e01f00300300300300300300300300300300300300300300300f00f0000000"),
+    ('^', "0000000000000600f01d818c306602000000000000000000000000000000000000000000"),
+    ('_', "000000000000000000000000000000000000000000000000000000000000000000000fff"),
+    ('`', "0000000000800c0060020000000000000000000000000000000000000000000000000000"),
+    ('a', "0000000000000000000000003f830c0060061fe3fe30660660e31e3f6040000000000000"),
+    ('b', "0000000002003003003003003f838c30630630630630630630638c3f8000000000000000"),
+    ('c', "0000000000000000000000100fe1c61803003003003003001801c60fe010000000000000"),
+    ('d', "0000000000040060060060461f639e30e60e60e60660e60e30e39e1f6040000000000000"),
+    ('e', "0000000000000000000000201f838c3066067fe7fe6006003003861fe020000000000000"),
+    ('f', "00000000000c03e0600600603fe060060060060060060060060060060000000000000000"),
+    ('g', "0000000000000000000000401f639e30e60e60660660e60e30e39e1f600400c00c3f80e0"),
+    ('h', "00000000020030030030030037c38c30c306306306306306306306306000000000000000"),
+    ('i', "0000000000200600200000003e00600600600600600600600600603fe000000000000000"),
+    ('j', "0000000000300300300000001f00300300300300300300300300300300300300703e0380"),
+    ('k', "00000000010030030030030030e31c3383703e03f033831830c306307000000000000000"),
+    ('l', "0000000003c03e00e00e00e00e00e00e00e00e00e00e006006007c03e000000000000000"),
+    ('m', "0000000000000000000000007fe676662663663663663663663663663000000000000000"),
+    ('n', "00000000000000000000000037c38c30c306306306306306306306306000000000000000"),
+    ('o', "0000000000000000000000001f839c30e30660660660630630e39c1f8020000000000000"),
+    ('p', "0000000000000000000000003f838c30630630630630630630638c3f8320300300300200"),
+    ('q', "0000000000000000000000001f639e30e30e60660660630630e39e1f6046006006006004"),
+    ('r', "0000000000000000000000000df0f30c00c00c00c00c00c00c00c00c0000000000000000"),
+    ('s', "0000000000000000000000201fc3843003003c00f801c00c00c30c3f8040000000000000"),
+    ('t', "0000000000000000c00c00c07fe0c00c00c00c00c00c00c00c006007e000000000000000"),
+    ('u', "00000000000000000000000030630630630630630630630630e39e1f6040000000000000"),
+    ('v', "00000000000000000000000060630630430c18c1881980d80f00f0070000000000000000"),
+    ('w', "000000000000000000000000c03c034036636626762d639639c38c38c000000000000000"),
+    ('x', "00000000000000000000000030630c1980f80700600f01d818c30c606000000000000000"),
+    ('y', "00000000000000000000000060630630630c18c1880d80d80f00700700600600c03c0300"),
+    ('z', "0000000000000000000000003fe00c00c0180300600e00c01803003fe000000000000000"),
+    ('{', "00000000000c03c0700600600600600600603c03c006006006006006006003003c00c000"),
+    ('|', "000000000020060060060060060060060060060060060060060060060060060060060060"),
+    ('}', "0000000001003c006006006006006006006003c03c0600600600600600600603c0100000"),
+    ('~', "0000000000000000000000000000000001807ff43e000000000000000000000000000000"),
+    ('±', "0000000000000000000000600600600607ff0600600600600007fe7ff000000000000000"),
+    ('×', "00000000000000000000000020430e19c0f80700f01d838c306000000000000000000000"),
+    ('≈', "0000000000000000000000000001807f343e0003c07ff43e000000000000000000000000"),
+    ('−', "0000000000000000000000000000000000007ff7fe000000000000000000000000000000"),
+    ('≤', "00000000000000000000000000001f0fc7e07003e007e00f0007fe7ff000000000000000"),
+    ('≥', "0000000000000000000000004007803f003e00f07e3e07000007fe7ff000000000000000"),
+    ('·', "000000000000000000000000000000060070070060000000000000000000000000000000"),
+    ('µ', "00000000000000000000000030630630630630630630630630e39e3f7322300300300200"),
+    ('°', "0000000000000f01981081081980f0000000000000000000000000000000000000000000"),
+    ('∞', "00000000000000000000000000038e7de473c61c614f379e10c000000000000000000000"),
+    ('→', "00000000000000000000000000000000800c0067ff00600c008000000000000000000000"),
+    ('…', "000000000000000000000000000000000000000000000000677677677000000000000000"),
+];
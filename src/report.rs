@@ -0,0 +1,147 @@
+//! Shared pieces of the machine-readable (JSON / JSONL) reports.
+//!
+//! Every report carries `schema` and `schema_version`. The version is bumped
+//! whenever a field is removed, renamed or changes meaning; adding fields does
+//! not bump it. Non-finite numbers (e.g. an infinite score) are written as `null`.
+
+use crate::profile::Quantizer;
+use crate::soft_float::SoftFloatSpec;
+use crate::value::{to_json, to_json_pretty, Value};
+
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// Layout of a machine-readable report.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MachineFormat {
+    /// One JSON document.
+    Json,
+    /// One JSON record per line: a `header`, then the other records in order.
+    Jsonl,
+}
+
+impl MachineFormat {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "json" => Ok(MachineFormat::Json),
+            "jsonl" => Ok(MachineFormat::Jsonl),
+            other => Err(format!("invalid output format '{other}', expected json or jsonl")),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MachineFormat::Json => "json",
+            MachineFormat::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        self.name()
+    }
+}
+
+pub fn spec_json(spec: &SoftFloatSpec) -> Value {
+    let mut t = Value::table();
+    t.set("name", Value::Str(spec.name.clone()));
+    t.set("mantissa_bits", Value::Int(spec.mantissa_bits as i64));
+    t.set("min_exp2", Value::Int(spec.min_exp2 as i64));
+    t.set("max_exp2", Value::Int(spec.max_exp2 as i64));
+    t.set("exponent_bits", Value::Int(spec.exponent_bits() as i64));
+    t.set("total_bits", Value::Int(spec.total_bits() as i64));
+    t.set("min_normal", Value::Float(spec.min_normal()));
+    t.set("max_finite", Value::Float(spec.max_finite()));
+    t.set("epsilon_at_one", Value::Float(spec.epsilon_at_one()));
+    t
+}
+
+/// `kind` is `"uniform"` (spec fields inline) or `"piecewise"` (`regions` + `fallback`).
+pub fn quantizer_json(q: &Quantizer) -> Value {
+    match q {
+        Quantizer::Uniform(spec) => {
+            let mut t = Value::table();
+            t.set("name", Value::Str(spec.name.clone()));
+            t.set("kind", Value::Str("uniform".to_string()));
+            if let Value::Table(entries) = spec_json(spec) {
+                for (k, v) in entries.into_iter().skip(1) {
+                    t.set(k, v);
+                }
+            }
+            t
+        }
+        Quantizer::Piecewise(p) => {
+            let mut t = Value::table();
+            t.set("name", Value::Str(p.name.clone()));
+            t.set("kind", Value::Str("piecewise".to_string()));
+            t.set("total_bits", Value::Int(q.total_bits() as i64));
+            t.set(
+                "regions",
+                Value::Array(
+                    p.regions
+                        .iter()
+                        .map(|r| {
+                            let mut rt = Value::table();
+                            rt.set("min", Value::Float(r.min));
+                            rt.set("max", Value::Float(r.max));
+                            rt.set("spec", spec_json(&r.spec));
+                            rt
+                        })
+                        .collect(),
+                ),
+            );
+            t.set("fallback", spec_json(&p.fallback));
+            t
+        }
+    }
+}
+
+/// Starts a report object with the schema fields every report shares.
+pub fn report_header(schema: &str, tool: &str) -> Value {
+    let mut t = Value::table();
+    t.set("schema", Value::Str(schema.to_string()));
+    t.set("schema_version", Value::Int(SCHEMA_VERSION));
+    t.set("tool", Value::Str(tool.to_string()));
+    t
+}
+
+/// Renders `header` plus named record lists.
+///
+/// JSON nests each list under its name in the header object. JSONL writes
+/// the header as `{"record": "header", ...}` and every list item as
+/// `{"record": <singular name>, ...}` on its own line.
+pub fn render(format: MachineFormat, header: Value, sections: Vec<(&str, &str, Vec<Value>)>) -> String {
+    match format {
+        MachineFormat::Json => {
+            let mut doc = header;
+            for (name, _, items) in sections {
+                doc.set(name, Value::Array(items));
+            }
+            to_json_pretty(&doc)
+        }
+        MachineFormat::Jsonl => {
+            let mut out = String::new();
+            let mut head = Value::table();
+            head.set("record", Value::Str("header".to_string()));
+            if let Value::Table(entries) = header {
+                for (k, v) in entries {
+                    head.set(k, v);
+                }
+            }
+            out.push_str(&to_json(&head));
+            out.push('\n');
+            for (_, record, items) in sections {
+                for item in items {
+                    let mut line = Value::table();
+                    line.set("record", Value::Str(record.to_string()));
+                    if let Value::Table(entries) = item {
+                        for (k, v) in entries {
+                            line.set(k, v);
+                        }
+                    }
+                    out.push_str(&to_json(&line));
+                    out.push('\n');
+                }
+            }
+            out
+        }
+    }
+}
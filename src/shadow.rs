@@ -0,0 +1,351 @@
+//! Shadow execution: values in a reduced format that each carry a
+//! double-double shadow, with every operation recorded in a [`Tracer`].
+//!
+//! Create a tracer for a format, take inputs from it with [`Tracer::input`]
+//! and compute with the usual operators (`(big + 1.0) - big`); then print
+//! [`Tracer::table`] or save [`Tracer::csv`]. Each step records the rounding
+//! error of that operation alone, how many leading bits an addition or
+//! subtraction cancelled, and the error accumulated so far (the value against
+//! its shadow).
+
+use crate::arith::Format;
+use crate::cancellation::{analyze_difference, analyze_sum};
+use crate::double_double::DoubleDouble;
+use crate::eval::{correct_bits, relative_error};
+use std::cell::RefCell;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// One recorded input or operation.
+#[derive(Clone, Debug)]
+pub struct TraceStep {
+    /// Name given with [`Tracer::input`] or [`Shadow::named`], otherwise empty.
+    pub label: String,
+    /// `input`, `const`, `+`, `-`, `*`, `/`, `neg` or `sqrt`.
+    pub op: &'static str,
+    /// Indices of the operand steps.
+    pub operands: Vec<usize>,
+    pub value: f64,
+    pub shadow: DoubleDouble,
+    /// Rounding error of this step alone, in ULPs of the format at the result.
+    pub local_ulps: f64,
+    /// Leading bits cancelled by `+`/`-`: larger operand exponent minus result exponent.
+    pub cancelled_bits: i32,
+    /// Error of the value against its shadow, i.e. accumulated so far.
+    pub rel_error: f64,
+    pub correct_bits: f64,
+    /// Correct bits of the least accurate operand minus those of the result.
+    pub bits_lost: f64,
+}
+
+/// Records the steps of a computation in one format.
+#[derive(Debug)]
+pub struct Tracer {
+    pub format: Format,
+    steps: RefCell<Vec<TraceStep>>,
+}
+
+/// A value in the tracer's format together with its high-precision shadow.
+#[derive(Clone, Copy, Debug)]
+pub struct Shadow<'t> {
+    value: f64,
+    shadow: DoubleDouble,
+    id: usize,
+    tracer: &'t Tracer,
+}
+
+impl Tracer {
+    pub fn new(format: Format) -> Self {
+        Self {
+            format,
+            steps: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// A named input: `x` rounded into the format, shadowed by `x` itself.
+    pub fn input(&self, name: &str, x: f64) -> Shadow<'_> {
+        self.input_exact(name, DoubleDouble::from(x))
+    }
+
+    /// Like [`Tracer::input`] with a double-double value, e.g. a parsed decimal.
+    pub fn input_exact(&self, name: &str, x: DoubleDouble) -> Shadow<'_> {
+        self.leaf(name, "input", x)
+    }
+
+    fn leaf(&self, name: &str, op: &'static str, x: DoubleDouble) -> Shadow<'_> {
+        let value = self.format.round_reference(x);
+        let local = DoubleDouble::from(value) - x;
+        let leaf = self.push(op, Vec::new(), value, x, local, 0);
+        if name.is_empty() {
+            leaf
+        } else {
+            leaf.named(name)
+        }
+    }
+
+    pub fn steps(&self) -> Vec<TraceStep> {
+        self.steps.borrow().clone()
+    }
+
+    /// Index of the step that lost the most bits, if any lost some.
+    pub fn worst(&self) -> Option<usize> {
+        self.steps
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.bits_lost > 0.0)
+            .max_by(|a, b| a.1.bits_lost.total_cmp(&b.1.bits_lost))
+            .map(|(i, _)| i)
+    }
+
+    /// The step as an expression over operand labels, e.g. `big + #2`.
+    pub fn describe(&self, index: usize) -> String {
+        let steps = self.steps.borrow();
+        let name = |i: usize| {
+            if steps[i].label.is_empty() {
+                format!("#{}", i + 1)
+            } else {
+                steps[i].label.clone()
+            }
+        };
+        let step = &steps[index];
+        match (step.op, step.operands.as_slice()) {
+            ("input" | "const", _) => format!("{} {:e}", step.op, step.shadow.to_f64()),
+            ("neg", [a]) => format!("-{}", name(*a)),
+            ("sqrt", [a]) => format!("sqrt({})", name(*a)),
+            (op, [a, b]) => format!("{} {op} {}", name(*a), name(*b)),
+            (op, _) => op.to_string(),
+        }
+    }
+
+    /// Aligned text table, with the step that lost the most bits marked.
+    pub fn table(&self) -> String {
+        let steps = self.steps.borrow();
+        let rows: Vec<(String, String)> = (0..steps.len())
+            .map(|i| (steps[i].label.clone(), self.describe(i)))
+            .collect();
+        let label_w = rows.iter().map(|r| r.0.len()).max().unwrap_or(0).max(4);
+        let expr_w = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(4);
+        let worst = self.worst();
+        let mut out = format!(
+            "{:>3}  {:<label_w$}  {:<expr_w$}  {:>14}  {:>22}  {:>10}  {:>9}  {:>10}  {:>5}\n",
+            "#", "name", "step", "value", "shadow", "local ulp", "cancelled", "rel error", "bits"
+        );
+        for (i, (step, (label, expr))) in steps.iter().zip(rows).enumerate() {
+            let mark = if Some(i) == worst {
+                format!("  <- {:.1} bits lost", step.bits_lost)
+            } else {
+                String::new()
+            };
+            out.push_str(&format!(
+                "{:>3}  {:<label_w$}  {:<expr_w$}  {:>14e}  {:>22}  {:>10.3}  {:>9}  {:>10.3e}  {:>5.1}{mark}\n",
+                i + 1,
+                label,
+                expr,
+                step.value,
+                step.shadow.to_sci_string(16),
+                step.local_ulps,
+                step.cancelled_bits,
+                step.rel_error,
+                step.correct_bits,
+            ));
+        }
+        out
+    }
+
+    /// One row per step: `step,name,op,operands,value,shadow,local_ulps,cancelled_bits,rel_error,correct_bits,bits_lost`.
+    pub fn csv(&self) -> String {
+        let mut out = String::from(
+            "step,name,op,operands,value,shadow,local_ulps,cancelled_bits,rel_error,correct_bits,bits_lost\n",
+        );
+        for (i, s) in self.steps.borrow().iter().enumerate() {
+            let operands: Vec<String> = s.operands.iter().map(|o| (o + 1).to_string()).collect();
+            out.push_str(&format!(
+                "{},{},{},{},{:.17e},{},{:.6},{},{:.6e},{:.3},{:.3}\n",
+                i + 1,
+                s.label,
+                s.op,
+                operands.join(" "),
+                s.value,
+                s.shadow.to_sci_string(32),
+                s.local_ulps,
+                s.cancelled_bits,
+                s.rel_error,
+                s.correct_bits,
+                s.bits_lost
+            ));
+        }
+        out
+    }
+
+    /// Records a step; `local` is the result minus the exact operation on the rounded operands.
+    fn push(
+        &self,
+        op: &'static str,
+        operands: Vec<usize>,
+        value: f64,
+        shadow: DoubleDouble,
+        local: DoubleDouble,
+        cancelled_bits: i32,
+    ) -> Shadow<'_> {
+        let precision = self.format.precision();
+        let rel_error = relative_error(value, shadow);
+        let correct = correct_bits(rel_error, precision);
+        let mut steps = self.steps.borrow_mut();
+        let operand_bits = operands
+            .iter()
+            .map(|&i| steps[i].correct_bits)
+            .fold(precision as f64, f64::min);
+        let local_ulps = match self.format.ulp(value) {
+            Some(ulp) if local.is_finite() => (local.abs() / DoubleDouble::from(ulp)).to_f64(),
+            _ => 0.0,
+        };
+        steps.push(TraceStep {
+            label: String::new(),
+            op,
+            operands,
+            value,
+            shadow,
+            local_ulps,
+            cancelled_bits,
+            rel_error,
+            correct_bits: correct,
+            bits_lost: operand_bits - correct,
+        });
+        Shadow {
+            value,
+            shadow,
+            id: steps.len() - 1,
+            tracer: self,
+        }
+    }
+}
+
+impl<'t> Shadow<'t> {
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn shadow(&self) -> DoubleDouble {
+        self.shadow
+    }
+
+    /// Index of the step that produced this value.
+    pub fn step(&self) -> usize {
+        self.id
+    }
+
+    /// Labels the step that produced this value in the trace.
+    pub fn named(self, name: &str) -> Self {
+        self.tracer.steps.borrow_mut()[self.id].label = name.to_string();
+        self
+    }
+
+    pub fn sqrt(self) -> Self {
+        let t = self.tracer;
+        let value = t.format.sqrt(self.value);
+        let local = DoubleDouble::from(value) - DoubleDouble::from(self.value).sqrt();
+        t.push("sqrt", vec![self.id], value, self.shadow.sqrt(), local, 0)
+    }
+
+    fn constant(self, x: f64) -> Self {
+        self.tracer.leaf("", "const", DoubleDouble::from(x))
+    }
+
+    fn binary(self, rhs: Self, op: &'static str) -> Self {
+        let t = self.tracer;
+        assert!(
+            std::ptr::eq(t, rhs.tracer),
+            "shadow values from different tracers"
+        );
+        let (a, b) = (self.value, rhs.value);
+        // Sums and products of two f64s are exact in double-double.
+        let (da, db) = (DoubleDouble::from(a), DoubleDouble::from(b));
+        let (value, exact, shadow) = match op {
+            "+" => (t.format.add(a, b), da + db, self.shadow + rhs.shadow),
+            "-" => (t.format.sub(a, b), da - db, self.shadow - rhs.shadow),
+            "*" => (t.format.mul(a, b), da * db, self.shadow * rhs.shadow),
+            _ => (t.format.div(a, b), da / db, self.shadow / rhs.shadow),
+        };
+        let cancelled = match op {
+            "+" => analyze_sum(&t.format, a, b).bits_lost as i32,
+            "-" => analyze_difference(&t.format, a, b).bits_lost as i32,
+            _ => 0,
+        };
+        let local = DoubleDouble::from(value) - exact;
+        t.push(op, vec![self.id, rhs.id], value, shadow, local, cancelled)
+    }
+}
+
+impl<'t> Add for Shadow<'t> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.binary(rhs, "+")
+    }
+}
+
+impl<'t> Sub for Shadow<'t> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.binary(rhs, "-")
+    }
+}
+
+impl<'t> Mul for Shadow<'t> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.binary(rhs, "*")
+    }
+}
+
+impl<'t> Div for Shadow<'t> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        self.binary(rhs, "/")
+    }
+}
+
+impl<'t> Add<f64> for Shadow<'t> {
+    type Output = Self;
+
+    fn add(self, rhs: f64) -> Self {
+        self.binary(self.constant(rhs), "+")
+    }
+}
+
+impl<'t> Sub<f64> for Shadow<'t> {
+    type Output = Self;
+
+    fn sub(self, rhs: f64) -> Self {
+        self.binary(self.constant(rhs), "-")
+    }
+}
+
+impl<'t> Mul<f64> for Shadow<'t> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        self.binary(self.constant(rhs), "*")
+    }
+}
+
+impl<'t> Div<f64> for Shadow<'t> {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self {
+        self.binary(self.constant(rhs), "/")
+    }
+}
+
+impl<'t> Neg for Shadow<'t> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        let zero = DoubleDouble::ZERO;
+        self.tracer
+            .push("neg", vec![self.id], -self.value, -self.shadow, zero, 0)
+    }
+}
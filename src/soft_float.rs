@@ -16,6 +16,36 @@ impl SoftFloatSpec {
         }
     }
 
+    /// Rejects formats the model cannot represent: values are kept in an
+    /// `f64`, so the mantissa and exponent range must fit in one.
+    pub fn check(&self) -> Result<(), String> {
+        if self.mantissa_bits > 52 {
+            return Err(format!(
+                "{} mantissa bits exceed the 52 of an f64",
+                self.mantissa_bits
+            ));
+        }
+        if self.min_exp2 > self.max_exp2 {
+            return Err(format!(
+                "min_exp2 {} is above max_exp2 {}",
+                self.min_exp2, self.max_exp2
+            ));
+        }
+        if self.min_exp2 < -1022 || self.max_exp2 > 1023 {
+            return Err(format!(
+                "exponents {}..={} exceed the f64 range -1022..=1023",
+                self.min_exp2, self.max_exp2
+            ));
+        }
+        Ok(())
+    }
+
+    /// IEEE-style layout from field widths: bias `2^(w-1) - 1`, exponents `[1 - bias, bias]`.
+    pub fn ieee_like(name: impl Into<String>, mantissa_bits: u32, exponent_bits: u32) -> Self {
+        let bias = (1i64 << (exponent_bits.clamp(2, 30) - 1)) - 1;
+        Self::new(name, mantissa_bits, (1 - bias) as i32, bias as i32)
+    }
+
     pub fn quantize(&self, x: f64) -> f64 {
         if x.is_nan() {
             return f64::NAN;
@@ -30,7 +60,7 @@ impl SoftFloatSpec {
             return sign * f64::INFINITY;
         }
 
-        let mut exp2 = ax.log2().floor() as i32;
+        let mut exp2 = exponent_of(ax);
         if exp2 < self.min_exp2 {
             return sign * 0.0;
         }
@@ -56,6 +86,30 @@ impl SoftFloatSpec {
         sign * (1.0 + frac_q) * 2f64.powi(exp2)
     }
 
+    /// [`Self::quantize`] with the exception flags it raises: inexact,
+    /// underflow (a tiny result that is also inexact) and overflow. The value
+    /// is the same for both [`Tininess`] modes; with
+    /// [`Tininess::AfterRounding`], values just below the smallest normal
+    /// that would round up to it are flushed without raising underflow.
+    pub fn quantize_flags(&self, x: f64, tininess: Tininess) -> (f64, Flags) {
+        let q = self.quantize(x);
+        if !x.is_finite() || q == x {
+            return (q, Flags::NONE);
+        }
+        if q.is_infinite() {
+            return (q, Flags::OVERFLOW | Flags::INEXACT);
+        }
+        let min_normal = self.min_normal();
+        if x.abs() < min_normal {
+            let halfway = min_normal - 2f64.powi(self.min_exp2 - self.mantissa_bits as i32 - 2);
+            if tininess == Tininess::AfterRounding && x.abs() >= halfway {
+                return (q, Flags::INEXACT);
+            }
+            return (q, Flags::UNDERFLOW | Flags::INEXACT);
+        }
+        (q, Flags::INEXACT)
+    }
+
     pub fn epsilon_at_one(&self) -> f64 {
         2f64.powi(-(self.mantissa_bits as i32))
     }
@@ -68,18 +122,212 @@ impl SoftFloatSpec {
         (2.0 - self.epsilon_at_one()) * 2f64.powi(self.max_exp2)
     }
 
+    /// Width of an IEEE-style exponent field covering `[min_exp2, max_exp2]`,
+    /// with the all-zeros and all-ones codes reserved for zero and inf/NaN.
+    pub fn exponent_bits(&self) -> u32 {
+        let codes = (self.max_exp2 as i64 - self.min_exp2 as i64 + 1).max(1) as u64 + 2;
+        64 - (codes - 1).leading_zeros()
+    }
+
+    /// Storage size: sign bit + exponent field + stored mantissa bits.
+    pub fn total_bits(&self) -> u32 {
+        1 + self.exponent_bits() + self.mantissa_bits
+    }
+
+    /// Number of positive finite values (no subnormals in this model).
+    pub fn positive_count(&self) -> u64 {
+        let binades = (self.max_exp2 as i64 - self.min_exp2 as i64 + 1).max(0) as u64;
+        binades.saturating_mul(1u64.checked_shl(self.mantissa_bits).unwrap_or(u64::MAX))
+    }
+
+    /// Every positive finite value in increasing order, or `None` above `limit` values.
+    pub fn positive_values(&self, limit: u64) -> Option<Vec<f64>> {
+        let count = self.positive_count();
+        if count > limit {
+            return None;
+        }
+        let steps = 1u64 << self.mantissa_bits;
+        let mut out = Vec::with_capacity(count as usize);
+        for exp2 in self.min_exp2..=self.max_exp2 {
+            let base = 2f64.powi(exp2);
+            for i in 0..steps {
+                out.push(base * (1.0 + i as f64 / steps as f64));
+            }
+        }
+        Some(out)
+    }
+
     pub fn ulp_near(&self, x: f64) -> Option<f64> {
         if !(x.is_finite() && x > 0.0) {
             return None;
         }
 
-        let exp2 = x.log2().floor() as i32;
+        let exp2 = exponent_of(x);
         if exp2 < self.min_exp2 || exp2 > self.max_exp2 {
             return None;
         }
 
         Some(2f64.powi(exp2 - self.mantissa_bits as i32))
     }
+
+    /// Bit pattern of `quantize(x)`: sign, exponent field as in
+    /// [`Self::exponent_bits`] (bias `1 - min_exp2`; all zeros for zero, all
+    /// ones for infinity and NaN), then the stored mantissa. NaN is the quiet
+    /// pattern with only the top mantissa bit set. Needs `mantissa_bits <= 52`.
+    pub fn encode(&self, x: f64) -> u64 {
+        let q = self.quantize(x);
+        let m = self.mantissa_bits;
+        let all_ones = (1u64 << self.exponent_bits()) - 1;
+        let (field, frac) = if q.is_nan() {
+            (all_ones, if m > 0 { 1u64 << (m - 1) } else { 0 })
+        } else if q.is_infinite() {
+            (all_ones, 0)
+        } else if q == 0.0 {
+            (0, 0)
+        } else {
+            let e = exponent_of(q.abs());
+            let frac = (q.to_bits() & ((1u64 << 52) - 1)) >> (52 - m);
+            ((e - self.min_exp2 + 1) as u64, frac)
+        };
+        let sign = u64::from(q.is_sign_negative() && !q.is_nan());
+        sign << (self.exponent_bits() + m) | field << m | frac
+    }
+
+    /// The value of a bit pattern laid out as in [`Self::encode`]. Exponent
+    /// field 0 with a nonzero mantissa reads as an IEEE subnormal, which this
+    /// model never produces. `None` for exponent codes the layout leaves unused.
+    pub fn decode(&self, bits: u64) -> Option<f64> {
+        let m = self.mantissa_bits;
+        let w = self.exponent_bits();
+        if bits >> (w + m) > 1 {
+            return None;
+        }
+        let all_ones = (1u64 << w) - 1;
+        let field = (bits >> m) & all_ones;
+        let frac = bits & ((1u64 << m) - 1);
+        let steps = 2f64.powi(m as i32);
+        let magnitude = if field == all_ones {
+            if frac == 0 {
+                f64::INFINITY
+            } else {
+                return Some(f64::NAN);
+            }
+        } else if field == 0 {
+            frac as f64 / steps * 2f64.powi(self.min_exp2)
+        } else if field as i64 > self.max_exp2 as i64 - self.min_exp2 as i64 + 1 {
+            return None;
+        } else {
+            (1.0 + frac as f64 / steps) * 2f64.powi(self.min_exp2 + field as i32 - 1)
+        };
+        Some(if bits >> (w + m) == 1 {
+            -magnitude
+        } else {
+            magnitude
+        })
+    }
+}
+
+/// IEEE 754 exception flags, with the bit values Berkeley SoftFloat and
+/// TestFloat use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flags(pub u8);
+
+impl Flags {
+    pub const NONE: Flags = Flags(0);
+    pub const INEXACT: Flags = Flags(1);
+    pub const UNDERFLOW: Flags = Flags(2);
+    pub const OVERFLOW: Flags = Flags(4);
+    pub const DIV_BY_ZERO: Flags = Flags(8);
+    pub const INVALID: Flags = Flags(16);
+
+    pub const ALL: [(Flags, &'static str); 5] = [
+        (Flags::INEXACT, "inexact"),
+        (Flags::UNDERFLOW, "underflow"),
+        (Flags::OVERFLOW, "overflow"),
+        (Flags::DIV_BY_ZERO, "div-by-zero"),
+        (Flags::INVALID, "invalid"),
+    ];
+
+    pub fn contains(self, other: Flags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Set flags joined by `|`, or `none`.
+    pub fn names(self) -> String {
+        let names: Vec<&str> = Flags::ALL
+            .iter()
+            .filter(|(f, _)| self.contains(*f))
+            .map(|(_, name)| *name)
+            .collect();
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join("|")
+        }
+    }
+}
+
+impl std::ops::BitOr for Flags {
+    type Output = Flags;
+
+    fn bitor(self, rhs: Flags) -> Flags {
+        Flags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for Flags {
+    fn bitor_assign(&mut self, rhs: Flags) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// When a non-zero result counts as tiny, for the underflow flag (IEEE 754
+/// leaves the choice to the implementation).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tininess {
+    /// The exact result is below the smallest normal, as in ARM hardware and
+    /// the flushing of this model.
+    BeforeRounding,
+    /// The result rounded with an unbounded exponent is below the smallest
+    /// normal, as in x86 and RISC-V hardware.
+    AfterRounding,
+}
+
+impl Tininess {
+    pub fn name(self) -> &'static str {
+        match self {
+            Tininess::BeforeRounding => "before",
+            Tininess::AfterRounding => "after",
+        }
+    }
+
+    /// `before` or `after` (rounding).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "before" => Ok(Tininess::BeforeRounding),
+            "after" => Ok(Tininess::AfterRounding),
+            other => Err(format!(
+                "invalid tininess '{other}', expected before or after"
+            )),
+        }
+    }
+}
+
+/// `floor(log2(|x|))` read from the bits of a finite non-zero `x`, so values
+/// just below a power of two are not pushed into the next binade by `log2` rounding.
+pub fn exponent_of(x: f64) -> i32 {
+    let bits = x.to_bits();
+    let field = ((bits >> 52) & 0x7ff) as i32;
+    if field == 0 {
+        let frac = bits & ((1u64 << 52) - 1);
+        -1011 - frac.leading_zeros() as i32
+    } else {
+        field - 1023
+    }
 }
 
 pub fn default_presets() -> Vec<SoftFloatSpec> {
@@ -91,3 +339,104 @@ pub fn default_presets() -> Vec<SoftFloatSpec> {
         SoftFloatSpec::new("f64_like", 52, -1022, 1023),
     ]
 }
+
+/// 8-bit formats in the style of OCP FP8 (E4M3, E5M2), without their NaN/subnormal encodings.
+/// Not part of the default sweep; reachable by name through [`preset`].
+pub fn fp8_presets() -> Vec<SoftFloatSpec> {
+    vec![
+        SoftFloatSpec::new("fp8_e4m3", 3, -6, 8),
+        SoftFloatSpec::new("fp8_e5m2", 2, -14, 15),
+    ]
+}
+
+pub fn preset(name: &str) -> Option<SoftFloatSpec> {
+    default_presets()
+        .into_iter()
+        .chain(fp8_presets())
+        .find(|p| p.name == name)
+}
+
+pub fn preset_names() -> Vec<String> {
+    default_presets()
+        .into_iter()
+        .chain(fp8_presets())
+        .map(|p| p.name)
+        .collect()
+}
+
+/// Parses `name,mantissa_bits,min_exp2,max_exp2` as given to `--format`.
+pub fn parse_format(spec: &str) -> Result<SoftFloatSpec, String> {
+    let parts: Vec<&str> = spec.split(',').collect();
+    if parts.len() != 4 {
+        return Err(format!(
+            "invalid --format '{spec}', expected name,mantissa_bits,min_exp2,max_exp2"
+        ));
+    }
+
+    let name = parts[0].trim();
+    let mantissa_bits = parts[1]
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| format!("invalid mantissa_bits in '{spec}': {e}"))?;
+    let min_exp2 = parts[2]
+        .trim()
+        .parse::<i32>()
+        .map_err(|e| format!("invalid min_exp2 in '{spec}': {e}"))?;
+    let max_exp2 = parts[3]
+        .trim()
+        .parse::<i32>()
+        .map_err(|e| format!("invalid max_exp2 in '{spec}': {e}"))?;
+
+    let format = SoftFloatSpec::new(name, mantissa_bits, min_exp2, max_exp2);
+    format
+        .check()
+        .map_err(|e| format!("invalid --format '{spec}': {e}"))?;
+    Ok(format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_format, preset, Flags, Tininess};
+
+    #[test]
+    fn tininess_decides_the_underflow_flag_only() {
+        let spec = preset("tiny8").unwrap();
+        let min_normal = spec.min_normal();
+        // Halfway between the largest value below 2^-6 (at full precision) and 2^-6.
+        let halfway = min_normal - 2f64.powi(spec.min_exp2 - spec.mantissa_bits as i32 - 2);
+        for (x, after) in [
+            (halfway, Flags::INEXACT),
+            (halfway.next_down(), Flags::UNDERFLOW | Flags::INEXACT),
+            (min_normal / 2.0, Flags::UNDERFLOW | Flags::INEXACT),
+        ] {
+            let (q, flags) = spec.quantize_flags(x, Tininess::BeforeRounding);
+            assert_eq!((q, flags), (0.0, Flags::UNDERFLOW | Flags::INEXACT), "{x}");
+            assert_eq!(
+                spec.quantize_flags(x, Tininess::AfterRounding),
+                (0.0, after),
+                "{x}"
+            );
+        }
+        assert_eq!(
+            spec.quantize_flags(1e9, Tininess::AfterRounding),
+            (f64::INFINITY, Flags::OVERFLOW | Flags::INEXACT)
+        );
+        assert_eq!(
+            spec.quantize_flags(1.125, Tininess::BeforeRounding),
+            (1.125, Flags::NONE)
+        );
+        assert_eq!(
+            spec.quantize_flags(1.1, Tininess::BeforeRounding),
+            (1.125, Flags::INEXACT)
+        );
+    }
+
+    #[test]
+    fn parse_format_rejects_formats_beyond_f64() {
+        assert!(parse_format("x,60,-2,2").is_err());
+        assert!(parse_format("x,4,3,-3").is_err());
+        assert!(parse_format("x,4,-1100,10").is_err());
+        let spec = parse_format("x,52,-1022,1023").unwrap();
+        assert_eq!(spec.max_finite(), f64::MAX);
+    }
+}
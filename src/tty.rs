@@ -0,0 +1,332 @@
+//! Terminal rendering of plot panels, for when an SVG can't be opened
+//! (for example over SSH).
+//!
+//! Series are drawn on a braille grid (2x4 dots per character cell) or,
+//! with [`TtyOptions::ascii`], on plain ASCII. Each cell takes the color of
+//! the last series that touched it.
+
+use crate::plot::{step_points, Panel, Style};
+use crate::raster::parse_color;
+use std::env;
+use std::io::IsTerminal;
+
+#[derive(Clone, Debug)]
+pub struct TtyOptions {
+    /// Total width in characters, labels included.
+    pub width: usize,
+    /// Height of the plot area in character rows.
+    pub height: usize,
+    pub color: bool,
+    pub ascii: bool,
+}
+
+impl TtyOptions {
+    /// Width from `$COLUMNS` (default 100); colors only when stdout is a
+    /// terminal and `$NO_COLOR` is unset.
+    pub fn detect(ascii: bool) -> Self {
+        let width = env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.trim().parse::<usize>().ok())
+            .unwrap_or(100)
+            .clamp(40, 240);
+        Self {
+            width,
+            height: 20,
+            color: std::io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+            ascii,
+        }
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        match parse_color(color).filter(|_| self.color) {
+            Some([r, g, b]) => format!("\x1b[38;2;{r};{g};{b}m{text}\x1b[0m"),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Dot grid: one bit per dot, plus the series index that last drew in each cell.
+struct Grid {
+    cols: usize,
+    rows: usize,
+    dots: Vec<u8>,
+    owner: Vec<Option<usize>>,
+}
+
+impl Grid {
+    fn new(cols: usize, rows: usize) -> Self {
+        Self {
+            cols,
+            rows,
+            dots: vec![0; cols * rows],
+            owner: vec![None; cols * rows],
+        }
+    }
+
+    fn dot_width(&self) -> f64 {
+        (self.cols * 2) as f64
+    }
+
+    fn dot_height(&self) -> f64 {
+        (self.rows * 4) as f64
+    }
+
+    fn set(&mut self, x: f64, y: f64, series: usize) {
+        if !(x >= 0.0 && y >= 0.0 && x < self.dot_width() && y < self.dot_height()) {
+            return;
+        }
+        let (dx, dy) = (x as usize, y as usize);
+        // Braille dot numbering: left column 1,2,3,7 and right column 4,5,6,8.
+        const BITS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+        let cell = dy / 4 * self.cols + dx / 2;
+        self.dots[cell] |= BITS[dx % 2][dy % 4];
+        self.owner[cell] = Some(series);
+    }
+
+    fn line(&mut self, (x0, y0): (f64, f64), (x1, y1): (f64, f64), series: usize) {
+        // Long off-screen segments are sampled more coarsely instead of walking every dot.
+        let limit = 2.0 * (self.dot_width() + self.dot_height());
+        let steps = (x1 - x0)
+            .abs()
+            .max((y1 - y0).abs())
+            .ceil()
+            .clamp(1.0, limit) as usize;
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            self.set(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t, series);
+        }
+    }
+
+    fn glyph(&self, cell: usize, ascii: bool) -> char {
+        let bits = self.dots[cell];
+        if !ascii {
+            return char::from_u32(0x2800 + bits as u32).unwrap_or(' ');
+        }
+        let upper = bits & 0x1b != 0;
+        let lower = bits & 0xe4 != 0;
+        match (upper, lower) {
+            (true, true) => ':',
+            (true, false) => '\'',
+            (false, true) => '.',
+            (false, false) => ' ',
+        }
+    }
+}
+
+/// Places `labels` (column, text) on one line, centered, dropping any that would overlap.
+fn label_line(width: usize, labels: &[(usize, String)]) -> String {
+    let mut line = vec![' '; width];
+    let mut free_from = 0;
+    for (col, text) in labels {
+        let n = text.chars().count();
+        let start = col.saturating_sub(n / 2).min(width.saturating_sub(n));
+        if start < free_from || start + n > width {
+            continue;
+        }
+        for (i, ch) in text.chars().enumerate() {
+            line[start + i] = ch;
+        }
+        free_from = start + n + 1;
+    }
+    line.into_iter().collect::<String>().trim_end().to_string()
+}
+
+/// Draws the panel's series with its axes, tick labels, caption and a legend.
+/// Bands, cells and point labels are left out.
+pub fn render_panel(panel: &Panel, opts: &TtyOptions) -> String {
+    let y_ticks: Vec<(f64, String)> = panel
+        .y
+        .tick_positions()
+        .into_iter()
+        .filter(|(v, _)| panel.y.project(*v, 0.0, 1.0).is_finite())
+        .collect();
+    let label_w = y_ticks
+        .iter()
+        .map(|(_, l)| l.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(12);
+    let cols = opts.width.saturating_sub(label_w + 2).max(10);
+    let rows = opts.height.max(4);
+    let mut grid = Grid::new(cols, rows);
+    let (dw, dh) = (grid.dot_width() - 1.0, grid.dot_height() - 1.0);
+
+    for (idx, series) in panel.series.iter().enumerate() {
+        let to_dot = |&(x, y): &(f64, f64)| {
+            let p = (panel.x.project(x, 0.0, dw), panel.y.project(y, dh, 0.0));
+            (p.0.is_finite() && p.1.is_finite()).then_some(p)
+        };
+        match series.style {
+            Style::Line | Style::Step => {
+                let points = if series.style == Style::Step {
+                    step_points(&series.points)
+                } else {
+                    series.points.clone()
+                };
+                let dots: Vec<Option<(f64, f64)>> = points.iter().map(to_dot).collect();
+                for pair in dots.windows(2) {
+                    if let [Some(a), Some(b)] = pair {
+                        grid.line(*a, *b, idx);
+                    }
+                }
+                if let [Some(only)] = dots.as_slice() {
+                    grid.set(only.0, only.1, idx);
+                }
+            }
+            Style::Points { .. } => {
+                for (x, y) in series.points.iter().filter_map(to_dot) {
+                    grid.set(x, y, idx);
+                }
+            }
+            Style::Ticks { .. } => {
+                for (x, y) in series.points.iter().filter_map(to_dot) {
+                    grid.line((x, y - 2.0), (x, y + 2.0), idx);
+                }
+            }
+        }
+    }
+
+    // Keep every n-th tick so labels end up at least two rows apart, then put
+    // each on the row its value falls in unless a neighbour already took it.
+    let stride = y_ticks.len().div_ceil((rows / 2).max(1)).max(1);
+    let mut row_labels: Vec<Option<&str>> = vec![None; rows];
+    let mut last_row: Option<usize> = None;
+    let mut ticks: Vec<(usize, &str)> = y_ticks
+        .iter()
+        .step_by(stride)
+        .map(|(v, l)| {
+            let row = (panel.y.project(*v, dh, 0.0) / 4.0).floor();
+            (row.clamp(0.0, (rows - 1) as f64) as usize, l.as_str())
+        })
+        .collect();
+    ticks.sort_by_key(|t| t.0);
+    for (row, label) in ticks {
+        if last_row.is_none_or(|r| row >= r + 2) {
+            row_labels[row] = Some(label);
+            last_row = Some(row);
+        }
+    }
+
+    let (v_bar, corner, h_bar) = if opts.ascii {
+        ('|', '+', '-')
+    } else {
+        ('│', '└', '─')
+    };
+    let mut out = String::new();
+    if let Some(caption) = &panel.caption {
+        out.push_str(caption);
+        out.push('\n');
+    }
+    if !panel.y.label.is_empty() {
+        out.push_str(&format!("{:>w$}  {}\n", "", panel.y.label, w = label_w));
+    }
+    for (row, row_label) in row_labels.iter().enumerate() {
+        let label: String = row_label.unwrap_or("").chars().take(label_w).collect();
+        out.push_str(&format!("{label:>label_w$} {v_bar}"));
+        let mut run: Option<usize> = None;
+        let mut text = String::new();
+        for col in 0..cols {
+            let cell = row * cols + col;
+            let owner = grid.owner[cell];
+            if owner != run && !text.is_empty() {
+                let color = run.map_or("", |i| panel.series[i].color.as_str());
+                out.push_str(&opts.paint(color, &text));
+                text.clear();
+            }
+            run = owner;
+            text.push(grid.glyph(cell, opts.ascii));
+        }
+        let color = run.map_or("", |i| panel.series[i].color.as_str());
+        out.push_str(opts.paint(color, &text).trim_end());
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "{:>label_w$} {corner}{}\n",
+        "",
+        h_bar.to_string().repeat(cols)
+    ));
+
+    let x_labels: Vec<(usize, String)> = panel
+        .x
+        .tick_positions()
+        .into_iter()
+        .filter_map(|(v, l)| {
+            let col = panel.x.project(v, 0.0, (cols - 1) as f64);
+            (col.is_finite() && col >= 0.0).then(|| (col.round() as usize + label_w + 2, l))
+        })
+        .collect();
+    out.push_str(&label_line(opts.width, &x_labels));
+    out.push('\n');
+    out.push_str(&label_line(
+        opts.width,
+        &[(label_w + 2 + cols / 2, panel.x.label.clone())],
+    ));
+    out.push('\n');
+
+    let mut seen = Vec::new();
+    let mut legend = Vec::new();
+    for series in &panel.series {
+        if !series.name.is_empty() && !seen.contains(&&series.name) {
+            seen.push(&series.name);
+            let mark = if opts.ascii { "*" } else { "■" };
+            legend.push(format!(
+                "{} {}",
+                opts.paint(&series.color, mark),
+                series.name
+            ));
+        }
+    }
+    if !legend.is_empty() {
+        out.push_str(&legend.join("   "));
+        out.push('\n');
+    }
+    out
+}
+
+/// One row of [`render_bars`].
+#[derive(Clone, Debug)]
+pub struct Bar {
+    pub label: String,
+    pub value: f64,
+    /// Printed after the bar, e.g. the formatted value.
+    pub note: String,
+    pub color: String,
+}
+
+/// Horizontal bars, lengths proportional to `value` above the smallest value
+/// (which still gets one character).
+pub fn render_bars(title: &str, bars: &[Bar], opts: &TtyOptions) -> String {
+    let mut out = format!("{title}\n");
+    let label_w = bars
+        .iter()
+        .map(|b| b.label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let note_w = bars
+        .iter()
+        .map(|b| b.note.chars().count())
+        .max()
+        .unwrap_or(0);
+    let span = opts.width.saturating_sub(label_w + note_w + 3).max(8);
+    let finite = bars.iter().map(|b| b.value).filter(|v| v.is_finite());
+    let lo = finite.clone().fold(f64::INFINITY, f64::min);
+    let hi = finite.fold(f64::NEG_INFINITY, f64::max);
+    let fill = if opts.ascii { "#" } else { "█" };
+    for bar in bars {
+        let len = if !bar.value.is_finite() {
+            span
+        } else if hi > lo {
+            1 + ((bar.value - lo) / (hi - lo) * (span - 1) as f64).round() as usize
+        } else {
+            1
+        };
+        out.push_str(&format!(
+            "{:<label_w$} {}{} {}\n",
+            bar.label,
+            opts.paint(&bar.color, &fill.repeat(len)),
+            " ".repeat(span - len),
+            bar.note
+        ));
+    }
+    out
+}
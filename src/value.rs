@@ -0,0 +1,792 @@
+//! A small tree of config values with readers for a TOML subset and JSON,
+//! plus TOML and JSON writers.
+//!
+//! The supported TOML subset covers what sweep configs need: `key = value`
+//! pairs, `[table]` and `[[array.of.tables]]` headers (dotted paths allowed),
+//! strings, integers, floats, booleans, arrays, inline tables and comments.
+
+use std::fmt::Write as _;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn table() -> Self {
+        Value::Table(Vec::new())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Table(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        match self {
+            Value::Table(entries) => entries
+                .iter_mut()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Appends or replaces `key` in a table; no-op on other variants.
+    pub fn set(&mut self, key: impl Into<String>, value: Value) {
+        let key = key.into();
+        if let Value::Table(entries) = self {
+            if let Some(slot) = entries.iter_mut().find(|(k, _)| *k == key) {
+                slot.1 = value;
+            } else {
+                entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            Value::Float(f) if f.fract() == 0.0 && f.abs() < 9.0e15 => Some(*f as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn entries(&self) -> Option<&[(String, Value)]> {
+        match self {
+            Value::Table(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "boolean",
+            Value::Int(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Str(_) => "string",
+            Value::Array(_) => "array",
+            Value::Table(_) => "table",
+        }
+    }
+}
+
+/// Parses a config file, picking JSON for `.json` paths and TOML otherwise.
+pub fn parse_config_file(path: &str, src: &str) -> Result<Value, String> {
+    if path.to_ascii_lowercase().ends_with(".json") {
+        parse_json(src).map_err(|e| format!("{path}: {e}"))
+    } else {
+        parse_toml(src).map_err(|e| format!("{path}: {e}"))
+    }
+}
+
+struct Cursor<'a> {
+    src: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src: src.as_bytes(),
+            pos: 0,
+            line: 1,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == b'\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn err(&self, msg: impl AsRef<str>) -> String {
+        format!("line {}: {}", self.line, msg.as_ref())
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), String> {
+        match self.bump() {
+            Some(got) if got == c => Ok(()),
+            Some(got) => Err(self.err(format!(
+                "expected '{}', found '{}'",
+                c as char, got as char
+            ))),
+            None => Err(self.err(format!("expected '{}', found end of input", c as char))),
+        }
+    }
+
+    /// Skips spaces and tabs only.
+    fn skip_inline_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips whitespace, newlines and `#` comments (TOML arrays span lines).
+    fn skip_ws_comments(&mut self) {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\r' | b'\n') => {
+                    self.bump();
+                }
+                Some(b'#') => {
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.bump();
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_json_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.bump();
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        let quote = self.bump().ok_or_else(|| self.err("expected string"))?;
+        let mut out = Vec::new();
+        loop {
+            let c = self
+                .bump()
+                .ok_or_else(|| self.err("unterminated string"))?;
+            if c == quote {
+                break;
+            }
+            if c == b'\n' {
+                return Err(self.err("newline in string"));
+            }
+            if c == b'\\' && quote == b'"' {
+                let esc = self.bump().ok_or_else(|| self.err("unterminated escape"))?;
+                let ch = match esc {
+                    b'"' => '"',
+                    b'\\' => '\\',
+                    b'/' => '/',
+                    b'n' => '\n',
+                    b't' => '\t',
+                    b'r' => '\r',
+                    b'b' => '\u{8}',
+                    b'f' => '\u{c}',
+                    b'u' => {
+                        let mut code = 0u32;
+                        for _ in 0..4 {
+                            let h = self.bump().ok_or_else(|| self.err("short \\u escape"))?;
+                            let d = (h as char)
+                                .to_digit(16)
+                                .ok_or_else(|| self.err("bad \\u escape"))?;
+                            code = code * 16 + d;
+                        }
+                        char::from_u32(code).ok_or_else(|| self.err("bad \\u code point"))?
+                    }
+                    other => return Err(self.err(format!("unknown escape '\\{}'", other as char))),
+                };
+                let mut buf = [0u8; 4];
+                out.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            } else {
+                out.push(c);
+            }
+        }
+        String::from_utf8(out).map_err(|_| self.err("invalid UTF-8 in string"))
+    }
+
+    fn parse_number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || matches!(c, b'+' | b'-' | b'.' | b'_') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        let raw: String = String::from_utf8_lossy(&self.src[start..self.pos]).replace('_', "");
+        match raw.as_str() {
+            "inf" | "+inf" => return Ok(Value::Float(f64::INFINITY)),
+            "-inf" => return Ok(Value::Float(f64::NEG_INFINITY)),
+            "nan" | "+nan" | "-nan" => return Ok(Value::Float(f64::NAN)),
+            _ => {}
+        }
+        if let Ok(i) = raw.parse::<i64>() {
+            return Ok(Value::Int(i));
+        }
+        raw.parse::<f64>()
+            .map(Value::Float)
+            .map_err(|_| self.err(format!("invalid number '{raw}'")))
+    }
+}
+
+pub fn parse_json(src: &str) -> Result<Value, String> {
+    let mut cur = Cursor::new(src);
+    cur.skip_json_ws();
+    let value = parse_json_value(&mut cur)?;
+    cur.skip_json_ws();
+    if cur.peek().is_some() {
+        return Err(cur.err("trailing characters after JSON value"));
+    }
+    Ok(value)
+}
+
+fn parse_json_value(cur: &mut Cursor) -> Result<Value, String> {
+    match cur.peek() {
+        Some(b'{') => {
+            cur.bump();
+            let mut table = Value::table();
+            cur.skip_json_ws();
+            if cur.peek() == Some(b'}') {
+                cur.bump();
+                return Ok(table);
+            }
+            loop {
+                cur.skip_json_ws();
+                if cur.peek() != Some(b'"') {
+                    return Err(cur.err("expected object key"));
+                }
+                let key = cur.parse_string()?;
+                cur.skip_json_ws();
+                cur.expect(b':')?;
+                cur.skip_json_ws();
+                let value = parse_json_value(cur)?;
+                table.set(key, value);
+                cur.skip_json_ws();
+                match cur.bump() {
+                    Some(b',') => continue,
+                    Some(b'}') => return Ok(table),
+                    _ => return Err(cur.err("expected ',' or '}' in object")),
+                }
+            }
+        }
+        Some(b'[') => {
+            cur.bump();
+            let mut items = Vec::new();
+            cur.skip_json_ws();
+            if cur.peek() == Some(b']') {
+                cur.bump();
+                return Ok(Value::Array(items));
+            }
+            loop {
+                cur.skip_json_ws();
+                items.push(parse_json_value(cur)?);
+                cur.skip_json_ws();
+                match cur.bump() {
+                    Some(b',') => continue,
+                    Some(b']') => return Ok(Value::Array(items)),
+                    _ => return Err(cur.err("expected ',' or ']' in array")),
+                }
+            }
+        }
+        Some(b'"') => cur.parse_string().map(Value::Str),
+        Some(b't' | b'f' | b'n') => {
+            let start = cur.pos;
+            while cur.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                cur.bump();
+            }
+            match &cur.src[start..cur.pos] {
+                b"true" => Ok(Value::Bool(true)),
+                b"false" => Ok(Value::Bool(false)),
+                // `to_json` writes non-finite floats as `null`.
+                b"null" => Ok(Value::Float(f64::NAN)),
+                _ => Err(cur.err("unexpected literal")),
+            }
+        }
+        Some(c) if c == b'-' || c.is_ascii_digit() => cur.parse_number(),
+        Some(c) => Err(cur.err(format!("unexpected character '{}'", c as char))),
+        None => Err(cur.err("unexpected end of input")),
+    }
+}
+
+pub fn parse_toml(src: &str) -> Result<Value, String> {
+    let mut root = Value::table();
+    // Path of the table that `key = value` lines currently write into.
+    let mut current: Vec<String> = Vec::new();
+    let mut cur = Cursor::new(src);
+
+    loop {
+        cur.skip_ws_comments();
+        let Some(c) = cur.peek() else {
+            break;
+        };
+
+        if c == b'[' {
+            cur.bump();
+            let is_array = cur.peek() == Some(b'[');
+            if is_array {
+                cur.bump();
+            }
+            let path = parse_key_path(&mut cur)?;
+            cur.expect(b']')?;
+            if is_array {
+                cur.expect(b']')?;
+            }
+            let line = cur.line;
+            open_table(&mut root, &path, is_array).map_err(|e| format!("line {line}: {e}"))?;
+            current = path;
+            expect_line_end(&mut cur)?;
+            continue;
+        }
+
+        let key_path = parse_key_path(&mut cur)?;
+        cur.expect(b'=')?;
+        cur.skip_inline_ws();
+        let value = parse_toml_value(&mut cur)?;
+        expect_line_end(&mut cur)?;
+
+        let line = cur.line;
+        let table = resolve_current(&mut root, &current).map_err(|e| format!("line {line}: {e}"))?;
+        let (last, parents) = key_path.split_last().expect("key path is never empty");
+        let mut target = table;
+        for part in parents {
+            if target.get(part).is_none() {
+                target.set(part.clone(), Value::table());
+            }
+            target = target.get_mut(part).expect("just inserted");
+            if !matches!(target, Value::Table(_)) {
+                return Err(format!("line {line}: '{part}' is not a table"));
+            }
+        }
+        if target.get(last).is_some() {
+            return Err(format!("line {line}: duplicate key '{last}'"));
+        }
+        target.set(last.clone(), value);
+    }
+
+    Ok(root)
+}
+
+fn expect_line_end(cur: &mut Cursor) -> Result<(), String> {
+    cur.skip_inline_ws();
+    match cur.peek() {
+        None | Some(b'\n') | Some(b'#') => Ok(()),
+        Some(c) => Err(cur.err(format!("unexpected '{}' after value", c as char))),
+    }
+}
+
+fn parse_key_path(cur: &mut Cursor) -> Result<Vec<String>, String> {
+    let mut parts = Vec::new();
+    loop {
+        cur.skip_inline_ws();
+        let part = match cur.peek() {
+            Some(b'"' | b'\'') => cur.parse_string()?,
+            _ => {
+                let start = cur.pos;
+                while cur
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-')
+                {
+                    cur.bump();
+                }
+                if start == cur.pos {
+                    return Err(cur.err("expected key"));
+                }
+                String::from_utf8_lossy(&cur.src[start..cur.pos]).into_owned()
+            }
+        };
+        parts.push(part);
+        cur.skip_inline_ws();
+        if cur.peek() == Some(b'.') {
+            cur.bump();
+        } else {
+            return Ok(parts);
+        }
+    }
+}
+
+fn parse_toml_value(cur: &mut Cursor) -> Result<Value, String> {
+    match cur.peek() {
+        Some(b'"' | b'\'') => cur.parse_string().map(Value::Str),
+        Some(b'[') => {
+            cur.bump();
+            let mut items = Vec::new();
+            loop {
+                cur.skip_ws_comments();
+                if cur.peek() == Some(b']') {
+                    cur.bump();
+                    return Ok(Value::Array(items));
+                }
+                items.push(parse_toml_value(cur)?);
+                cur.skip_ws_comments();
+                match cur.bump() {
+                    Some(b',') => continue,
+                    Some(b']') => return Ok(Value::Array(items)),
+                    _ => return Err(cur.err("expected ',' or ']' in array")),
+                }
+            }
+        }
+        Some(b'{') => {
+            cur.bump();
+            let mut table = Value::table();
+            loop {
+                cur.skip_inline_ws();
+                if cur.peek() == Some(b'}') {
+                    cur.bump();
+                    return Ok(table);
+                }
+                let key_path = parse_key_path(cur)?;
+                cur.expect(b'=')?;
+                cur.skip_inline_ws();
+                let value = parse_toml_value(cur)?;
+                if key_path.len() != 1 {
+                    return Err(cur.err("dotted keys are not supported in inline tables"));
+                }
+                table.set(key_path[0].clone(), value);
+                cur.skip_inline_ws();
+                match cur.bump() {
+                    Some(b',') => continue,
+                    Some(b'}') => return Ok(table),
+                    _ => return Err(cur.err("expected ',' or '}' in inline table")),
+                }
+            }
+        }
+        Some(b't' | b'f') => {
+            if cur.src[cur.pos..].starts_with(b"true") {
+                cur.pos += 4;
+                Ok(Value::Bool(true))
+            } else if cur.src[cur.pos..].starts_with(b"false") {
+                cur.pos += 5;
+                Ok(Value::Bool(false))
+            } else {
+                Err(cur.err("expected value"))
+            }
+        }
+        Some(c) if c.is_ascii_digit() || matches!(c, b'+' | b'-' | b'i' | b'n') => {
+            cur.parse_number()
+        }
+        Some(c) => Err(cur.err(format!("unexpected '{}' where a value was expected", c as char))),
+        None => Err(cur.err("expected value, found end of input")),
+    }
+}
+
+/// Walks `path` from the root, descending into the last element of arrays of tables.
+fn resolve_current<'a>(root: &'a mut Value, path: &[String]) -> Result<&'a mut Value, String> {
+    let mut node = root;
+    for part in path {
+        node = node
+            .get_mut(part)
+            .ok_or_else(|| format!("missing table '{part}'"))?;
+        if let Value::Array(items) = node {
+            node = items
+                .last_mut()
+                .ok_or_else(|| format!("empty array of tables '{part}'"))?;
+        }
+        if !matches!(node, Value::Table(_)) {
+            return Err(format!("'{part}' is a {}, not a table", node.type_name()));
+        }
+    }
+    Ok(node)
+}
+
+fn open_table(root: &mut Value, path: &[String], is_array: bool) -> Result<(), String> {
+    let (last, parents) = path.split_last().expect("header path is never empty");
+    let parent = {
+        let mut node = root;
+        for part in parents {
+            if node.get(part).is_none() {
+                node.set(part.clone(), Value::table());
+            }
+            node = node.get_mut(part).expect("just inserted");
+            if let Value::Array(items) = node {
+                node = items
+                    .last_mut()
+                    .ok_or_else(|| format!("empty array of tables '{part}'"))?;
+            }
+            if !matches!(node, Value::Table(_)) {
+                return Err(format!("'{part}' is a {}, not a table", node.type_name()));
+            }
+        }
+        node
+    };
+
+    match (parent.get_mut(last), is_array) {
+        (None, false) => parent.set(last.clone(), Value::table()),
+        (None, true) => parent.set(last.clone(), Value::Array(vec![Value::table()])),
+        (Some(Value::Array(items)), true) => items.push(Value::table()),
+        (Some(Value::Table(_)), false) => {
+            return Err(format!("table '{last}' defined more than once"));
+        }
+        (Some(existing), _) => {
+            return Err(format!(
+                "'{last}' is already a {}, cannot redefine it",
+                existing.type_name()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn write_toml_key(out: &mut String, key: &str) {
+    if !key.is_empty()
+        && key
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-')
+    {
+        out.push_str(key);
+    } else {
+        write_quoted(out, key);
+    }
+}
+
+fn write_quoted(out: &mut String, s: &str) {
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_toml_inline(out: &mut String, value: &Value) {
+    match value {
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(i) => {
+            let _ = write!(out, "{i}");
+        }
+        Value::Float(f) => {
+            if f.is_nan() {
+                out.push_str("nan");
+            } else if f.is_infinite() {
+                out.push_str(if *f > 0.0 { "inf" } else { "-inf" });
+            } else if f.fract() == 0.0 && f.abs() < 1e15 {
+                let _ = write!(out, "{f:.1}");
+            } else {
+                let _ = write!(out, "{f}");
+            }
+        }
+        Value::Str(s) => write_quoted(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    out.push_str(", ");
+                }
+                write_toml_inline(out, item);
+            }
+            out.push(']');
+        }
+        Value::Table(entries) => {
+            out.push('{');
+            for (idx, (k, v)) in entries.iter().enumerate() {
+                out.push_str(if idx > 0 { ", " } else { " " });
+                write_toml_key(out, k);
+                out.push_str(" = ");
+                write_toml_inline(out, v);
+            }
+            out.push_str(if entries.is_empty() { "}" } else { " }" });
+        }
+    }
+}
+
+fn is_table_array(value: &Value) -> bool {
+    matches!(value, Value::Array(items) if !items.is_empty() && items.iter().all(|v| matches!(v, Value::Table(_))))
+}
+
+/// Serializes a table as TOML that `parse_toml` reads back to the same tree.
+pub fn to_toml(value: &Value) -> String {
+    let mut out = String::new();
+    write_toml_table(&mut out, value, &mut Vec::new());
+    out
+}
+
+fn write_toml_table(out: &mut String, table: &Value, path: &mut Vec<String>) {
+    let Some(entries) = table.entries() else {
+        return;
+    };
+
+    for (k, v) in entries {
+        if matches!(v, Value::Table(_)) || is_table_array(v) {
+            continue;
+        }
+        write_toml_key(out, k);
+        out.push_str(" = ");
+        write_toml_inline(out, v);
+        out.push('\n');
+    }
+
+    for (k, v) in entries {
+        if let Value::Table(_) = v {
+            path.push(k.clone());
+            out.push_str("\n[");
+            write_toml_path(out, path);
+            out.push_str("]\n");
+            write_toml_table(out, v, path);
+            path.pop();
+        } else if is_table_array(v) {
+            path.push(k.clone());
+            for item in v.as_array().unwrap_or_default() {
+                out.push_str("\n[[");
+                write_toml_path(out, path);
+                out.push_str("]]\n");
+                write_toml_table(out, item, path);
+            }
+            path.pop();
+        }
+    }
+}
+
+fn write_toml_path(out: &mut String, path: &[String]) {
+    for (idx, part) in path.iter().enumerate() {
+        if idx > 0 {
+            out.push('.');
+        }
+        write_toml_key(out, part);
+    }
+}
+
+/// Compact single-line JSON (one record per line for JSONL).
+///
+/// JSON has no NaN or infinity, so non-finite floats are written as `null`,
+/// which [`parse_json`] reads back as NaN.
+pub fn to_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_json(&mut out, value, None, 0);
+    out
+}
+
+/// JSON indented by two spaces per level.
+pub fn to_json_pretty(value: &Value) -> String {
+    let mut out = String::new();
+    write_json(&mut out, value, Some(2), 0);
+    out.push('\n');
+    out
+}
+
+fn write_json(out: &mut String, value: &Value, indent: Option<usize>, depth: usize) {
+    let newline = |out: &mut String, depth: usize| {
+        if let Some(n) = indent {
+            out.push('\n');
+            out.push_str(&" ".repeat(n * depth));
+        }
+    };
+    match value {
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(i) => {
+            let _ = write!(out, "{i}");
+        }
+        Value::Float(f) => {
+            if f.is_finite() {
+                // `{:?}` keeps a round-trippable representation (e.g. `1e-20`, `0.1`).
+                let _ = write!(out, "{f:?}");
+            } else {
+                out.push_str("null");
+            }
+        }
+        Value::Str(s) => write_quoted(out, s),
+        Value::Array(items) => {
+            if items.is_empty() {
+                out.push_str("[]");
+                return;
+            }
+            // Arrays of scalars stay on one line even in pretty mode.
+            let flat = items
+                .iter()
+                .all(|v| !matches!(v, Value::Array(_) | Value::Table(_)));
+            out.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                    if flat && indent.is_some() {
+                        out.push(' ');
+                    }
+                }
+                if !flat {
+                    newline(out, depth + 1);
+                }
+                write_json(out, item, indent, depth + 1);
+            }
+            if !flat {
+                newline(out, depth);
+            }
+            out.push(']');
+        }
+        Value::Table(entries) => {
+            if entries.is_empty() {
+                out.push_str("{}");
+                return;
+            }
+            out.push('{');
+            for (idx, (k, v)) in entries.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                write_quoted(out, k);
+                out.push(':');
+                if indent.is_some() {
+                    out.push(' ');
+                }
+                write_json(out, v, indent, depth + 1);
+            }
+            newline(out, depth);
+            out.push('}');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_json, to_json, Value};
+
+    #[test]
+    fn nan_round_trips_through_null() {
+        let mut root = Value::table();
+        root.set("mean", Value::Float(f64::NAN));
+        root.set("max", Value::Float(0.5));
+        let text = to_json(&root);
+        assert_eq!(text, r#"{"mean":null,"max":0.5}"#);
+        let back = parse_json(&text).unwrap();
+        assert!(matches!(back.get("mean"), Some(Value::Float(x)) if x.is_nan()));
+        assert!(matches!(back.get("max"), Some(Value::Float(x)) if *x == 0.5));
+    }
+}
@@ -0,0 +1,450 @@
+//! Test vectors: operand and result bit patterns of a [`SoftFloatSpec`] with
+//! the exception flags raised, for checking hardware or other float libraries
+//! against this model and the other way round.
+//!
+//! One vector per line, hexadecimal fields separated by spaces, `#` starting
+//! a comment:
+//!
+//! ```text
+//! # prectest test vectors
+//! # format fp16_like 10 -14 15
+//! # tininess before rounding
+//! # op rounding operands... result flags
+//! add rmm 3c00 3c00 4000 00
+//! sqrt rmm 4400 4000 00
+//! fma rmm 3c00 4000 bc00 3c00 00
+//! ```
+//!
+//! Bit patterns are laid out as in [`SoftFloatSpec::encode`]. Flags use the
+//! Berkeley SoftFloat bits: `01` inexact, `02` underflow, `04` overflow, `08`
+//! divide-by-zero, `10` invalid. The rounding modes are the RISC-V names:
+//! `rne`, `rtz`, `rdn`, `rup` and `rmm` (nearest, ties away from zero), the
+//! only one the model implements. The TestFloat layout (`testfloat_gen`
+//! output) drops the first two columns; the operation and rounding mode then
+//! apply to the whole file. The `# tininess` line says whether underflow was
+//! detected before or after rounding (see [`Tininess`]); without it, before.
+
+use crate::arith::Format;
+use crate::soft_float::{parse_format, Flags, SoftFloatSpec, Tininess};
+use crate::verify::Op;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    NearestEven,
+    TowardZero,
+    Down,
+    Up,
+    /// Nearest, ties away from zero: [`SoftFloatSpec::quantize`].
+    NearestAway,
+}
+
+impl RoundingMode {
+    pub const ALL: [RoundingMode; 5] = [
+        RoundingMode::NearestEven,
+        RoundingMode::TowardZero,
+        RoundingMode::Down,
+        RoundingMode::Up,
+        RoundingMode::NearestAway,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RoundingMode::NearestEven => "rne",
+            RoundingMode::TowardZero => "rtz",
+            RoundingMode::Down => "rdn",
+            RoundingMode::Up => "rup",
+            RoundingMode::NearestAway => "rmm",
+        }
+    }
+
+    /// Also accepts the TestFloat names (`near_even`, `minMag`, `min`, `max`, `near_maxMag`).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let testfloat = ["near_even", "minMag", "min", "max", "near_maxMag"];
+        RoundingMode::ALL
+            .into_iter()
+            .zip(testfloat)
+            .find(|(mode, alias)| mode.name() == s || *alias == s)
+            .map(|(mode, _)| mode)
+            .ok_or_else(|| {
+                format!("unknown rounding mode '{s}', expected rne, rtz, rdn, rup or rmm")
+            })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// `op rounding operands... result flags`.
+    Prectest,
+    /// `operands... result flags`.
+    TestFloat,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vector {
+    pub op: Op,
+    pub rounding: RoundingMode,
+    pub operands: Vec<u64>,
+    pub result: u64,
+    pub flags: Flags,
+}
+
+/// Formats the vectors can describe: bit patterns up to 64 bits of which at most 52 mantissa.
+fn check_spec(spec: &SoftFloatSpec) -> Result<(), String> {
+    if spec.mantissa_bits > 52 || spec.total_bits() > 64 {
+        return Err(format!(
+            "{} needs more than 64 bits or 52 mantissa bits; vectors support up to f64",
+            spec.name
+        ));
+    }
+    Ok(())
+}
+
+/// The model's result and flags for `inputs`.
+fn model(spec: &SoftFloatSpec, op: Op, inputs: &[f64], tininess: Tininess) -> (u64, Flags) {
+    let format = Format::Soft(spec.clone());
+    let (value, flags) = op.compute_flags(&format, inputs, tininess);
+    (spec.encode(value), flags)
+}
+
+/// Vectors for `op` over every combination of encodings (zeros, finite
+/// values, infinities and one NaN) when there are at most `count`,
+/// otherwise `count` random ones. The flag says which it was.
+pub fn generate(
+    spec: &SoftFloatSpec,
+    op: Op,
+    count: usize,
+    tininess: Tininess,
+) -> Result<(Vec<Vector>, bool), String> {
+    check_spec(spec)?;
+    let bits = spec.total_bits();
+    let codes: Vec<u64> = if bits <= 20 {
+        (0..1u64 << bits)
+            .filter(|&c| spec.decode(c).is_some_and(|x| spec.encode(x) == c))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let arity = op.arity() as u32;
+    let exhaustive = !codes.is_empty()
+        && (codes.len() as u64)
+            .checked_pow(arity)
+            .is_some_and(|n| n <= count as u64);
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut random_code = || loop {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let c = if codes.is_empty() {
+            state >> (64 - bits)
+        } else {
+            codes[(state % codes.len() as u64) as usize]
+        };
+        if spec.decode(c).is_some_and(|x| spec.encode(x) == c) {
+            return c;
+        }
+    };
+    let mut operand_sets: Vec<Vec<u64>> = Vec::new();
+    if exhaustive {
+        let n = codes.len();
+        let total = n.pow(arity);
+        for mut i in 0..total {
+            let mut set = Vec::with_capacity(arity as usize);
+            for _ in 0..arity {
+                set.push(codes[i % n]);
+                i /= n;
+            }
+            set.reverse();
+            operand_sets.push(set);
+        }
+    } else {
+        for _ in 0..count {
+            operand_sets.push((0..arity).map(|_| random_code()).collect());
+        }
+    }
+    let vectors = operand_sets
+        .into_iter()
+        .map(|operands| {
+            let inputs: Vec<f64> = operands
+                .iter()
+                .map(|&c| spec.decode(c).unwrap_or(f64::NAN))
+                .collect();
+            let (result, flags) = model(spec, op, &inputs, tininess);
+            Vector {
+                op,
+                rounding: RoundingMode::NearestAway,
+                operands,
+                result,
+                flags,
+            }
+        })
+        .collect();
+    Ok((vectors, exhaustive))
+}
+
+/// The vectors as text, with a header naming the format and tininess mode.
+pub fn write(
+    spec: &SoftFloatSpec,
+    vectors: &[Vector],
+    layout: Layout,
+    tininess: Tininess,
+) -> String {
+    let width = spec.total_bits().div_ceil(4) as usize;
+    let mut out = String::from("# prectest test vectors\n");
+    out.push_str(&format!(
+        "# format {} {} {} {}\n",
+        spec.name, spec.mantissa_bits, spec.min_exp2, spec.max_exp2
+    ));
+    out.push_str(&format!(
+        "# {} bits: sign 1, exponent {} (bias {}), mantissa {}\n",
+        spec.total_bits(),
+        spec.exponent_bits(),
+        1 - spec.min_exp2 as i64,
+        spec.mantissa_bits
+    ));
+    out.push_str(&format!("# tininess {} rounding\n", tininess.name()));
+    out.push_str("# flags: 01 inexact, 02 underflow, 04 overflow, 08 div-by-zero, 10 invalid\n");
+    match (layout, vectors.first()) {
+        (Layout::Prectest, _) => out.push_str("# op rounding operands... result flags\n"),
+        (Layout::TestFloat, Some(v)) => out.push_str(&format!(
+            "# {} {}: operands... result flags\n",
+            v.op.name(),
+            v.rounding.name()
+        )),
+        (Layout::TestFloat, None) => {}
+    }
+    for v in vectors {
+        if layout == Layout::Prectest {
+            out.push_str(&format!("{} {} ", v.op.name(), v.rounding.name()));
+        }
+        for c in &v.operands {
+            out.push_str(&format!("{c:0width$x} "));
+        }
+        out.push_str(&format!("{:0width$x} {:02x}\n", v.result, v.flags.0));
+    }
+    out
+}
+
+/// What [`parse`] needs besides the text.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    /// Overrides the `# format` header.
+    pub spec: Option<SoftFloatSpec>,
+    /// Overrides the `# tininess` header.
+    pub tininess: Option<Tininess>,
+    /// `None` detects the layout from the first vector.
+    pub layout: Option<Layout>,
+    /// Operation and rounding mode of TestFloat-layout files.
+    pub op: Option<Op>,
+    pub rounding: RoundingMode,
+}
+
+fn parse_hex(field: &str, what: &str, line: usize) -> Result<u64, String> {
+    u64::from_str_radix(field, 16)
+        .map_err(|_| format!("line {line}: invalid {what} '{field}', expected hexadecimal"))
+}
+
+/// A parsed vector file.
+#[derive(Clone, Debug)]
+pub struct VectorFile {
+    pub spec: SoftFloatSpec,
+    pub tininess: Tininess,
+    /// Vectors with their line numbers.
+    pub vectors: Vec<(usize, Vector)>,
+}
+
+/// Reads vectors with their line numbers, and the format they are in.
+pub fn parse(text: &str, options: &ParseOptions) -> Result<VectorFile, String> {
+    let mut spec = options.spec.clone();
+    let mut tininess = options.tininess;
+    let mut layout = options.layout;
+    let mut vectors = Vec::new();
+    for (idx, raw) in text.lines().enumerate() {
+        let line = idx + 1;
+        let raw = raw.trim();
+        if let Some(comment) = raw.strip_prefix('#') {
+            let words: Vec<&str> = comment.split_whitespace().collect();
+            if let ["format", name, m, lo, hi] = words[..] {
+                if spec.is_none() {
+                    let parsed = parse_format(&format!("{name},{m},{lo},{hi}"))
+                        .map_err(|e| format!("line {line}: {e}"))?;
+                    check_spec(&parsed)?;
+                    spec = Some(parsed);
+                }
+            }
+            if let ["tininess", mode, ..] = words[..] {
+                if tininess.is_none() {
+                    tininess =
+                        Some(Tininess::parse(mode).map_err(|e| format!("line {line}: {e}"))?);
+                }
+            }
+            continue;
+        }
+        let mut fields: Vec<&str> = raw.split_whitespace().collect();
+        if fields.is_empty() {
+            continue;
+        }
+        let layout = *layout.get_or_insert(if Op::parse(fields[0]).is_ok() {
+            Layout::Prectest
+        } else {
+            Layout::TestFloat
+        });
+        let (op, rounding) = match layout {
+            Layout::Prectest => {
+                if fields.len() < 2 {
+                    return Err(format!("line {line}: expected op and rounding mode"));
+                }
+                let op = Op::parse(fields[0]).map_err(|e| format!("line {line}: {e}"))?;
+                let rounding =
+                    RoundingMode::parse(fields[1]).map_err(|e| format!("line {line}: {e}"))?;
+                fields.drain(..2);
+                (op, rounding)
+            }
+            Layout::TestFloat => (
+                options.op.ok_or_else(|| {
+                    "TestFloat-layout vectors don't name the operation; pass --op".to_string()
+                })?,
+                options.rounding,
+            ),
+        };
+        let Some(spec) = &spec else {
+            return Err(format!(
+                "line {line}: no '# format' header before the first vector; pass --format"
+            ));
+        };
+        if fields.len() != op.arity() + 2 {
+            return Err(format!(
+                "line {line}: {} takes {} operand(s), then result and flags; found {} fields",
+                op.name(),
+                op.arity(),
+                fields.len()
+            ));
+        }
+        let mut codes = Vec::with_capacity(fields.len());
+        for (i, f) in fields.iter().enumerate() {
+            let what = if i < op.arity() { "operand" } else { "result" };
+            let c = parse_hex(f, what, line)?;
+            if i <= op.arity() && spec.decode(c).is_none() {
+                return Err(format!(
+                    "line {line}: '{f}' is not a valid {} encoding",
+                    spec.name
+                ));
+            }
+            codes.push(c);
+        }
+        let flags = codes.pop().unwrap_or(0);
+        let flags = u8::try_from(flags)
+            .ok()
+            .filter(|f| f & !0x1f == 0)
+            .ok_or_else(|| format!("line {line}: invalid flags '{flags:x}'"))?;
+        let result = codes.pop().unwrap_or(0);
+        vectors.push((
+            line,
+            Vector {
+                op,
+                rounding,
+                operands: codes,
+                result,
+                flags: Flags(flags),
+            },
+        ));
+    }
+    let spec = spec.ok_or_else(|| "no '# format' header; pass --format".to_string())?;
+    Ok(VectorFile {
+        spec,
+        tininess: tininess.unwrap_or(Tininess::BeforeRounding),
+        vectors,
+    })
+}
+
+/// A vector the model computes differently.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Disagreement {
+    pub line: usize,
+    pub vector: Vector,
+    pub result: u64,
+    pub flags: Flags,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Replay {
+    pub checked: u64,
+    /// Vectors in rounding modes the model does not implement.
+    pub skipped: u64,
+    pub result_mismatches: u64,
+    /// Same result, different flags.
+    pub flag_mismatches: u64,
+    /// The first disagreements, in file order.
+    pub disagreements: Vec<Disagreement>,
+}
+
+/// Recomputes every vector with the model. Results compare bit for bit,
+/// except that any NaN matches any NaN.
+pub fn replay(file: &VectorFile, shown: usize) -> Replay {
+    let spec = &file.spec;
+    let mut out = Replay::default();
+    for (line, v) in &file.vectors {
+        if v.rounding != RoundingMode::NearestAway {
+            out.skipped += 1;
+            continue;
+        }
+        out.checked += 1;
+        let inputs: Vec<f64> = v
+            .operands
+            .iter()
+            .map(|&c| spec.decode(c).unwrap_or(f64::NAN))
+            .collect();
+        let (result, flags) = model(spec, v.op, &inputs, file.tininess);
+        let both_nan = spec.decode(result).is_some_and(f64::is_nan)
+            && spec.decode(v.result).is_some_and(f64::is_nan);
+        if result != v.result && !both_nan {
+            out.result_mismatches += 1;
+        } else if flags != v.flags {
+            out.flag_mismatches += 1;
+        } else {
+            continue;
+        }
+        if out.disagreements.len() < shown {
+            out.disagreements.push(Disagreement {
+                line: *line,
+                vector: v.clone(),
+                result,
+                flags,
+            });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, parse, replay, write, Layout, ParseOptions, RoundingMode};
+    use crate::soft_float::{preset, Tininess};
+    use crate::verify::Op;
+
+    #[test]
+    fn tininess_round_trips_through_the_header() {
+        let spec = preset("tiny8").unwrap();
+        let (vectors, exhaustive) =
+            generate(&spec, Op::Mul, 100_000, Tininess::AfterRounding).unwrap();
+        assert!(exhaustive);
+        let text = write(&spec, &vectors, Layout::Prectest, Tininess::AfterRounding);
+        let mut options = ParseOptions {
+            spec: None,
+            tininess: None,
+            layout: None,
+            op: None,
+            rounding: RoundingMode::NearestAway,
+        };
+        let file = parse(&text, &options).unwrap();
+        assert_eq!(file.tininess, Tininess::AfterRounding);
+        assert_eq!(file.vectors.len(), vectors.len());
+        let result = replay(&file, 0);
+        assert_eq!(result.checked, vectors.len() as u64);
+        assert_eq!(result.result_mismatches + result.flag_mismatches, 0);
+
+        options.tininess = Some(Tininess::BeforeRounding);
+        let result = replay(&parse(&text, &options).unwrap(), 0);
+        assert_eq!(result.result_mismatches, 0);
+        assert!(result.flag_mismatches > 0);
+    }
+}
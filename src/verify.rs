@@ -0,0 +1,899 @@
+//! Checks the soft float operations in [`crate::arith`] against an
+//! independent, correctly rounded reference, over every input for small formats.
+//!
+//! The reference works on exact integers: each value is `±m·2^e`, sums and
+//! products are formed exactly in `u128`, quotients and square roots keep a
+//! sticky bit for the discarded remainder, and a single rounding to nearest
+//! produces the result. The tie rule and underflow are configurable:
+//! [`Reference::IEEE`] is what fp16/bf16 hardware does, [`Reference::MODEL`]
+//! is the documented behaviour of [`SoftFloatSpec::quantize`] (ties away from
+//! zero, no subnormals), against which any mismatch is a bug. Where the
+//! results agree, the exception flags are compared too.
+
+use crate::arith::Format;
+use crate::fixed_point::all_values;
+use crate::soft_float::{Flags, SoftFloatSpec, Tininess};
+use std::ops::Range;
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Mul,
+    Div,
+    Fma,
+    Sqrt,
+}
+
+impl Op {
+    pub const ALL: [Op; 5] = [Op::Add, Op::Mul, Op::Div, Op::Fma, Op::Sqrt];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Op::Add => "add",
+            Op::Mul => "mul",
+            Op::Div => "div",
+            Op::Fma => "fma",
+            Op::Sqrt => "sqrt",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Op::ALL
+            .into_iter()
+            .find(|op| op.name() == s)
+            .ok_or_else(|| format!("unknown operation '{s}', expected add, mul, div, fma or sqrt"))
+    }
+
+    pub fn arity(self) -> usize {
+        match self {
+            Op::Sqrt => 1,
+            Op::Fma => 3,
+            _ => 2,
+        }
+    }
+
+    /// The operation as implemented by `format`.
+    pub fn compute(self, format: &Format, x: &[f64]) -> f64 {
+        match self {
+            Op::Add => format.add(x[0], x[1]),
+            Op::Mul => format.mul(x[0], x[1]),
+            Op::Div => format.div(x[0], x[1]),
+            Op::Fma => format.fma(x[0], x[1], x[2]),
+            Op::Sqrt => format.sqrt(x[0]),
+        }
+    }
+
+    /// [`Self::compute`] with the exception flags it raises.
+    pub fn compute_flags(self, format: &Format, x: &[f64], tininess: Tininess) -> (f64, Flags) {
+        match self {
+            Op::Add => format.add_flags(x[0], x[1], tininess),
+            Op::Mul => format.mul_flags(x[0], x[1], tininess),
+            Op::Div => format.div_flags(x[0], x[1], tininess),
+            Op::Fma => format.fma_flags(x[0], x[1], x[2], tininess),
+            Op::Sqrt => format.sqrt_flags(x[0], tininess),
+        }
+    }
+
+    /// The operation written out, e.g. `a * b + c`.
+    pub fn describe(self, x: &[f64]) -> String {
+        match self {
+            Op::Add => format!("{:e} + {:e}", x[0], x[1]),
+            Op::Mul => format!("{:e} * {:e}", x[0], x[1]),
+            Op::Div => format!("{:e} / {:e}", x[0], x[1]),
+            Op::Fma => format!("{:e} * {:e} + {:e}", x[0], x[1], x[2]),
+            Op::Sqrt => format!("sqrt({:e})", x[0]),
+        }
+    }
+
+    fn exact(self, x: &[Exact]) -> Exact {
+        match self {
+            Op::Add => add(x[0], x[1]),
+            Op::Mul => mul(x[0], x[1]),
+            Op::Div => div(x[0], x[1]),
+            Op::Fma => add(mul(x[0], x[1]), x[2]),
+            Op::Sqrt => sqrt(x[0]),
+        }
+    }
+}
+
+/// Which way the reference rounds exact halfway cases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ties {
+    /// IEEE 754 default: to the value with an even last mantissa bit.
+    Even,
+    /// Away from zero, as `quantize` does.
+    Away,
+}
+
+/// How the reference treats results below the smallest normal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Underflow {
+    /// IEEE 754 subnormals: the quantum stops shrinking at `2^(min_exp2 - m)`.
+    Gradual,
+    /// Exact results below `2^min_exp2` become zero, as in the soft float model.
+    Flush,
+}
+
+/// The rounding the implementation is compared with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reference {
+    pub ties: Ties,
+    pub underflow: Underflow,
+    /// When the underflow flag counts a result as tiny; the values do not depend on it.
+    pub tininess: Tininess,
+}
+
+impl Reference {
+    pub const IEEE: Reference = Reference {
+        ties: Ties::Even,
+        underflow: Underflow::Gradual,
+        tininess: Tininess::BeforeRounding,
+    };
+    pub const MODEL: Reference = Reference {
+        ties: Ties::Away,
+        underflow: Underflow::Flush,
+        tininess: Tininess::BeforeRounding,
+    };
+
+    pub fn describe(&self) -> String {
+        let ties = match self.ties {
+            Ties::Even => "ties to even",
+            Ties::Away => "ties away from zero",
+        };
+        let underflow = match self.underflow {
+            Underflow::Gradual => "subnormals",
+            Underflow::Flush => "flush to zero below the smallest normal",
+        };
+        format!(
+            "round to nearest, {ties}, {underflow}, tininess {} rounding",
+            self.tininess.name()
+        )
+    }
+}
+
+/// Why the implementation and the reference disagree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// The exact result is halfway between two values and the tie went the other way.
+    Tie,
+    /// The exact result is below the smallest normal.
+    Underflow,
+    /// One side overflowed to infinity.
+    Overflow,
+    /// Both are zero with different signs.
+    SignedZero,
+    /// Same result, different exception flags.
+    Flags,
+    Other,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 6] = [
+        Kind::Tie,
+        Kind::Underflow,
+        Kind::Overflow,
+        Kind::SignedZero,
+        Kind::Flags,
+        Kind::Other,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Tie => "tie",
+            Kind::Underflow => "underflow",
+            Kind::Overflow => "overflow",
+            Kind::SignedZero => "signed zero",
+            Kind::Flags => "flags",
+            Kind::Other => "other",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    pub inputs: Vec<f64>,
+    /// The implementation's result.
+    pub got: f64,
+    /// The correctly rounded result.
+    pub expected: f64,
+    pub got_flags: Flags,
+    pub expected_flags: Flags,
+    pub kind: Kind,
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub format: Format,
+    pub op: Op,
+    pub reference: Reference,
+    /// Every input combination was checked, rather than a random sample.
+    pub exhaustive: bool,
+    pub cases: u64,
+    pub mismatches: u64,
+    /// Mismatch counts indexed like [`Kind::ALL`].
+    pub by_kind: [u64; 6],
+    /// The first few mismatches of each kind, in input order.
+    pub examples: Vec<Mismatch>,
+}
+
+impl Report {
+    pub fn count(&self, kind: Kind) -> u64 {
+        self.by_kind[kind.index()]
+    }
+}
+
+/// An exact value: `±(mant + sticky)·2^exp`, where `sticky` stands for a
+/// nonzero remainder below the last bit of `mant`.
+#[derive(Clone, Copy, Debug)]
+enum Exact {
+    Finite {
+        neg: bool,
+        mant: u128,
+        exp: i32,
+        sticky: bool,
+    },
+    Inf(bool),
+    Nan,
+}
+
+impl Exact {
+    fn zero(neg: bool) -> Self {
+        Exact::Finite {
+            neg,
+            mant: 0,
+            exp: 0,
+            sticky: false,
+        }
+    }
+
+    fn from_f64(x: f64) -> Self {
+        if x.is_nan() {
+            return Exact::Nan;
+        }
+        let neg = x.is_sign_negative();
+        if x.is_infinite() {
+            return Exact::Inf(neg);
+        }
+        if x == 0.0 {
+            return Exact::zero(neg);
+        }
+        let bits = x.to_bits();
+        let field = ((bits >> 52) & 0x7ff) as i32;
+        let frac = bits & ((1u64 << 52) - 1);
+        let (mant, exp) = if field == 0 {
+            (frac, -1074)
+        } else {
+            (frac | 1 << 52, field - 1075)
+        };
+        let tz = mant.trailing_zeros();
+        Exact::Finite {
+            neg,
+            mant: (mant >> tz) as u128,
+            exp: exp + tz as i32,
+            sticky: false,
+        }
+    }
+}
+
+fn bit_len(m: u128) -> i32 {
+    128 - m.leading_zeros() as i32
+}
+
+/// `m·2^e` as an integer at exponent `t`, and whether bits were shifted out.
+fn align(m: u128, e: i32, t: i32) -> (u128, bool) {
+    if e >= t {
+        (m << (e - t), false)
+    } else {
+        let s = (t - e) as u32;
+        if s >= 128 {
+            (0, m != 0)
+        } else {
+            (m >> s, m & ((1u128 << s) - 1) != 0)
+        }
+    }
+}
+
+/// Exact sum of two exact (non-sticky) values. Operands more than 120 bits
+/// below the larger one keep only a sticky bit, far below any rounding position.
+fn add(a: Exact, b: Exact) -> Exact {
+    let (
+        Exact::Finite {
+            neg: na,
+            mant: ma,
+            exp: ea,
+            ..
+        },
+        Exact::Finite {
+            neg: nb,
+            mant: mb,
+            exp: eb,
+            ..
+        },
+    ) = (a, b)
+    else {
+        return match (a, b) {
+            (Exact::Nan, _) | (_, Exact::Nan) => Exact::Nan,
+            (Exact::Inf(x), Exact::Inf(y)) if x != y => Exact::Nan,
+            (Exact::Inf(x), _) | (_, Exact::Inf(x)) => Exact::Inf(x),
+            _ => unreachable!(),
+        };
+    };
+    if ma == 0 && mb == 0 {
+        return Exact::zero(na && nb);
+    }
+    if mb == 0 {
+        return a;
+    }
+    if ma == 0 {
+        return b;
+    }
+    let top = (ea + bit_len(ma)).max(eb + bit_len(mb)) - 1;
+    let t = ea.min(eb).max(top - 120);
+    let (x, fx) = align(ma, ea, t);
+    let (y, fy) = align(mb, eb, t);
+    if na == nb {
+        return Exact::Finite {
+            neg: na,
+            mant: x + y,
+            exp: t,
+            sticky: fx || fy,
+        };
+    }
+    // At most one side lost bits, and it is the far smaller one.
+    let ((big, neg), (small, small_frac)) = if (x, fx) > (y, fy) {
+        ((x, na), (y, fy))
+    } else {
+        ((y, nb), (x, fx))
+    };
+    let mant = big - small - small_frac as u128;
+    if mant == 0 && !(fx || fy) {
+        // Exact cancellation gives +0 under round to nearest.
+        return Exact::zero(false);
+    }
+    Exact::Finite {
+        neg,
+        mant,
+        exp: t,
+        sticky: fx || fy,
+    }
+}
+
+fn mul(a: Exact, b: Exact) -> Exact {
+    match (a, b) {
+        (Exact::Nan, _) | (_, Exact::Nan) => Exact::Nan,
+        (Exact::Inf(_), Exact::Finite { mant: 0, .. })
+        | (Exact::Finite { mant: 0, .. }, Exact::Inf(_)) => Exact::Nan,
+        (Exact::Inf(x), Exact::Inf(y)) => Exact::Inf(x != y),
+        (Exact::Inf(x), Exact::Finite { neg, .. }) | (Exact::Finite { neg, .. }, Exact::Inf(x)) => {
+            Exact::Inf(x != neg)
+        }
+        (
+            Exact::Finite {
+                neg: na,
+                mant: ma,
+                exp: ea,
+                ..
+            },
+            Exact::Finite {
+                neg: nb,
+                mant: mb,
+                exp: eb,
+                ..
+            },
+        ) => Exact::Finite {
+            neg: na != nb,
+            mant: ma * mb,
+            exp: if ma * mb == 0 { 0 } else { ea + eb },
+            sticky: false,
+        },
+    }
+}
+
+fn div(a: Exact, b: Exact) -> Exact {
+    match (a, b) {
+        (Exact::Nan, _) | (_, Exact::Nan) | (Exact::Inf(_), Exact::Inf(_)) => Exact::Nan,
+        (Exact::Inf(x), Exact::Finite { neg, .. }) => Exact::Inf(x != neg),
+        (Exact::Finite { neg, .. }, Exact::Inf(y)) => Exact::zero(neg != y),
+        (Exact::Finite { mant: 0, .. }, Exact::Finite { mant: 0, .. }) => Exact::Nan,
+        (
+            Exact::Finite { neg: na, .. },
+            Exact::Finite {
+                neg: nb, mant: 0, ..
+            },
+        ) => Exact::Inf(na != nb),
+        (
+            Exact::Finite {
+                neg: na, mant: 0, ..
+            },
+            Exact::Finite { neg: nb, .. },
+        ) => Exact::zero(na != nb),
+        (
+            Exact::Finite {
+                neg: na,
+                mant: ma,
+                exp: ea,
+                ..
+            },
+            Exact::Finite {
+                neg: nb,
+                mant: mb,
+                exp: eb,
+                ..
+            },
+        ) => {
+            // Widen the dividend so the quotient keeps at least 70 bits.
+            let k = 127 - bit_len(ma);
+            let n = ma << k;
+            Exact::Finite {
+                neg: na != nb,
+                mant: n / mb,
+                exp: ea - k - eb,
+                sticky: !n.is_multiple_of(mb),
+            }
+        }
+    }
+}
+
+fn sqrt(a: Exact) -> Exact {
+    match a {
+        Exact::Finite { mant: 0, .. } | Exact::Nan | Exact::Inf(false) => a,
+        Exact::Finite { neg: true, .. } | Exact::Inf(true) => Exact::Nan,
+        Exact::Finite { mant, exp, .. } => {
+            let mut k = 126 - bit_len(mant);
+            if (exp - k) % 2 != 0 {
+                k -= 1;
+            }
+            let n = mant << k;
+            let root = n.isqrt();
+            Exact::Finite {
+                neg: false,
+                mant: root,
+                exp: (exp - k) / 2,
+                sticky: root * root != n,
+            }
+        }
+    }
+}
+
+/// `2^k` for `-1074 <= k <= 1023`.
+fn pow2(k: i32) -> f64 {
+    if k < -1022 {
+        f64::from_bits(1u64 << (k + 1074))
+    } else {
+        f64::from_bits(((k + 1023) as u64) << 52)
+    }
+}
+
+/// A correctly rounded reference result and what it took to get there.
+struct Rounded {
+    value: f64,
+    tie: bool,
+    tiny: bool,
+    /// Inexact, underflow (tininess before rounding) and overflow.
+    flags: Flags,
+}
+
+/// Rounds to nearest into `spec`.
+fn round(x: Exact, spec: &SoftFloatSpec, reference: Reference) -> Rounded {
+    let plain = |value| Rounded {
+        value,
+        tie: false,
+        tiny: false,
+        flags: Flags::NONE,
+    };
+    let (neg, mant, exp, sticky) = match x {
+        Exact::Nan => return plain(f64::NAN),
+        Exact::Inf(neg) => {
+            return plain(if neg {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            })
+        }
+        Exact::Finite { neg, mant: 0, .. } => return plain(if neg { -0.0 } else { 0.0 }),
+        Exact::Finite {
+            neg,
+            mant,
+            exp,
+            sticky,
+        } => (neg, mant, exp, sticky),
+    };
+    let signed = |v: f64| if neg { -v } else { v };
+    let m = spec.mantissa_bits as i32;
+    let lead = exp + bit_len(mant) - 1;
+    let tiny = lead < spec.min_exp2;
+    let underflows = tiny
+        && !(reference.tininess == Tininess::AfterRounding
+            && rounds_to_normal(mant, exp, sticky, spec, reference.ties));
+    if tiny && reference.underflow == Underflow::Flush {
+        let flags = if underflows {
+            Flags::UNDERFLOW | Flags::INEXACT
+        } else {
+            Flags::INEXACT
+        };
+        return Rounded {
+            value: signed(0.0),
+            tie: false,
+            tiny,
+            flags,
+        };
+    }
+    let q = lead.max(spec.min_exp2) - m;
+    let (mut r, tie, inexact) = if q <= exp {
+        // Exact values only: a sticky remainder always sits below the rounding position.
+        (mant << (exp - q), false, false)
+    } else {
+        let s = (q - exp) as u32;
+        let (r, rem, half) = if s >= 128 {
+            (0, mant, u128::MAX)
+        } else {
+            (mant >> s, mant & ((1u128 << s) - 1), 1u128 << (s - 1))
+        };
+        let tie = rem == half && !sticky;
+        let up = rem > half
+            || (rem == half && sticky)
+            || (tie && (reference.ties == Ties::Away || r & 1 == 1));
+        (r + up as u128, tie, rem != 0 || sticky)
+    };
+    let mut q = q;
+    if r >> (m + 1) != 0 {
+        // Rounded up to the next power of two.
+        r >>= 1;
+        q += 1;
+    }
+    let mut flags = if inexact { Flags::INEXACT } else { Flags::NONE };
+    if underflows && inexact {
+        flags |= Flags::UNDERFLOW;
+    }
+    let rounded = Rounded {
+        value: 0.0,
+        tie,
+        tiny,
+        flags,
+    };
+    if r == 0 {
+        return Rounded {
+            value: signed(0.0),
+            ..rounded
+        };
+    }
+    let result_lead = q + bit_len(r) - 1;
+    if result_lead > spec.max_exp2 {
+        return Rounded {
+            value: signed(f64::INFINITY),
+            flags: flags | Flags::OVERFLOW | Flags::INEXACT,
+            ..rounded
+        };
+    }
+    Rounded {
+        value: signed(r as f64 * pow2(q)),
+        ..rounded
+    }
+}
+
+/// Whether `mant·2^exp`, just below `2^min_exp2`, rounds up to it at full
+/// precision with an unbounded exponent (tininess after rounding).
+fn rounds_to_normal(mant: u128, exp: i32, sticky: bool, spec: &SoftFloatSpec, ties: Ties) -> bool {
+    let m = spec.mantissa_bits as i32;
+    let lead = exp + bit_len(mant) - 1;
+    let q = lead - m;
+    if lead != spec.min_exp2 - 1 || q <= exp {
+        return false;
+    }
+    let s = (q - exp) as u32;
+    let (r, rem, half) = (mant >> s, mant & ((1u128 << s) - 1), 1u128 << (s - 1));
+    let up = rem > half || (rem == half && (sticky || ties == Ties::Away || r & 1 == 1));
+    up && (r + 1) >> (m + 1) != 0
+}
+
+/// The correctly rounded result of `op` on `inputs` (values of `spec`, or
+/// infinities and NaN) with the exception flags it raises. NaN inputs are
+/// quiet: they propagate without raising invalid.
+pub fn correctly_rounded(
+    spec: &SoftFloatSpec,
+    op: Op,
+    inputs: &[f64],
+    reference: Reference,
+) -> (f64, Flags) {
+    if inputs.iter().any(|x| x.is_nan()) {
+        return (f64::NAN, Flags::NONE);
+    }
+    let exact: Vec<Exact> = inputs.iter().map(|&x| Exact::from_f64(x)).collect();
+    let result = op.exact(&exact);
+    let rounded = round(result, spec, reference);
+    (rounded.value, special_flags(result, inputs) | rounded.flags)
+}
+
+/// Invalid for a NaN from non-NaN inputs, div-by-zero for an infinity from finite ones.
+fn special_flags(result: Exact, inputs: &[f64]) -> Flags {
+    match result {
+        Exact::Nan if inputs.iter().any(|x| x.is_nan()) => Flags::NONE,
+        Exact::Nan => Flags::INVALID,
+        Exact::Inf(_) if inputs.iter().all(|x| x.is_finite()) => Flags::DIV_BY_ZERO,
+        _ => Flags::NONE,
+    }
+}
+
+/// Both NaN, or the same bits (so `0.0` and `-0.0` differ).
+fn same(a: f64, b: f64) -> bool {
+    (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+}
+
+fn classify(got: f64, reference: &Rounded) -> Kind {
+    let expected = reference.value;
+    if got == 0.0 && expected == 0.0 {
+        Kind::SignedZero
+    } else if reference.tiny {
+        Kind::Underflow
+    } else if got.is_infinite() || expected.is_infinite() {
+        Kind::Overflow
+    } else if reference.tie {
+        Kind::Tie
+    } else {
+        Kind::Other
+    }
+}
+
+/// Counts and examples from one slice of the cases.
+struct Tally {
+    cases: u64,
+    by_kind: [u64; 6],
+    examples: Vec<Mismatch>,
+}
+
+impl Tally {
+    fn new() -> Self {
+        Tally {
+            cases: 0,
+            by_kind: [0; 6],
+            examples: Vec::new(),
+        }
+    }
+
+    fn merge(&mut self, other: Tally, examples: usize) {
+        self.cases += other.cases;
+        for (a, b) in self.by_kind.iter_mut().zip(other.by_kind) {
+            *a += b;
+        }
+        for e in other.examples {
+            if self.examples.iter().filter(|x| x.kind == e.kind).count() < examples {
+                self.examples.push(e);
+            }
+        }
+    }
+}
+
+/// The settings shared by every case of one run.
+struct Check<'a> {
+    format: &'a Format,
+    spec: &'a SoftFloatSpec,
+    op: Op,
+    reference: Reference,
+    values: &'a [f64],
+    exact: &'a [Exact],
+    examples: usize,
+}
+
+impl Check<'_> {
+    fn run(&self, indices: &[usize], tally: &mut Tally) {
+        let mut inputs = [0.0; 3];
+        let mut exact = [Exact::Nan; 3];
+        for (k, &i) in indices.iter().enumerate() {
+            inputs[k] = self.values[i];
+            exact[k] = self.exact[i];
+        }
+        let n = indices.len();
+        let inputs = &inputs[..n];
+        let (got, got_flags) = self
+            .op
+            .compute_flags(self.format, inputs, self.reference.tininess);
+        let result = self.op.exact(&exact[..n]);
+        let reference = round(result, self.spec, self.reference);
+        let expected_flags = special_flags(result, inputs) | reference.flags;
+        tally.cases += 1;
+        let kind = if !same(got, reference.value) {
+            classify(got, &reference)
+        } else if got_flags != expected_flags {
+            Kind::Flags
+        } else {
+            return;
+        };
+        tally.by_kind[kind.index()] += 1;
+        if tally.by_kind[kind.index()] <= self.examples as u64 {
+            tally.examples.push(Mismatch {
+                inputs: inputs.to_vec(),
+                got,
+                expected: reference.value,
+                got_flags,
+                expected_flags,
+                kind,
+            });
+        }
+    }
+
+    /// Every combination whose first input index is in `first`.
+    fn exhaustive(&self, first: Range<usize>) -> Tally {
+        let mut tally = Tally::new();
+        let n = self.values.len();
+        for i in first {
+            match self.op.arity() {
+                1 => self.run(&[i], &mut tally),
+                2 => (0..n).for_each(|j| self.run(&[i, j], &mut tally)),
+                _ => {
+                    for j in 0..n {
+                        (0..n).for_each(|k| self.run(&[i, j, k], &mut tally));
+                    }
+                }
+            }
+        }
+        tally
+    }
+
+    /// `count` random combinations from the stream seeded by `seed`.
+    fn sampled(&self, count: u64, seed: u64) -> Tally {
+        let mut tally = Tally::new();
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        let n = self.values.len() as u64;
+        let mut indices = [0usize; 3];
+        for _ in 0..count {
+            for slot in indices.iter_mut().take(self.op.arity()) {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                *slot = (state % n) as usize;
+            }
+            self.run(&indices[..self.op.arity()], &mut tally);
+        }
+        tally
+    }
+}
+
+/// Checks `op` in `spec` over every input combination when there are at most
+/// `limit` of them, otherwise over `samples` random ones. Inputs are zero of
+/// both signs, every finite value, both infinities and a NaN; work is spread
+/// over all cores.
+pub fn verify(
+    spec: &SoftFloatSpec,
+    op: Op,
+    reference: Reference,
+    limit: u64,
+    samples: u64,
+    examples: usize,
+) -> Result<Report, String> {
+    if spec.mantissa_bits > 52 || spec.min_exp2 < -1022 || spec.max_exp2 > 1023 {
+        return Err(format!(
+            "{} does not fit in an f64, which the soft float arithmetic needs",
+            spec.name
+        ));
+    }
+    let mut values = all_values(spec, 1 << 26).ok_or_else(|| {
+        format!(
+            "{} has more than 2^26 values; verify samples only smaller formats",
+            spec.name
+        )
+    })?;
+    values.extend([-0.0, f64::INFINITY, f64::NEG_INFINITY, f64::NAN]);
+    let exact: Vec<Exact> = values.iter().map(|&x| Exact::from_f64(x)).collect();
+    let format = Format::Soft(spec.clone());
+    let check = Check {
+        format: &format,
+        spec,
+        op,
+        reference,
+        values: &values,
+        exact: &exact,
+        examples,
+    };
+    let n = values.len();
+    let cases = (n as u64)
+        .checked_pow(op.arity() as u32)
+        .unwrap_or(u64::MAX);
+    let exhaustive = cases <= limit;
+    let threads = thread::available_parallelism().map_or(1, |t| t.get());
+    let tallies: Vec<Tally> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let check = &check;
+                scope.spawn(move || {
+                    if exhaustive {
+                        check.exhaustive(n * t / threads..n * (t + 1) / threads)
+                    } else {
+                        let share =
+                            samples / threads as u64 + (samples % threads as u64 > t as u64) as u64;
+                        check.sampled(share, t as u64 + 1)
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("verify worker panicked"))
+            .collect()
+    });
+    let mut total = Tally::new();
+    for tally in tallies {
+        total.merge(tally, examples);
+    }
+    Ok(Report {
+        format,
+        op,
+        reference,
+        exhaustive,
+        cases: total.cases,
+        mismatches: total.by_kind.iter().sum(),
+        by_kind: total.by_kind,
+        examples: total.examples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{correctly_rounded, verify, Op, Reference};
+    use crate::arith::Format;
+    use crate::soft_float::{preset, Flags, Tininess};
+
+    #[test]
+    fn model_matches_the_reference_in_both_tininess_modes() {
+        let spec = preset("tiny8").unwrap();
+        for tininess in [Tininess::BeforeRounding, Tininess::AfterRounding] {
+            let reference = Reference {
+                tininess,
+                ..Reference::MODEL
+            };
+            for op in [Op::Add, Op::Mul, Op::Div, Op::Sqrt] {
+                let report = verify(&spec, op, reference, 1 << 20, 0, 1).unwrap();
+                assert!(report.exhaustive);
+                assert_eq!(report.mismatches, 0, "{} {tininess:?}", op.name());
+            }
+        }
+    }
+
+    #[test]
+    fn special_values_are_checked() {
+        let spec = preset("tiny8").unwrap();
+        let finite = spec.positive_values(1 << 10).unwrap().len() as u64 * 2 + 1;
+        let report = verify(&spec, Op::Mul, Reference::MODEL, 1 << 20, 0, 1).unwrap();
+        assert_eq!(report.cases, (finite + 4).pow(2));
+
+        let format = Format::Soft(spec.clone());
+        let inf = f64::INFINITY;
+        for (op, inputs, flags) in [
+            (Op::Add, [inf, -inf], Flags::INVALID),
+            (Op::Mul, [0.0, -inf], Flags::INVALID),
+            (Op::Div, [inf, inf], Flags::INVALID),
+            (Op::Div, [-1.0, 0.0], Flags::DIV_BY_ZERO),
+            (Op::Mul, [inf, -2.0], Flags::NONE),
+            (Op::Add, [f64::NAN, 1.0], Flags::NONE),
+        ] {
+            let expected = correctly_rounded(&spec, op, &inputs, Reference::MODEL);
+            let got = op.compute_flags(&format, &inputs, Tininess::BeforeRounding);
+            assert_eq!(expected.1, flags, "{}", op.describe(&inputs));
+            assert_eq!(got.1, flags, "{}", op.describe(&inputs));
+            assert_eq!(got.0.is_nan(), expected.0.is_nan());
+            assert!(got.0.is_nan() || got.0 == expected.0);
+        }
+    }
+
+    #[test]
+    fn tininess_changes_flags_not_values() {
+        let spec = preset("tiny8").unwrap();
+        let format = Format::Soft(spec.clone());
+        let values = spec.positive_values(1 << 10).unwrap();
+        let mut differ = 0;
+        for &a in &values {
+            for &b in &values {
+                let before = format.mul_flags(a, b, Tininess::BeforeRounding);
+                let after = format.mul_flags(a, b, Tininess::AfterRounding);
+                assert_eq!(before.0.to_bits(), after.0.to_bits());
+                assert_eq!(before.0.to_bits(), format.mul(a, b).to_bits());
+                differ += (before.1 != after.1) as u32;
+            }
+        }
+        assert!(differ > 0);
+    }
+}